@@ -1,5 +1,8 @@
+use super::format::FmtSegment;
 use crate::utils::PtrString;
 
+use std::rc::Rc;
+
 /// The bytecode instructions, generated by the `codegen` of the interpeter and
 /// executed by the `CVM`.
 #[derive(Debug, Clone, PartialEq)]
@@ -23,6 +26,11 @@ pub enum Bytecode {
     ConstF(f64),
     /// Pushes `CvmObject::Str()` on the top of the stack.
     ConstS(PtrString),
+    /// Pushes the `CvmObject::Str()` with the given id from the `CVM`'s
+    /// constant pool on the top of the stack. The `ConstS` instructions are
+    /// replaced with it when the program is loaded inside the `CVM`, so the
+    /// string is not copied along with the instruction.
+    LoadStr(usize),
     /// Pushes `CvmObject::Bool()` on the top of the stack.
     ConstB(bool),
     /// Pops the top N elements off the stack and builds an Object out of them.
@@ -66,6 +74,47 @@ pub enum Bytecode {
     LtEq,
     GtEq,
 
+    /// The binary operators, specialized for operands of the same statically
+    /// known type - `I` for `CvmObject::Int()`, `U` for `CvmObject::Uint()`
+    /// and `F` for `CvmObject::Float()`. If the operands' types differ at
+    /// runtime, the generic operator is applied instead.
+    AddI,
+    SubI,
+    MulI,
+    DivI,
+    ModI,
+    LtI,
+    GtI,
+    EqI,
+    LtEqI,
+    GtEqI,
+
+    AddU,
+    SubU,
+    MulU,
+    DivU,
+    ModU,
+    LtU,
+    GtU,
+    EqU,
+    LtEqU,
+    GtEqU,
+
+    AddF,
+    SubF,
+    MulF,
+    DivF,
+    ModF,
+    LtF,
+    GtF,
+    EqF,
+    LtEqF,
+    GtEqF,
+
+    /// Pops the top 2 operators off the stack and pushes back their
+    /// concatenation, where the left operand is a `CvmObject::Str()`.
+    ConcatS,
+
     /// Pops the top element off the stack, performs the corresponding unary
     /// operation and pushes back the resulting value on the stack.
     Neg,
@@ -92,6 +141,15 @@ pub enum Bytecode {
     /// Calls the function with the given id. The function's arguments must
     /// already be present at the top N positions on the stack.
     CallFunc(usize),
+    /// Calls the function with the given id in place of the current function,
+    /// reusing its call frame. The function's arguments must already be
+    /// present at the top N positions on the stack. Used for calls in a tail
+    /// position, i.e. `return f(...)`.
+    TailCall(usize),
+    /// Calls the host function with the given id, registered via
+    /// `Chalcedony::register_fn()`. The function's arguments must already be
+    /// present at the top N positions on the stack.
+    CallNative(usize),
 
     /// Both operations remove the top call frame from the CVM call stack and
     /// truncate the remaing stack length to `call_frame.stack_length`. The
@@ -134,6 +192,87 @@ pub enum Bytecode {
 
     /// Pops the top value off the stack and outputs it to `stdout`.
     Print,
+    /// Pops the prompt at the top of the stack, outputs it to `stdout` and
+    /// pushes the next line from `stdin` (without the trailing newline).
+    Input,
     /// Asserts the top of the stack is true, else an exception is thrown.
     Assert,
+    /// Pops the `CvmObject::Int()` at the top of the stack and stops the
+    /// `CVM`, recording it as the exit code, which the host reads via
+    /// `Cvm::exit_code()`. Codes outside of the `i32` range are clamped.
+    Exit,
+
+    /// Runs the garbage collector and pushes the number of freed objects as a
+    /// `CvmObject::Uint()`.
+    GcCollect,
+    /// Pushes a `CvmObject::Str()`, describing the state of the `CVM`'s heap.
+    GcStats,
+
+    /// Pops the function's arguments off the stack and pushes back the result
+    /// of the corresponding mathematical function.
+    Math(MathFn),
+
+    /// Pops the `CvmObject::Str()` at the top of the stack and pushes back the
+    /// object it describes, shaped after the schema. If the JSON is malformed
+    /// or does not match the schema an exception is thrown.
+    JsonParse(Rc<JsonSchema>),
+    /// Pops the top of the stack and pushes back its JSON representation as a
+    /// `CvmObject::Str()`.
+    JsonStringify(Rc<JsonSchema>),
+
+    /// Pops an argument for each placeholder in the parsed format string and
+    /// pushes back the formatted `CvmObject::Str()`.
+    Format(Rc<[FmtSegment]>),
+}
+
+/// The mathematical functions, natively implemented inside the `CVM`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MathFn {
+    /// Operate on a `CvmObject::Float()` and return a `CvmObject::Float()`.
+    Sqrt,
+    Exp,
+    Ln,
+    Log10,
+    Sin,
+    Cos,
+    Tan,
+    Asin,
+    Acos,
+    Atan,
+    /// Operate on a `CvmObject::Float()` and return a `CvmObject::Int()`,
+    /// saturating at the bounds of `int` (`NaN` results in 0).
+    Floor,
+    Ceil,
+    Round,
+    /// Operate on a `CvmObject::Float()` and return a `CvmObject::Bool()`.
+    IsNan,
+    IsInf,
+    /// Operate on numeric values of the same type, except for `Pow`, whose
+    /// integer exponent is always a `CvmObject::Uint()`. The integer results
+    /// saturate at the bounds of their type.
+    Pow,
+    /// Same as `Pow`, but an integer overflow raises an exception.
+    CheckedPow,
+    Abs,
+    Min,
+    Max,
+    Clamp,
+}
+
+/// The layout of an object, converted from or to JSON. Since objects do not
+/// keep track of their class, the members' names are listed in the order of
+/// their ids.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonSchema {
+    Int,
+    Uint,
+    Float,
+    Str,
+    Bool,
+    List(Box<JsonSchema>),
+    Object(Vec<(String, JsonSchema)>),
+    /// The object schema of a recursive class, which is already being
+    /// described. The value is the number of objects, enclosing it (counted
+    /// from the outermost one).
+    Recursive(usize),
 }
@@ -0,0 +1,195 @@
+//! The format strings, used by the `format()` builtin and the formatted string
+//! literals. Format strings are parsed during compilation, so the `CVM` only
+//! has to fill in the already validated segments.
+
+/// A single piece of a parsed format string.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FmtSegment {
+    /// Text, which is copied as is. The escaped `{{` and `}}` are already
+    /// replaced with single braces.
+    Str(String),
+    /// A placeholder (`{}` or `{:<spec>}`), which is replaced by the next
+    /// argument.
+    Arg(FmtSpec),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FmtAlign {
+    Left,   // <
+    Right,  // >
+    Center, // ^
+}
+
+/// The format specification of a placeholder, following the syntax
+/// `[[fill]align][0][width][.precision]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FmtSpec {
+    pub fill: char,
+    /// If not set, numbers are aligned to the right and anything else to the
+    /// left.
+    pub align: Option<FmtAlign>,
+    /// Pads numbers with zeroes after their sign.
+    pub zero_pad: bool,
+    pub width: usize,
+    /// The digits after the decimal point of floats or the maximum length of
+    /// strings.
+    pub precision: Option<usize>,
+}
+
+impl Default for FmtSpec {
+    fn default() -> Self {
+        FmtSpec {
+            fill: ' ',
+            align: None,
+            zero_pad: false,
+            width: 0,
+            precision: None,
+        }
+    }
+}
+
+impl FmtSpec {
+    fn parse(src: &str) -> Result<Self, String> {
+        let mut result = FmtSpec::default();
+        let chars: Vec<char> = src.chars().collect();
+        let mut idx = 0;
+
+        let to_align = |c: Option<&char>| match c {
+            Some('<') => Some(FmtAlign::Left),
+            Some('>') => Some(FmtAlign::Right),
+            Some('^') => Some(FmtAlign::Center),
+            _ => None,
+        };
+
+        if let Some(align) = to_align(chars.get(1)) {
+            result.fill = chars[0];
+            result.align = Some(align);
+            idx = 2;
+        } else if let Some(align) = to_align(chars.first()) {
+            result.align = Some(align);
+            idx = 1;
+        }
+
+        if chars.get(idx) == Some(&'0') {
+            result.zero_pad = true;
+            idx += 1;
+        }
+
+        let width: String = chars[idx..]
+            .iter()
+            .take_while(|c| c.is_ascii_digit())
+            .collect();
+        idx += width.len();
+        if !width.is_empty() {
+            result.width = width
+                .parse()
+                .map_err(|_| format!("invalid width `{}`", width))?;
+        }
+
+        if chars.get(idx) == Some(&'.') {
+            idx += 1;
+            let precision: String = chars[idx..]
+                .iter()
+                .take_while(|c| c.is_ascii_digit())
+                .collect();
+            if precision.is_empty() {
+                return Err(format!("missing precision in `{{:{}}}`", src));
+            }
+            idx += precision.len();
+            result.precision = Some(
+                precision
+                    .parse()
+                    .map_err(|_| format!("invalid precision `{}`", precision))?,
+            );
+        }
+
+        if idx != chars.len() {
+            return Err(format!("invalid format specifier `{{:{}}}`", src));
+        }
+        Ok(result)
+    }
+
+    /// Pads the already formatted value to the specified width.
+    pub fn pad(&self, val: String, is_numeric: bool) -> String {
+        let len = val.chars().count();
+        if len >= self.width {
+            return val;
+        }
+        let padding = self.width - len;
+
+        if self.zero_pad && is_numeric && self.align.is_none() {
+            let (sign, digits) = match val.strip_prefix('-') {
+                Some(digits) => ("-", digits),
+                None => ("", val.as_str()),
+            };
+            return format!("{}{}{}", sign, "0".repeat(padding), digits);
+        }
+
+        let align = match self.align {
+            Some(align) => align,
+            None if is_numeric => FmtAlign::Right,
+            None => FmtAlign::Left,
+        };
+        let fill = |count: usize| self.fill.to_string().repeat(count);
+        match align {
+            FmtAlign::Left => val + &fill(padding),
+            FmtAlign::Right => fill(padding) + &val,
+            FmtAlign::Center => fill(padding / 2) + &val + &fill(padding - padding / 2),
+        }
+    }
+}
+
+/// Parses the format string into segments. On failure the reason is returned.
+pub fn parse_format(src: &str) -> Result<Vec<FmtSegment>, String> {
+    let mut result = Vec::<FmtSegment>::new();
+    let mut text = String::new();
+    let mut chars = src.chars().peekable();
+
+    while let Some(current) = chars.next() {
+        match current {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                text.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                text.push('}');
+            }
+            '}' => return Err("unmatched `}`, use `}}` to escape it".to_string()),
+
+            '{' => {
+                let mut placeholder = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => placeholder.push(c),
+                        None => return Err("unclosed `{`, use `{{` to escape it".to_string()),
+                    }
+                }
+
+                let spec = match placeholder.strip_prefix(':') {
+                    Some(spec) => FmtSpec::parse(spec)?,
+                    None if placeholder.is_empty() => FmtSpec::default(),
+                    None => {
+                        return Err(format!(
+                            "invalid placeholder `{{{}}}`, expected `{{}}` or `{{:<spec>}}`",
+                            placeholder
+                        ))
+                    }
+                };
+
+                if !text.is_empty() {
+                    result.push(FmtSegment::Str(std::mem::take(&mut text)));
+                }
+                result.push(FmtSegment::Arg(spec));
+            }
+
+            _ => text.push(current),
+        }
+    }
+
+    if !text.is_empty() {
+        result.push(FmtSegment::Str(text));
+    }
+    Ok(result)
+}
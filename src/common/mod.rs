@@ -2,8 +2,9 @@
 //! `Chalcedony` interpreter.
 
 mod bytecode;
+pub mod format;
 pub mod operators;
 mod types;
 
-pub use bytecode::Bytecode;
+pub use bytecode::{Bytecode, JsonSchema, MathFn};
 pub use types::Type;
@@ -28,7 +28,6 @@ pub fn err(msg: &str) -> String {
     format!("{}: {}", color(Colors::Red, "error"), msg)
 }
 
-#[allow(dead_code)]
 pub fn warn(msg: &str) -> String {
     format!("{}: {}", color(Colors::Yellow, "warning"), msg)
 }
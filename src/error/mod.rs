@@ -8,27 +8,20 @@
 
 mod color;
 mod scopes;
+mod script;
 pub mod span;
 
-#[allow(unused_imports)]
 pub use color::{color, err, warn, Colors};
 pub use scopes::{
     CompileError, CompileErrorKind, LexerError, LexerErrorKind, ParserError, ParserErrorKind,
+    Warning, WarningKind,
 };
+pub use script::ScriptError;
 
-pub fn unhandled_exception(exc: String) {
+pub fn unhandled_exception(exc: &str, stderr: &mut dyn std::io::Write) {
     let fail_msg = color(Colors::Blue, "Unhandled exception");
-    eprintln!("{}: {}", fail_msg, exc);
-    terminate_program();
-}
-
-fn terminate_program() {
-    #[cfg(not(feature = "panicking-asserts"))]
-    std::process::exit(1);
-
-    // this is a workaround since tests don't check for `std::process::exit()`
-    #[cfg(feature = "panicking-asserts")]
-    panic!("");
+    /* the script is stopped regardless of whether the output succeeds */
+    let _ = writeln!(stderr, "{}: {}", fail_msg, exc);
 }
 
 pub enum ChalError {
@@ -21,6 +21,16 @@ pub enum CompileErrorKind {
     NonVoidFunctionStmnt(Type),
     /// `<filename>`
     ScriptNotFound(String),
+    /// `<module-name>`
+    UnknownStdModule(String),
+    /// `<script-paths>`
+    ImportCycle(Vec<String>),
+    /// `<name>`
+    ImportCollision(String),
+    /// `<filename>`, `<name>`
+    UnknownImportItem(String, String),
+    /// `<namespace-name>`
+    NamespaceAlreadyExists(String),
     /// `<class-name>`
     ClassAlreadyExists(String),
     /// `<class-name>`
@@ -39,11 +49,22 @@ pub enum CompileErrorKind {
     TypeDoesNotExits(String),
     /// `<rhs-ty>`
     UninferableType(Type),
+    /// `<type>`
+    InvalidJsonType(Type),
+    /// `<reason>`
+    InvalidFormatString(String),
+    /// `<exp-arg-count>`, `<recv-arg-count>`
+    FormatArgCount(usize, usize),
+    /// `<type>`
+    InvalidFormatArg(Type),
     /// `<exp>`, `<recv>`
     IncoherentList(Type, Type),
     InvalidIterable(Type),
+    /// `<var-name>`
+    PossiblyUnassigned(String),
     VoidFunctionExpr,
     NoDefaultReturnStmnt,
+    MissingReturn,
     MutatingExternalState,
     RedefiningFunctionArg,
     VoidArgument,
@@ -51,6 +72,10 @@ pub enum CompileErrorKind {
     VoidMember,
     OverwrittenFunction,
     RedefiningVariable,
+    MissingInitializer,
+    MutableLocal,
+    ConstDivisionByZero,
+    ConstOverflow,
     ReturnOutsideFunc,
     CtrlFlowOutsideLoop,
     NestedTryCatch,
@@ -59,6 +84,8 @@ pub enum CompileErrorKind {
     MutatingConstant,
     MemberAlreadyExists,
     ExceptionTyOutsideCatch,
+    UntypedJsonParse,
+    NonLiteralFormatString,
 }
 
 pub struct CompileError {
@@ -116,6 +143,31 @@ impl std::fmt::Display for CompileError {
                 display_err(&self.span, f, msg)
             }
 
+            CompileErrorKind::UnknownStdModule(name) => {
+                let msg = &format!("unknown standard library module `std::{}`", name);
+                display_err(&self.span, f, msg)
+            }
+
+            CompileErrorKind::ImportCycle(chain) => {
+                let msg = &format!("import cycle detected: {}", chain.join(" -> "));
+                display_err(&self.span, f, msg)
+            }
+
+            CompileErrorKind::ImportCollision(name) => {
+                let msg = &format!("the imported `{}` collides with an existing definition", name);
+                display_err(&self.span, f, msg)
+            }
+
+            CompileErrorKind::UnknownImportItem(script, name) => {
+                let msg = &format!("the script `{}` does not define `{}`", script, name);
+                display_err(&self.span, f, msg)
+            }
+
+            CompileErrorKind::NamespaceAlreadyExists(name) => {
+                let msg = &format!("namespace already exists `{}`", name);
+                display_err(&self.span, f, msg)
+            }
+
             CompileErrorKind::ClassAlreadyExists(name) => {
                 let msg = &format!("class already exists `{}`", name);
                 display_err(&self.span, f, msg)
@@ -171,6 +223,29 @@ impl std::fmt::Display for CompileError {
                 display_err(&self.span, f, msg)
             }
 
+            CompileErrorKind::InvalidJsonType(ty) => {
+                let msg = &format!("the type `{}` could not be represented as JSON", ty);
+                display_err(&self.span, f, msg)
+            }
+
+            CompileErrorKind::InvalidFormatString(reason) => {
+                let msg = &format!("invalid format string: {}", reason);
+                display_err(&self.span, f, msg)
+            }
+
+            CompileErrorKind::FormatArgCount(exp, recv) => {
+                let msg = &format!(
+                    "the format string expects {} arguments, but {} were passed",
+                    exp, recv
+                );
+                display_err(&self.span, f, msg)
+            }
+
+            CompileErrorKind::InvalidFormatArg(ty) => {
+                let msg = &format!("values of type `{}` could not be formatted", ty);
+                display_err(&self.span, f, msg)
+            }
+
             CompileErrorKind::IncoherentList(el1, el2) => {
                 let msg = &format!("incoherent list elements ('{:?}' and '{:?}')", el1, el2);
                 display_err(&self.span, f, msg)
@@ -191,10 +266,16 @@ impl std::fmt::Display for CompileError {
                 display_err(&self.span, f, "no default return statement inside function")
             }
 
+            CompileErrorKind::MissingReturn => display_err(
+                &self.span,
+                f,
+                "the function could reach its end after this statement without returning a value",
+            ),
+
             CompileErrorKind::MutatingExternalState => display_err(
                 &self.span,
                 f,
-                "functions are not allowed to mutate any external state",
+                "functions could mutate only the global variables, declared with `let mut`",
             ),
 
             CompileErrorKind::RedefiningFunctionArg => {
@@ -221,6 +302,33 @@ impl std::fmt::Display for CompileError {
                 display_err(&self.span, f, "redefining variable")
             }
 
+            CompileErrorKind::MissingInitializer => display_err(
+                &self.span,
+                f,
+                "only typed local variables could be declared without a value",
+            ),
+
+            CompileErrorKind::ConstDivisionByZero => {
+                display_err(&self.span, f, "division by zero in a constant expression")
+            }
+
+            CompileErrorKind::ConstOverflow => display_err(
+                &self.span,
+                f,
+                "the constant expression overflows its type",
+            ),
+
+            CompileErrorKind::MutableLocal => display_err(
+                &self.span,
+                f,
+                "only global variables could be declared with `let mut`",
+            ),
+
+            CompileErrorKind::PossiblyUnassigned(var) => {
+                let msg = &format!("the variable `{}` is possibly unassigned", var);
+                display_err(&self.span, f, msg)
+            }
+
             CompileErrorKind::ReturnOutsideFunc => {
                 display_err(&self.span, f, "return statement outside a function scope")
             }
@@ -254,6 +362,18 @@ impl std::fmt::Display for CompileError {
             CompileErrorKind::ExceptionTyOutsideCatch => {
                 display_err(&self.span, f, "the type `exception` is allowed only inside `catch` blocks")
             }
+
+            CompileErrorKind::NonLiteralFormatString => display_err(
+                &self.span,
+                f,
+                "the format string of `format()` must be a string literal",
+            ),
+
+            CompileErrorKind::UntypedJsonParse => display_err(
+                &self.span,
+                f,
+                "`json_parse!()` requires an explicitly typed variable, assignment or return value",
+            ),
         }
     }
 }
@@ -20,6 +20,20 @@ pub enum LexerErrorKind {
     InvalidGlobalStatement(TokenKind),
     InvalidChar(char),
     InvalidNewlineEscape,
+    /// `<escaped-char>`
+    InvalidEscapeSequence(char),
+    InvalidUnicodeEscape,
+    /// `<invalid-digit>`, `<radix>`
+    InvalidDigit(char, u32),
+    /// `<radix-prefix>`
+    MissingDigits(String),
+    /// `<literal>`
+    IntegerOverflow(String),
+    /// `<literal>`
+    InvalidNumber(String),
+    UnmatchedFmtBrace,
+    UnclosedFmtExpr,
+    EmptyFmtExpr,
 }
 
 pub struct LexerError {
@@ -80,6 +94,59 @@ impl std::fmt::Display for LexerError {
                 f,
                 "using newline escape (`\\`) before the end of the line",
             ),
+
+            LexerErrorKind::InvalidEscapeSequence(chr) => {
+                let msg = &format!("invalid escape sequence (`\\{}`)", chr.escape_default());
+                display_err(&self.span, f, msg)
+            }
+
+            LexerErrorKind::InvalidUnicodeEscape => display_err(
+                &self.span,
+                f,
+                "invalid unicode escape, expected `\\u{...}` with up to 6 hex digits of a valid code point",
+            ),
+
+            LexerErrorKind::InvalidDigit(digit, radix) => {
+                let base = match radix {
+                    2 => "a binary",
+                    8 => "an octal",
+                    16 => "a hexadecimal",
+                    _ => "a decimal",
+                };
+                let msg = &format!("invalid digit `{}` in {} literal", digit, base);
+                display_err(&self.span, f, msg)
+            }
+
+            LexerErrorKind::MissingDigits(prefix) => {
+                let msg = &format!("missing digits after the `{}` prefix", prefix);
+                display_err(&self.span, f, msg)
+            }
+
+            LexerErrorKind::IntegerOverflow(literal) => {
+                let msg = &format!("the integer literal `{}` does not fit in 64 bits", literal);
+                display_err(&self.span, f, msg)
+            }
+
+            LexerErrorKind::InvalidNumber(literal) => {
+                let msg = &format!("invalid numeric literal `{}`", literal);
+                display_err(&self.span, f, msg)
+            }
+
+            LexerErrorKind::UnmatchedFmtBrace => display_err(
+                &self.span,
+                f,
+                "unmatched `}` inside a formatted string, use `}}` to escape it",
+            ),
+
+            LexerErrorKind::UnclosedFmtExpr => display_err(
+                &self.span,
+                f,
+                "unclosed expression inside a formatted string, expected `}`",
+            ),
+
+            LexerErrorKind::EmptyFmtExpr => {
+                display_err(&self.span, f, "empty expression inside a formatted string")
+            }
         }
     }
 }
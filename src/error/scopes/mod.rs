@@ -4,12 +4,14 @@
 mod compile;
 mod lexer;
 mod parser;
+mod warning;
 
 pub use compile::{CompileError, CompileErrorKind};
 pub use lexer::{LexerError, LexerErrorKind};
 pub use parser::{ParserError, ParserErrorKind};
+pub use warning::{Warning, WarningKind};
 
-use super::{color, err, Colors};
+use super::{color, err, warn, Colors};
 
 use super::span::Span;
 
@@ -26,3 +28,26 @@ fn display_err(span: &Span, f: &mut std::fmt::Formatter, msg: &str) -> std::fmt:
         None => write!(f, "{}:\n{}\n", err(msg), span.context()),
     }
 }
+
+/* the denied warnings are displayed as errors */
+fn display_warn(
+    span: &Span,
+    f: &mut std::fmt::Formatter,
+    msg: &str,
+    denied: bool,
+) -> std::fmt::Result {
+    if denied {
+        return display_err(span, f, msg);
+    }
+    match span.spanner.filename() {
+        Some(filename) => write!(
+            f,
+            "{}[{}]: {}\n{}\n",
+            color(Colors::Yellow, "warning"),
+            color(Colors::Gray, &filename),
+            msg,
+            span.context()
+        ),
+        None => write!(f, "{}:\n{}\n", warn(msg), span.context()),
+    }
+}
@@ -0,0 +1,85 @@
+use super::display_warn;
+use crate::error::span::Span;
+
+/// The types of warnings, which could be emitted while compiling the Abstract
+/// Syntax Tree into bytecode. Unlike the errors, warnings do not stop the
+/// execution of the script (unless the warnings are denied). For each lint's
+/// name refer to `WarningKind::lint()`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WarningKind {
+    /// `<var-name>`
+    UnusedVariable(String),
+    /// `<arg-name>`
+    UnusedArgument(String),
+    /// `<import-name>`
+    UnusedImport(String),
+    /// `<func-name>`
+    UnusedFunction(String),
+    /// `<var-name>`
+    ShadowedVariable(String),
+    UnreachableCode,
+    ConstantCondition,
+    RedundantTry,
+}
+
+impl WarningKind {
+    /// The name of the lint, used to suppress the warning with the comment
+    /// `# chal: allow(<lint>, ...)`.
+    pub fn lint(&self) -> &'static str {
+        match self {
+            WarningKind::UnusedVariable(_) => "unused_variable",
+            WarningKind::UnusedArgument(_) => "unused_argument",
+            WarningKind::UnusedImport(_) => "unused_import",
+            WarningKind::UnusedFunction(_) => "unused_function",
+            WarningKind::ShadowedVariable(_) => "shadowed_variable",
+            WarningKind::UnreachableCode => "unreachable_code",
+            WarningKind::ConstantCondition => "constant_condition",
+            WarningKind::RedundantTry => "redundant_try",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Warning {
+    kind: WarningKind,
+    span: Span,
+    /// Whether the warning is treated as an error, i.e. `--deny-warnings`.
+    pub denied: bool,
+}
+
+impl Warning {
+    pub fn new(kind: WarningKind, span: Span) -> Self {
+        Warning {
+            kind,
+            span,
+            denied: false,
+        }
+    }
+
+    pub fn kind(&self) -> &WarningKind {
+        &self.kind
+    }
+
+    pub fn span(&self) -> &Span {
+        &self.span
+    }
+}
+
+impl std::fmt::Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let msg = match &self.kind {
+            WarningKind::UnusedVariable(name) => format!("unused variable `{}`", name),
+            WarningKind::UnusedArgument(name) => format!("unused function argument `{}`", name),
+            WarningKind::UnusedImport(name) => format!("unused import `{}`", name),
+            WarningKind::UnusedFunction(name) => format!("unused function `{}`", name),
+            WarningKind::ShadowedVariable(name) => {
+                format!("the local variable `{}` shadows a global variable", name)
+            }
+            WarningKind::UnreachableCode => "unreachable code".to_string(),
+            WarningKind::ConstantCondition => "the condition is always the same".to_string(),
+            WarningKind::RedundantTry => "the try block contains no unsafe operations".to_string(),
+        };
+        let msg = format!("{} [{}]", msg, self.kind.lint());
+        display_warn(&self.span, f, &msg, self.denied)
+    }
+}
@@ -0,0 +1,46 @@
+use crate::common::Type;
+
+use std::fmt;
+
+/// The errors, returned to the host when interacting with the scripts through
+/// the embedding API.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScriptError {
+    /// `<func-name>` - no function definition matches the passed arguments.
+    UnknownFunction(String),
+    /// `<exception-message>` - the called function raised an unhandled
+    /// exception.
+    Exception(String),
+    /// `<expected-type>`, `<received-type>`
+    InvalidValue(Type, Type),
+    /// `<class-name>` - the fields do not match the class' members.
+    InvalidObject(String),
+    /// `<exit-code>` - the called function stopped the script via `exit()`.
+    Exit(i32),
+    /// `<func-name>` - a host function with the same name and argument types
+    /// is already registered.
+    FunctionAlreadyExists(String),
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ScriptError::UnknownFunction(name) => {
+                write!(f, "no definition of `{}` matches the arguments", name)
+            }
+            ScriptError::Exception(exc) => write!(f, "unhandled exception: {}", exc),
+            ScriptError::InvalidValue(exp, recv) => {
+                write!(f, "invalid value (expected {:?}, received {:?})", exp, recv)
+            }
+            ScriptError::InvalidObject(class) => {
+                write!(f, "invalid instance of the class `{}`", class)
+            }
+            ScriptError::Exit(code) => write!(f, "the script exited with code {}", code),
+            ScriptError::FunctionAlreadyExists(name) => {
+                write!(f, "the function `{}` is already registered", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ScriptError {}
@@ -16,6 +16,9 @@ use std::rc::Rc;
 pub trait Spanning {
     fn context(&self, start: &Position, end: &Position) -> String;
     fn filename(&self) -> Option<String>;
+    /// Returns the source code of the line `ln` (starting from 1), if it is
+    /// available.
+    fn line(&self, ln: usize) -> Option<String>;
 }
 
 /// The structure, denoting a snippet of source code. Used in numerous
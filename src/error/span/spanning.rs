@@ -50,6 +50,10 @@ impl Spanning for InlineSpanner {
     fn filename(&self) -> Option<String> {
         self.filename.clone()
     }
+
+    fn line(&self, ln: usize) -> Option<String> {
+        self.src.get(ln.checked_sub(1)?).cloned()
+    }
 }
 
 impl InlineSpanner {
@@ -0,0 +1,375 @@
+//! The reconstruction of infix expressions from the `RPN`, stored inside
+//! `NodeExpr`, with the minimal amount of parentheses.
+
+use super::{Formatter, INDENT, MAX_WIDTH};
+
+use crate::common::operators::{BinOprType, UnaryOprType};
+use crate::error::span::Position;
+use crate::lexer::TokenKind;
+use crate::parser::ast::{
+    NodeAttrRes, NodeAttribute, NodeExpr, NodeExprInner, NodeFuncCall, NodeValue,
+};
+
+/// The binary tree of an expression, built back from its `RPN`.
+enum ExprTree<'a> {
+    Terminal(&'a NodeExprInner),
+    Unary(UnaryOprType, Box<ExprTree<'a>>),
+    Binary(BinOprType, Box<ExprTree<'a>>, Box<ExprTree<'a>>),
+}
+
+impl<'a> ExprTree<'a> {
+    fn new(expr: &'a NodeExpr) -> Self {
+        let mut stack = Vec::<ExprTree>::new();
+        for inner in &expr.expr {
+            match inner {
+                NodeExprInner::BinOpr(opr) => {
+                    let right = stack.pop().expect("expected a valid expression");
+                    let left = stack.pop().expect("expected a valid expression");
+                    stack.push(ExprTree::Binary(*opr, Box::new(left), Box::new(right)));
+                }
+                NodeExprInner::UnaryOpr(opr) => {
+                    let operand = stack.pop().expect("expected a valid expression");
+                    stack.push(ExprTree::Unary(*opr, Box::new(operand)));
+                }
+                _ => stack.push(ExprTree::Terminal(inner)),
+            }
+        }
+        stack.pop().expect("expected a non-empty expression")
+    }
+}
+
+/* the same precedence as the parser's */
+fn precedence(opr: BinOprType) -> u64 {
+    match opr {
+        BinOprType::Add | BinOprType::Sub => 5,
+        BinOprType::Mul | BinOprType::Div | BinOprType::Mod => 6,
+        BinOprType::And => 2,
+        BinOprType::Or => 1,
+        BinOprType::Lt | BinOprType::Gt | BinOprType::LtEq | BinOprType::GtEq => 4,
+        BinOprType::EqEq | BinOprType::BangEq => 3,
+    }
+}
+
+fn bin_opr_str(opr: BinOprType) -> &'static str {
+    match opr {
+        BinOprType::Add => "+",
+        BinOprType::Sub => "-",
+        BinOprType::Mul => "*",
+        BinOprType::Div => "/",
+        BinOprType::Mod => "%",
+        BinOprType::And => "&&",
+        BinOprType::Or => "||",
+        BinOprType::Lt => "<",
+        BinOprType::Gt => ">",
+        BinOprType::LtEq => "<=",
+        BinOprType::GtEq => ">=",
+        BinOprType::EqEq => "==",
+        BinOprType::BangEq => "!=",
+    }
+}
+
+/* the column after printing `text`, starting at the column `used` */
+fn next_col(used: usize, text: &str) -> usize {
+    match text.rsplit_once('\n') {
+        Some((_, last)) => last.chars().count(),
+        None => used + text.chars().count(),
+    }
+}
+
+/// An item of a comma separated sequence - call arguments, list elements or
+/// inline class members.
+enum SeqItem<'a> {
+    Arg(&'a NodeExpr),
+    Element(&'a NodeExpr),
+    Member(&'a str, &'a NodeExpr),
+}
+
+impl Formatter {
+    /// Prints the expression, starting at the column `used` of a line, whose
+    /// indentation is `indent`.
+    pub(super) fn expr(&mut self, node: &NodeExpr, indent: usize, used: usize) -> String {
+        self.seek_literal(node.span.start);
+        self.tree(&ExprTree::new(node), indent, used)
+    }
+
+    /* moves to the first literal inside the source after the position */
+    pub(super) fn seek_literal(&mut self, pos: Position) {
+        self.next_literal = self
+            .literals
+            .partition_point(|lit| (lit.pos.ln, lit.pos.col) < (pos.ln, pos.col));
+    }
+
+    fn tree(&mut self, tree: &ExprTree, indent: usize, used: usize) -> String {
+        match tree {
+            ExprTree::Terminal(inner) => self.terminal(inner, indent, used),
+
+            ExprTree::Unary(opr, operand) => {
+                let opr = match opr {
+                    UnaryOprType::Neg => "-",
+                    UnaryOprType::Bang => "!",
+                };
+                let operand_str = self.tree(operand, indent, used + 2);
+                /* repeated unary operators and negative literals need parentheses */
+                match operand.as_ref() {
+                    ExprTree::Terminal(_) if !operand_str.starts_with('-') => {
+                        format!("{}{}", opr, operand_str)
+                    }
+                    _ => format!("{}({})", opr, operand_str),
+                }
+            }
+
+            ExprTree::Binary(opr, left, right) => {
+                let prec = precedence(*opr);
+                let left = self.operand(left, prec, false, indent, used);
+                let middle = format!(" {} ", bin_opr_str(*opr));
+                let right = self.operand(right, prec, true, indent, next_col(used, &left) + 3);
+                left + &middle + &right
+            }
+        }
+    }
+
+    /// Prints the operand of a binary operator. Since the operators are left
+    /// associative, the right operand needs parentheses even if its operator
+    /// is of the same precedence.
+    fn operand(
+        &mut self,
+        tree: &ExprTree,
+        parent_prec: u64,
+        is_right: bool,
+        indent: usize,
+        used: usize,
+    ) -> String {
+        let needs_parens = match tree {
+            ExprTree::Binary(opr, _, _) => {
+                precedence(*opr) < parent_prec || (is_right && precedence(*opr) == parent_prec)
+            }
+            _ => false,
+        };
+        match needs_parens {
+            true => format!("({})", self.tree(tree, indent, used + 1)),
+            false => self.tree(tree, indent, used),
+        }
+    }
+
+    fn terminal(&mut self, inner: &NodeExprInner, indent: usize, used: usize) -> String {
+        match inner {
+            NodeExprInner::Value(val) => self.value(val),
+            NodeExprInner::Resolution(node) => self.attr_res(node, indent, used),
+            NodeExprInner::InlineClass(node) => {
+                /* the members are kept in a hashmap, so they are sorted back */
+                let mut members: Vec<(&String, &NodeExpr, Position)> = node
+                    .members
+                    .iter()
+                    .map(|(name, (expr, span))| (name, expr, span.start))
+                    .collect();
+                members.sort_by_key(|(_, _, pos)| (pos.ln, pos.col));
+
+                let items: Vec<SeqItem> = members
+                    .into_iter()
+                    .map(|(name, expr, _)| SeqItem::Member(name, expr))
+                    .collect();
+                self.seq(format!("{} {{", node.class), &items, "}", indent, used)
+            }
+            NodeExprInner::List(node) => {
+                /* only the first element could be mistaken for a subtraction */
+                let items: Vec<SeqItem> = node
+                    .elements
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, el)| match idx {
+                        0 => SeqItem::Element(el),
+                        _ => SeqItem::Arg(el),
+                    })
+                    .collect();
+                self.seq("[".to_string(), &items, "]", indent, used)
+            }
+            NodeExprInner::BinOpr(_) | NodeExprInner::UnaryOpr(_) => {
+                panic!("Formatter::terminal(): printing an operator as a terminal")
+            }
+        }
+    }
+
+    /* prints the literal's source if it matches the value */
+    fn value(&mut self, val: &NodeValue) -> String {
+        if let Some(lit) = self.literals.get(self.next_literal) {
+            let matches = match (&lit.kind, val) {
+                (TokenKind::Int(lhs), NodeValue::Int(rhs)) => lhs == rhs,
+                (TokenKind::Uint(lhs), NodeValue::Uint(rhs)) => lhs == rhs,
+                (TokenKind::Float(lhs), NodeValue::Float(rhs)) => lhs == rhs,
+                (TokenKind::Str(lhs), NodeValue::Str(rhs)) => lhs == rhs,
+                (TokenKind::Bool(lhs), NodeValue::Bool(rhs)) => lhs == rhs,
+                _ => false,
+            };
+            if matches {
+                self.next_literal += 1;
+                return lit.text.clone();
+            }
+        }
+
+        match val {
+            NodeValue::Int(val) => val.to_string(),
+            NodeValue::Uint(val) => val.to_string(),
+            NodeValue::Float(val) => format!("{:?}", val),
+            NodeValue::Str(val) => format!("{:?}", val),
+            NodeValue::Bool(val) => val.to_string(),
+        }
+    }
+
+    pub(super) fn attr_res(&mut self, node: &NodeAttrRes, indent: usize, used: usize) -> String {
+        /* formatted strings are printed as they are */
+        if let [NodeAttribute::FuncCall(call)] = node.resolution.as_slice() {
+            let pos = call.span.start;
+            if call.name == "format" && call.namespace.is_none() {
+                if let Some(text) = self.fmt_strs.get(&(pos.ln, pos.col)) {
+                    return text.clone();
+                }
+            }
+        }
+
+        let saved_literal = self.next_literal;
+        let flat = self.attr_chain(node, indent, used, false);
+        if !flat.contains('\n') && next_col(used, &flat) <= MAX_WIDTH {
+            return flat;
+        }
+        self.next_literal = saved_literal;
+        self.attr_chain(node, indent, used, true)
+    }
+
+    /// Prints the attributes of the resolution. If `split` is set, long method
+    /// chains are split with line continuations before each method call,
+    /// except the first one.
+    fn attr_chain(
+        &mut self,
+        node: &NodeAttrRes,
+        indent: usize,
+        used: usize,
+        split: bool,
+    ) -> String {
+        let calls = node
+            .resolution
+            .iter()
+            .filter(|attr| matches!(attr, NodeAttribute::FuncCall(_)))
+            .count();
+        let split = split && calls >= 2;
+
+        let mut result = String::new();
+        let mut has_call = false;
+        for (idx, attr) in node.resolution.iter().enumerate() {
+            if idx > 0 {
+                if split && has_call && matches!(attr, NodeAttribute::FuncCall(_)) {
+                    result.push_str(" \\\n");
+                    result.push_str(&" ".repeat(indent + INDENT));
+                }
+                result.push('.');
+            }
+            match attr {
+                NodeAttribute::VarCall(node) => result.push_str(&node.name),
+                NodeAttribute::FuncCall(node) => {
+                    let call_indent = match split && has_call {
+                        true => indent + INDENT,
+                        false => indent,
+                    };
+                    let call = self.func_call(node, call_indent, next_col(used, &result));
+                    result.push_str(&call);
+                    has_call = true;
+                }
+            }
+        }
+        result
+    }
+
+    fn func_call(&mut self, node: &NodeFuncCall, indent: usize, used: usize) -> String {
+        let prefix = match &node.namespace {
+            Some(namespace) => format!("{}::{}(", namespace, node.name),
+            None => format!("{}(", node.name),
+        };
+        let items: Vec<SeqItem> = node.args.iter().map(SeqItem::Arg).collect();
+        self.seq(prefix, &items, ")", indent, used)
+    }
+
+    /// Prints a comma separated sequence on a single line if it fits or with
+    /// each item on a separate line otherwise.
+    fn seq(
+        &mut self,
+        prefix: String,
+        items: &[SeqItem],
+        close: &str,
+        indent: usize,
+        used: usize,
+    ) -> String {
+        let saved_literal = self.next_literal;
+        let mut flat = Vec::<String>::new();
+        for item in items {
+            flat.push(self.seq_item(item, indent, 0));
+        }
+        let flat = format!("{}{}{}", prefix, flat.join(", "), close);
+        if items.is_empty() || (!flat.contains('\n') && next_col(used, &flat) <= MAX_WIDTH) {
+            return flat;
+        }
+
+        self.next_literal = saved_literal;
+
+        /* a single list or inline class is kept on the same line, i.e. `f([` */
+        if let [item @ (SeqItem::Arg(expr) | SeqItem::Element(expr))] = items {
+            if let (1, Some(NodeExprInner::List(_) | NodeExprInner::InlineClass(_))) =
+                (expr.expr.len(), expr.expr.front())
+            {
+                let item = self.seq_item(item, indent, next_col(used, &prefix));
+                return prefix + &item + close;
+            }
+        }
+
+        let item_indent = indent + INDENT;
+        let mut result = prefix;
+        for (idx, item) in items.iter().enumerate() {
+            result.push('\n');
+            result.push_str(&" ".repeat(item_indent));
+            result.push_str(&self.seq_item(item, item_indent, item_indent));
+            if idx + 1 < items.len() {
+                result.push(',');
+            }
+        }
+        result.push('\n');
+        result.push_str(&" ".repeat(indent));
+        result.push_str(close);
+        result
+    }
+
+    fn seq_item(&mut self, item: &SeqItem, indent: usize, used: usize) -> String {
+        match item {
+            SeqItem::Arg(expr) => self.expr(expr, indent, used),
+            SeqItem::Element(expr) => self.element(expr, indent, used),
+            SeqItem::Member(name, expr) => {
+                /* `Point {x: x}` is the same as `Point {x}` */
+                if let (1, Some(NodeExprInner::Resolution(res))) =
+                    (expr.expr.len(), expr.expr.front())
+                {
+                    if let [NodeAttribute::VarCall(var)] = res.resolution.as_slice() {
+                        if var.name == *name {
+                            return name.to_string();
+                        }
+                    }
+                }
+                let prefix = format!("{}: ", name);
+                let used = used + prefix.len();
+                prefix + &self.element(expr, indent, used)
+            }
+        }
+    }
+
+    /// Prints an element of a list or an inline class. The lexer treats a `-`
+    /// after `[` or `:` as a binary operator, so a leading negation is kept
+    /// inside parentheses.
+    fn element(&mut self, expr: &NodeExpr, indent: usize, used: usize) -> String {
+        let result = self.expr(expr, indent, used + 1);
+        let is_negation = result.starts_with('-')
+            && !result
+                .chars()
+                .nth(1)
+                .is_some_and(|c| c.is_ascii_digit() || c == '.');
+        match is_negation {
+            true => format!("({})", result),
+            false => result,
+        }
+    }
+}
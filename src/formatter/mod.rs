@@ -0,0 +1,468 @@
+//! The source code formatter, used by `chal fmt`. The script is parsed into the
+//! `AST`, which is printed back with canonical indentation and spacing, while
+//! the [`LosslessLexer`] provides the parts of the source code, which the `AST`
+//! does not keep - comments, blank lines and the exact text of literals.
+//!
+//! The formatter is idempotent, i.e. formatting an already formatted script
+//! does not change it.
+
+mod expr;
+
+use crate::error::span::Position;
+use crate::error::ChalError;
+use crate::lexer::{Keyword, Lexer, LosslessLexer, TokenKind, TriviaKind};
+use crate::parser::ast::{
+    ImportKind, NodeAssign, NodeClass, NodeExpr, NodeForLoop, NodeFuncCallStmnt, NodeFuncDef,
+    NodeIfBranch, NodeIfStmnt, NodeImport, NodeProg, NodeStmnt, NodeTryCatch, NodeVarDef,
+    NodeWhileLoop,
+};
+use crate::parser::Parser;
+
+use crate::common::operators::AssignOprType;
+use crate::common::Type;
+
+use ahash::AHashMap;
+
+/// The maximum width of a line, after which calls, lists and inline classes
+/// are split on multiple lines.
+const MAX_WIDTH: usize = 100;
+const INDENT: usize = 4;
+
+struct Comment {
+    line: usize,
+    col: usize,
+    text: String,
+}
+
+/// A literal token, whose source is used instead of the parsed value, so
+/// numbers keep their base and strings keep their quotes and escapes.
+struct Literal {
+    pos: Position,
+    kind: TokenKind,
+    text: String,
+}
+
+/// The structure, used to print the `AST` of a script back into source code.
+pub struct Formatter {
+    out: String,
+
+    comments: Vec<Comment>,
+    next_comment: usize,
+    literals: Vec<Literal>,
+    next_literal: usize,
+    /// The source of the formatted strings by their position, since they are
+    /// desugared into calls to `format()`.
+    fmt_strs: AHashMap<(usize, usize), String>,
+
+    /// Whether each line (starting from 1) is empty.
+    blank_lines: Vec<bool>,
+    /// The lines, on which statements end, i.e. the lines of the newline tokens.
+    stmnt_ends: Vec<usize>,
+    /// The lines of the `else` keywords, since `NodeElseStmnt` has no span.
+    else_lines: Vec<usize>,
+
+    /// The current indentation level.
+    depth: usize,
+    /// The indentation level of the last printed line.
+    last_depth: usize,
+    /// The source line, on which the last printed statement ends.
+    last_line: usize,
+    /// Set after a block's header, so no blank line is inserted before the
+    /// block's first statement.
+    block_start: bool,
+}
+
+impl Formatter {
+    /// Formats the script. Fails if the script could not be parsed.
+    pub fn format(code: &str, filename: Option<String>) -> Result<String, ChalError> {
+        /* the lexer converts tabs to spaces, so the positions must match */
+        let code = code.replace('\t', "    ");
+
+        let mut parser = match filename.clone() {
+            Some(filename) => Parser::from_source(&code, filename),
+            None => Parser::new(&code),
+        };
+        let mut nodes = Vec::<NodeProg>::new();
+        let mut errors = Vec::<ChalError>::new();
+        while !parser.is_empty() {
+            match parser.advance() {
+                Ok(node) => nodes.push(node),
+                Err(err) => errors.push(err),
+            }
+        }
+        if !errors.is_empty() {
+            return Err(errors.into());
+        }
+
+        let mut formatter = Formatter::new(&code, Lexer::lossless(&code, filename))?;
+        for node in &nodes {
+            formatter.prog(node);
+        }
+        formatter.flush_comments(usize::MAX);
+
+        let mut result = formatter.out.trim_end().to_string();
+        if !result.is_empty() {
+            result.push('\n');
+        }
+        Ok(result)
+    }
+
+    fn new(code: &str, lexer: LosslessLexer) -> Result<Self, ChalError> {
+        let mut line_starts = vec![0];
+        line_starts.extend(code.match_indices('\n').map(|(idx, _)| idx + 1));
+        let line_of = |offset: usize| line_starts.partition_point(|start| *start <= offset);
+
+        let mut blank_lines = vec![false];
+        blank_lines.extend(code.split('\n').map(|line| line.trim().is_empty()));
+
+        let mut result = Formatter {
+            out: String::new(),
+            comments: Vec::new(),
+            next_comment: 0,
+            literals: Vec::new(),
+            next_literal: 0,
+            fmt_strs: AHashMap::new(),
+            blank_lines,
+            stmnt_ends: Vec::new(),
+            else_lines: Vec::new(),
+            depth: 0,
+            last_depth: 0,
+            last_line: 0,
+            block_start: false,
+        };
+
+        let mut errors = Vec::<ChalError>::new();
+        for tok in lexer {
+            let tok = match tok {
+                Ok(tok) => tok,
+                Err(err) => {
+                    errors.push(err);
+                    continue;
+                }
+            };
+
+            for trivia in tok.leading {
+                if trivia.kind == TriviaKind::Comment {
+                    let line = line_of(trivia.range.start);
+                    result.comments.push(Comment {
+                        line,
+                        col: trivia.range.start - line_starts[line - 1],
+                        text: trivia.text.trim_end().to_string(),
+                    });
+                }
+            }
+
+            let Some(token) = tok.token else {
+                continue;
+            };
+            match token.kind {
+                TokenKind::Newline => result.stmnt_ends.push(line_of(tok.range.start)),
+                TokenKind::Keyword(Keyword::Else) => {
+                    result.else_lines.push(line_of(tok.range.start))
+                }
+                TokenKind::FmtStr(_) => {
+                    let pos = token.span.start;
+                    result.fmt_strs.insert((pos.ln, pos.col), tok.text);
+                }
+                TokenKind::Int(_)
+                | TokenKind::Uint(_)
+                | TokenKind::Float(_)
+                | TokenKind::Str(_)
+                | TokenKind::Bool(_) => result.literals.push(Literal {
+                    pos: token.span.start,
+                    kind: token.kind,
+                    text: tok.text,
+                }),
+                _ => {}
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(errors.into());
+        }
+        Ok(result)
+    }
+
+    fn is_blank(&self, line: usize) -> bool {
+        self.blank_lines.get(line).copied().unwrap_or(false)
+    }
+
+    /* the line, on which the statement, starting on `line`, ends */
+    fn stmnt_end(&self, line: usize) -> usize {
+        let idx = self.stmnt_ends.partition_point(|end| *end < line);
+        self.stmnt_ends.get(idx).copied().unwrap_or(line)
+    }
+
+    /* keeps a single blank line wherever the source has any */
+    fn blank_line_before(&mut self, line: usize) {
+        if self.block_start || self.out.is_empty() || self.out.ends_with("\n\n") {
+            return;
+        }
+        if line > 1 && self.is_blank(line - 1) {
+            self.out.push('\n');
+        }
+    }
+
+    /// Prints the comments before the line. Comments between the end of a
+    /// block and the next statement keep their indentation, as long as it is
+    /// between the two statements' indentation levels.
+    fn flush_comments(&mut self, line: usize) {
+        while let Some(comment) = self.comments.get(self.next_comment) {
+            if comment.line >= line {
+                break;
+            }
+            let (comment_line, text) = (comment.line, comment.text.clone());
+            let max_depth = self.last_depth.max(self.depth);
+            let depth = (comment.col / INDENT).clamp(self.depth, max_depth);
+            self.next_comment += 1;
+
+            self.blank_line_before(comment_line);
+            self.out.push_str(&" ".repeat(depth * INDENT));
+            self.out.push_str(&text);
+            self.out.push('\n');
+            self.block_start = false;
+        }
+    }
+
+    /// Prints a single statement (or a block's header), which starts on the
+    /// source `line`, along with any comments on its lines.
+    fn line(&mut self, text: &str, line: usize) {
+        self.flush_comments(line);
+        self.blank_line_before(line);
+
+        self.out.push_str(&" ".repeat(self.depth * INDENT));
+        self.out.push_str(text);
+
+        let end = self.stmnt_end(line);
+        while let Some(comment) = self.comments.get(self.next_comment) {
+            if comment.line > end {
+                break;
+            }
+            self.out.push_str("  ");
+            self.out.push_str(&comment.text);
+            self.next_comment += 1;
+        }
+        self.out.push('\n');
+
+        self.block_start = false;
+        self.last_depth = self.depth;
+        self.last_line = end;
+    }
+
+    fn indent(&self) -> usize {
+        self.depth * INDENT
+    }
+
+    fn block(&mut self, body: &[NodeStmnt]) {
+        self.depth += 1;
+        self.block_start = true;
+        for node in body {
+            self.stmnt(node);
+        }
+        self.block_start = false;
+        self.depth -= 1;
+    }
+
+    fn prog(&mut self, node: &NodeProg) {
+        match node {
+            NodeProg::VarDef(node) => self.var_def(node),
+            NodeProg::FuncDef(node) => self.func_def(node),
+            NodeProg::FuncCall(node) => self.func_call_stmnt(node),
+            NodeProg::Assign(node) => self.assign(node),
+            NodeProg::IfStmnt(node) => self.if_stmnt(node),
+            NodeProg::WhileLoop(node) => self.while_loop(node),
+            NodeProg::ForLoop(node) => self.for_loop(node),
+            NodeProg::TryCatch(node) => self.try_catch(node),
+            NodeProg::Import(node) => self.import(node),
+            NodeProg::Class(node) => self.class(node),
+        }
+    }
+
+    fn stmnt(&mut self, node: &NodeStmnt) {
+        match node {
+            NodeStmnt::VarDef(node) => self.var_def(node),
+            NodeStmnt::FuncCall(node) => self.func_call_stmnt(node),
+            NodeStmnt::Assign(node) => self.assign(node),
+            NodeStmnt::RetStmnt(node) => {
+                let text = match node.value.expr.is_empty() {
+                    true => "return".to_string(),
+                    false => self.prefixed("return ", &node.value),
+                };
+                self.line(&text, node.span.start.ln);
+            }
+
+            NodeStmnt::IfStmnt(node) => self.if_stmnt(node),
+            NodeStmnt::WhileLoop(node) => self.while_loop(node),
+            NodeStmnt::ContStmnt(node) => self.line("continue", node.span.start.ln),
+            NodeStmnt::BreakStmnt(node) => self.line("break", node.span.start.ln),
+            NodeStmnt::ForLoop(node) => self.for_loop(node),
+
+            NodeStmnt::TryCatch(node) => self.try_catch(node),
+            NodeStmnt::Throw(node) => {
+                let text = self.prefixed("throw ", &node.0);
+                self.line(&text, node.0.span.start.ln);
+            }
+        }
+    }
+
+    /* the expression, preceded by the prefix on the same line */
+    fn prefixed(&mut self, prefix: &str, expr: &NodeExpr) -> String {
+        let used = self.indent() + prefix.len();
+        prefix.to_string() + &self.expr(expr, self.indent(), used)
+    }
+
+    /* the first line of a block, i.e. `<prefix><expr>:` */
+    fn header(&mut self, prefix: &str, cond: &NodeExpr) -> String {
+        let used = self.indent() + prefix.len() + 1;
+        format!("{}{}:", prefix, self.expr(cond, self.indent(), used))
+    }
+
+    fn func_call_stmnt(&mut self, node: &NodeFuncCallStmnt) {
+        let text = self.attr_res(&node.0, self.indent(), self.indent());
+        self.line(&text, node.0.span.start.ln);
+    }
+
+    fn while_loop(&mut self, node: &NodeWhileLoop) {
+        let text = self.header("while ", &node.condition);
+        self.line(&text, node.condition.span.start.ln);
+        self.block(&node.body);
+    }
+
+    fn for_loop(&mut self, node: &NodeForLoop) {
+        let prefix = format!("for {} in ", node.iter.name);
+        let text = self.header(&prefix, &node.iterable);
+        self.line(&text, node.iter.span.start.ln);
+        self.block(&node.body);
+    }
+
+    fn var_def(&mut self, node: &NodeVarDef) {
+        let mut prefix = match (node.is_const, node.is_mut) {
+            (true, _) => format!("const {}", node.name),
+            (false, true) => format!("let mut {}", node.name),
+            (false, false) => format!("let {}", node.name),
+        };
+        if node.ty != Type::Any {
+            prefix += &format!(": {}", node.ty);
+        }
+
+        let text = match node.value.expr.is_empty() {
+            true => prefix,
+            false => self.prefixed(&(prefix + " = "), &node.value),
+        };
+        self.line(&text, node.span.start.ln);
+    }
+
+    fn assign(&mut self, node: &NodeAssign) {
+        let opr = match node.opr {
+            AssignOprType::Eq => "=",
+            AssignOprType::AddEq => "+=",
+            AssignOprType::SubEq => "-=",
+            AssignOprType::MulEq => "*=",
+            AssignOprType::DivEq => "/=",
+            AssignOprType::ModEq => "%=",
+        };
+        let lhs = self.attr_res(&node.lhs, self.indent(), self.indent());
+        let text = self.prefixed(&format!("{} {} ", lhs, opr), &node.rhs);
+        self.line(&text, node.lhs.span.start.ln);
+    }
+
+    fn if_stmnt(&mut self, node: &NodeIfStmnt) {
+        let text = self.header("if ", &node.condition);
+        self.line(&text, node.condition.span.start.ln);
+        self.block(&node.body);
+
+        for branch in &node.branches {
+            match branch {
+                NodeIfBranch::Elif(branch) => {
+                    let text = self.header("elif ", &branch.condition);
+                    self.line(&text, branch.condition.span.start.ln);
+                    self.block(&branch.body);
+                }
+                NodeIfBranch::Else(branch) => {
+                    let idx = self.else_lines.partition_point(|ln| *ln <= self.last_line);
+                    let line = self.else_lines.get(idx).copied().unwrap_or(self.last_line);
+                    self.line("else:", line);
+                    self.block(&branch.body);
+                }
+            }
+        }
+    }
+
+    fn try_catch(&mut self, node: &NodeTryCatch) {
+        self.line("try:", node.try_span.start.ln);
+        self.block(&node.try_body);
+
+        let text = format!("catch ({}: exception):", node.exception_var.name);
+        self.line(&text, node.exception_var.span.start.ln);
+        self.block(&node.catch_body);
+    }
+
+    fn func_def(&mut self, node: &NodeFuncDef) {
+        let mut args = Vec::<String>::new();
+        for (idx, arg) in node.args.iter().enumerate() {
+            /* the type of `self` is implied in methods */
+            let is_self = idx == 0
+                && arg.name == "self"
+                && node.namespace.is_some()
+                && arg.ty == Type::Custom(Box::new(node.namespace.clone().unwrap()));
+            match is_self {
+                true => args.push(arg.name.clone()),
+                false => args.push(format!("{}: {}", arg.name, arg.ty)),
+            }
+        }
+
+        let mut text = format!("fn {}({})", node.name, args.join(", "));
+        if node.ret_type != Type::Void {
+            text += &format!(" -> {}", node.ret_type);
+        }
+        text.push(':');
+
+        self.line(&text, node.span.start.ln);
+        self.block(&node.body);
+    }
+
+    fn class(&mut self, node: &NodeClass) {
+        self.line(&format!("class {}:", node.name), node.span.start.ln);
+
+        self.depth += 1;
+        self.block_start = true;
+        for member in &node.members {
+            self.line(
+                &format!("{}: {}", member.name, member.ty),
+                member.span.start.ln,
+            );
+        }
+        for method in &node.methods {
+            self.func_def(method);
+        }
+        self.block_start = false;
+        self.depth -= 1;
+    }
+
+    fn import(&mut self, node: &NodeImport) {
+        /* `std` modules are not string literals */
+        let path = match self.next_literal_in(node) {
+            Some(path) => path,
+            None => node.path.clone(),
+        };
+
+        let text = match &node.kind {
+            ImportKind::Global => format!("import {}", path),
+            ImportKind::Namespaced(namespace) => format!("import {} as {}", path, namespace),
+            ImportKind::Selective(items) => {
+                let items: Vec<&str> = items.iter().map(|item| item.name.as_str()).collect();
+                format!("from {} import {}", path, items.join(", "))
+            }
+        };
+        self.line(&text, node.span.start.ln);
+    }
+
+    fn next_literal_in(&mut self, node: &NodeImport) -> Option<String> {
+        self.seek_literal(node.span.start);
+        let literal = self.literals.get(self.next_literal)?;
+        if literal.pos.ln != node.span.start.ln {
+            return None;
+        }
+        Some(literal.text.clone())
+    }
+}
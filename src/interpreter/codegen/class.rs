@@ -48,6 +48,7 @@ impl ToBytecode for NodeClass {
         interpreter.namespaces.insert(self.name, namespace);
         for method in self.methods {
             let code = method.to_bytecode(interpreter)?;
+            let code = interpreter.optimized(code);
             interpreter.vm.execute(code);
         }
 
@@ -0,0 +1,191 @@
+//! The compile-time evaluation of constant expressions. An expression is
+//! constant if it consists only of literals, references to constants, and
+//! arithmetic operations over them, in which case it is folded into a single
+//! value instead of being computed at runtime.
+//!
+//! The evaluation mirrors the semantics of the operators inside the `Cvm`, so
+//! the folded value is the same as the one computed at runtime.
+
+use crate::error::{ChalError, CompileError, CompileErrorKind};
+use crate::interpreter::Chalcedony;
+use crate::parser::ast::{NodeAttribute, NodeExpr, NodeExprInner, NodeValue};
+
+use crate::common::operators::{BinOprType, UnaryOprType};
+use crate::common::Type;
+
+/// Evaluates the expression at compile time. Returns `None` if the expression
+/// is not constant and an error if the evaluation divides by zero or
+/// overflows.
+pub fn const_eval(
+    expr: &NodeExpr,
+    interpreter: &mut Chalcedony,
+) -> Result<Option<NodeValue>, ChalError> {
+    let mut stack = Vec::<NodeValue>::new();
+
+    for inner in &expr.expr {
+        let value = match inner {
+            NodeExprInner::Value(value) => value.clone(),
+
+            NodeExprInner::Resolution(res) => match res.resolution.as_slice() {
+                [NodeAttribute::VarCall(var)] => match interpreter.get_const_value(&var.name) {
+                    Some(value) => value,
+                    None => return Ok(None),
+                },
+                _ => return Ok(None),
+            },
+
+            NodeExprInner::BinOpr(opr) => {
+                let (Some(right), Some(left)) = (stack.pop(), stack.pop()) else {
+                    return Ok(None);
+                };
+                let folded = fold_bin_opr(opr, left, right)
+                    .map_err(|kind| CompileError::new(kind, expr.span.clone()))?;
+                match folded {
+                    Some(value) => value,
+                    None => return Ok(None),
+                }
+            }
+
+            NodeExprInner::UnaryOpr(UnaryOprType::Neg) => {
+                let Some(value) = stack.pop() else {
+                    return Ok(None);
+                };
+                let folded =
+                    fold_neg(value).map_err(|kind| CompileError::new(kind, expr.span.clone()))?;
+                match folded {
+                    Some(value) => value,
+                    None => return Ok(None),
+                }
+            }
+
+            _ => return Ok(None),
+        };
+        stack.push(value);
+    }
+
+    match (stack.pop(), stack.is_empty()) {
+        (Some(value), true) => Ok(Some(value)),
+        _ => Ok(None),
+    }
+}
+
+/// Converts the constant's value to the declared type, the same way
+/// `Type::verify()` casts the values at runtime.
+pub fn cast_const(value: NodeValue, ty: &Type) -> Option<NodeValue> {
+    match (ty, value) {
+        (Type::Int, NodeValue::Uint(val)) => Some(NodeValue::Int(val as i64)),
+        (Type::Float, NodeValue::Int(val)) => Some(NodeValue::Float(val as f64)),
+        (Type::Float, NodeValue::Uint(val)) => Some(NodeValue::Float(val as f64)),
+        (ty, value) if value.as_type() == *ty || *ty == Type::Any => Some(value),
+        _ => None,
+    }
+}
+
+/// Negates the constant value. Returns `None` if the value could not be
+/// negated.
+pub fn fold_neg(value: NodeValue) -> Result<Option<NodeValue>, CompileErrorKind> {
+    let result = match value {
+        NodeValue::Int(val) => NodeValue::Int(checked(val.checked_neg())?),
+        NodeValue::Uint(val) => NodeValue::Int(-checked(i64::try_from(val).ok())?),
+        NodeValue::Float(val) => NodeValue::Float(-val),
+        _ => return Ok(None),
+    };
+    Ok(Some(result))
+}
+
+fn checked<T>(val: Option<T>) -> Result<T, CompileErrorKind> {
+    val.ok_or(CompileErrorKind::ConstOverflow)
+}
+
+/* applies the checked integer arithmetic, shared between `int` and `uint` */
+macro_rules! int_opr {
+    ($opr:ident, $left:expr, $right:expr) => {{
+        let (left, right) = ($left, $right);
+        if matches!($opr, BinOprType::Div | BinOprType::Mod) && right == 0 {
+            return Err(CompileErrorKind::ConstDivisionByZero);
+        }
+        let result = match $opr {
+            BinOprType::Add => left.checked_add(right),
+            BinOprType::Sub => left.checked_sub(right),
+            BinOprType::Mul => left.checked_mul(right),
+            BinOprType::Div => left.checked_div(right),
+            BinOprType::Mod => left.checked_rem(right),
+            _ => return Ok(None),
+        };
+        checked(result)?
+    }};
+}
+
+fn float_opr(opr: &BinOprType, left: f64, right: f64) -> Option<f64> {
+    match opr {
+        BinOprType::Add => Some(left + right),
+        BinOprType::Sub => Some(left - right),
+        BinOprType::Mul => Some(left * right),
+        BinOprType::Div => Some(left / right),
+        BinOprType::Mod => Some(left % right),
+        _ => None,
+    }
+}
+
+/// Applies the arithmetic operator over the constant values. Returns `None`
+/// if the operator could not be applied at compile time.
+///
+/// The operand types follow the rules in `type_eval.rs`, i.e. the left
+/// operand's type is preserved.
+pub fn fold_bin_opr(
+    opr: &BinOprType,
+    left: NodeValue,
+    right: NodeValue,
+) -> Result<Option<NodeValue>, CompileErrorKind> {
+    let to_int = |val: u64| checked(i64::try_from(val).ok());
+
+    let result = match (left, right) {
+        (NodeValue::Int(lval), NodeValue::Int(rval)) => NodeValue::Int(int_opr!(opr, lval, rval)),
+        (NodeValue::Int(lval), NodeValue::Uint(rval)) => {
+            NodeValue::Int(int_opr!(opr, lval, to_int(rval)?))
+        }
+        (NodeValue::Int(lval), NodeValue::Float(rval)) => {
+            NodeValue::Int(int_opr!(opr, lval, rval as i64))
+        }
+
+        (NodeValue::Uint(lval), NodeValue::Int(rval)) => {
+            NodeValue::Int(int_opr!(opr, to_int(lval)?, rval))
+        }
+        (NodeValue::Uint(lval), NodeValue::Uint(rval)) => {
+            NodeValue::Uint(int_opr!(opr, lval, rval))
+        }
+        (NodeValue::Uint(lval), NodeValue::Float(rval)) => {
+            NodeValue::Int(int_opr!(opr, to_int(lval)?, rval as i64))
+        }
+
+        (NodeValue::Float(lval), NodeValue::Int(rval)) => match float_opr(opr, lval, rval as f64) {
+            Some(val) => NodeValue::Float(val),
+            None => return Ok(None),
+        },
+        (NodeValue::Float(lval), NodeValue::Uint(rval)) => {
+            match float_opr(opr, lval, rval as f64) {
+                Some(val) => NodeValue::Float(val),
+                None => return Ok(None),
+            }
+        }
+        (NodeValue::Float(lval), NodeValue::Float(rval)) => match float_opr(opr, lval, rval) {
+            Some(val) => NodeValue::Float(val),
+            None => return Ok(None),
+        },
+
+        /* anything added to a string is concatenated */
+        (NodeValue::Str(lval), rval) if *opr == BinOprType::Add => {
+            let rval = match rval {
+                NodeValue::Int(val) => val.to_string(),
+                NodeValue::Uint(val) => val.to_string(),
+                NodeValue::Float(val) => val.to_string(),
+                NodeValue::Str(val) => val,
+                NodeValue::Bool(val) => val.to_string(),
+            };
+            NodeValue::Str(lval + &rval)
+        }
+
+        _ => return Ok(None),
+    };
+    Ok(Some(result))
+}
@@ -1,3 +1,4 @@
+use super::consts::const_eval;
 use super::ToBytecode;
 
 use crate::error::{ChalError, CompileError, CompileErrorKind};
@@ -7,14 +8,45 @@ use crate::parser::ast::{NodeExpr, NodeExprInner, NodeValue};
 use crate::common::operators::{BinOprType, UnaryOprType};
 use crate::common::{Bytecode, Type};
 
+use std::collections::VecDeque;
+use std::iter::zip;
+
 impl ToBytecode for NodeExpr {
-    fn to_bytecode(self, interpreter: &mut Chalcedony) -> Result<Vec<Bytecode>, ChalError> {
+    fn to_bytecode(mut self, interpreter: &mut Chalcedony) -> Result<Vec<Bytecode>, ChalError> {
         let mut result = Vec::<Bytecode>::new();
 
+        /* the constant expressions are folded into a single value */
+        if self.expr.len() > 1 {
+            if let Some(value) = const_eval(&self, interpreter)? {
+                self.expr = VecDeque::from([NodeExprInner::Value(value)]);
+            }
+        }
+
+        /* the operators over statically known types are specialized */
+        let operand_types = match self
+            .expr
+            .iter()
+            .any(|inner| matches!(inner, NodeExprInner::BinOpr(_)))
+        {
+            true => self.operand_types(interpreter),
+            false => vec![None; self.expr.len()],
+        };
+
         // since the expressions are already parsed into a Reverse Polish
         // Notation, the only thing needed for their compilation is to convert
         // them to their appropriate bytecode instructions
-        for inner in self.expr {
+        for (inner, operands) in zip(self.expr, operand_types) {
+            let typed_code = match (&inner, operands) {
+                (NodeExprInner::BinOpr(opr), Some((left, right))) => {
+                    typed_bin_opr(opr, &left, &right)
+                }
+                _ => None,
+            };
+            if let Some(code) = typed_code {
+                result.extend(code);
+                continue;
+            }
+
             interpreter.inside_stmnt = false;
             result.extend(inner.to_bytecode(interpreter)?);
             interpreter.inside_stmnt = true;
@@ -24,6 +56,52 @@ impl ToBytecode for NodeExpr {
     }
 }
 
+/* returns the specialized instructions for the operator, if the operands'
+ * types are known. The right operand is cast to the left one's type, the same
+ * way the generic operators treat them */
+fn typed_bin_opr(opr: &BinOprType, left: &Type, right: &Type) -> Option<Vec<Bytecode>> {
+    let is_arithmetic = matches!(
+        opr,
+        BinOprType::Add | BinOprType::Sub | BinOprType::Mul | BinOprType::Div | BinOprType::Mod
+    );
+
+    let cast = match (left, right) {
+        (Type::Str, _) if *opr == BinOprType::Add => return Some(vec![Bytecode::ConcatS]),
+        (left, right) if left == right => None,
+        (Type::Int, Type::Uint) => Some(Bytecode::CastI),
+        (Type::Int, Type::Float) if is_arithmetic => Some(Bytecode::CastI),
+        (Type::Float, Type::Int | Type::Uint) => Some(Bytecode::CastF),
+        _ => return None,
+    };
+
+    let [int, uint, float] = match opr {
+        BinOprType::Add => [Bytecode::AddI, Bytecode::AddU, Bytecode::AddF],
+        BinOprType::Sub => [Bytecode::SubI, Bytecode::SubU, Bytecode::SubF],
+        BinOprType::Mul => [Bytecode::MulI, Bytecode::MulU, Bytecode::MulF],
+        BinOprType::Div => [Bytecode::DivI, Bytecode::DivU, Bytecode::DivF],
+        BinOprType::Mod => [Bytecode::ModI, Bytecode::ModU, Bytecode::ModF],
+        BinOprType::Lt => [Bytecode::LtI, Bytecode::LtU, Bytecode::LtF],
+        BinOprType::Gt => [Bytecode::GtI, Bytecode::GtU, Bytecode::GtF],
+        BinOprType::LtEq => [Bytecode::LtEqI, Bytecode::LtEqU, Bytecode::LtEqF],
+        BinOprType::GtEq => [Bytecode::GtEqI, Bytecode::GtEqU, Bytecode::GtEqF],
+        BinOprType::EqEq | BinOprType::BangEq => [Bytecode::EqI, Bytecode::EqU, Bytecode::EqF],
+        BinOprType::And | BinOprType::Or => return None,
+    };
+    let instr = match left {
+        Type::Int => int,
+        Type::Uint => uint,
+        Type::Float => float,
+        _ => return None,
+    };
+
+    let mut result: Vec<Bytecode> = cast.into_iter().collect();
+    result.push(instr);
+    if *opr == BinOprType::BangEq {
+        result.push(Bytecode::Not);
+    }
+    Some(result)
+}
+
 impl ToBytecode for NodeExprInner {
     fn to_bytecode(self, interpreter: &mut Chalcedony) -> Result<Vec<Bytecode>, ChalError> {
         match self {
@@ -64,7 +142,11 @@ impl ToBytecode for NodeExprInner {
 
             NodeExprInner::InlineClass(mut node) => {
                 // TODO: try to remove the clone
-                let Some(class) = interpreter.namespaces.get(&node.class).cloned() else {
+                let class = match interpreter.is_class(&node.class) {
+                    true => interpreter.namespaces.get(&node.class).cloned(),
+                    false => None,
+                };
+                let Some(class) = class else {
                     return Err(CompileError::new(
                         CompileErrorKind::UnknownClass(node.class),
                         node.span,
@@ -0,0 +1,110 @@
+//! The control-flow analysis over the statements, used to check whether every
+//! path through a function returns a value and to find unreachable code.
+
+use crate::error::span::Span;
+use crate::parser::ast::{NodeExprInner, NodeIfBranch, NodeStmnt, NodeValue};
+
+/// The way the execution leaves a block of statements.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Flow {
+    /// The execution never reaches the end of the block, i.e. every path
+    /// returns, throws, or jumps out of the current loop iteration.
+    Diverges,
+    /// The execution could reach the end of the block. The span points to the
+    /// statement, after which the execution falls through.
+    FallsThrough(Span),
+}
+
+impl Flow {
+    pub fn diverges(&self) -> bool {
+        *self == Flow::Diverges
+    }
+}
+
+/// Analyses the block, whose end is at `end` - the span used if the block is
+/// empty.
+pub fn block_flow(body: &[NodeStmnt], end: &Span) -> Flow {
+    let mut last = end.clone();
+    for stmnt in body {
+        match stmnt_flow(stmnt) {
+            Flow::Diverges => return Flow::Diverges,
+            Flow::FallsThrough(span) => last = span,
+        }
+    }
+    Flow::FallsThrough(last)
+}
+
+pub fn stmnt_flow(stmnt: &NodeStmnt) -> Flow {
+    match stmnt {
+        /* `break` and `continue` leave the block, so the loop decides the flow */
+        NodeStmnt::RetStmnt(_)
+        | NodeStmnt::Throw(_)
+        | NodeStmnt::BreakStmnt(_)
+        | NodeStmnt::ContStmnt(_) => Flow::Diverges,
+
+        NodeStmnt::IfStmnt(node) => {
+            let body = block_flow(&node.body, &node.condition.span);
+            if !body.diverges() {
+                return body;
+            }
+
+            let mut has_else = false;
+            for branch in &node.branches {
+                let branch_flow = match branch {
+                    NodeIfBranch::Elif(branch) => block_flow(&branch.body, &branch.condition.span),
+                    NodeIfBranch::Else(branch) => {
+                        has_else = true;
+                        block_flow(&branch.body, &node.condition.span)
+                    }
+                };
+                if !branch_flow.diverges() {
+                    return branch_flow;
+                }
+            }
+
+            /* without an `else` the condition could be false */
+            match has_else {
+                true => Flow::Diverges,
+                false => Flow::FallsThrough(node.condition.span.clone()),
+            }
+        }
+
+        /* the exceptions inside the `try` block are handled by the `catch` block */
+        NodeStmnt::TryCatch(node) => {
+            let try_flow = block_flow(&node.try_body, &node.try_span);
+            if !try_flow.diverges() {
+                return try_flow;
+            }
+            block_flow(&node.catch_body, &node.exception_var.span)
+        }
+
+        NodeStmnt::WhileLoop(node) => {
+            let is_infinite = node.condition.expr.len() == 1
+                && node.condition.expr.front()
+                    == Some(&NodeExprInner::Value(NodeValue::Bool(true)));
+            if is_infinite && !breaks_loop(&node.body) {
+                return Flow::Diverges;
+            }
+            Flow::FallsThrough(node.condition.span.clone())
+        }
+
+        _ => Flow::FallsThrough(stmnt.span()),
+    }
+}
+
+/* checks for a `break`, which terminates the loop owning the body */
+fn breaks_loop(body: &[NodeStmnt]) -> bool {
+    body.iter().any(|stmnt| match stmnt {
+        NodeStmnt::BreakStmnt(_) => true,
+        NodeStmnt::IfStmnt(node) => {
+            breaks_loop(&node.body)
+                || node.branches.iter().any(|branch| match branch {
+                    NodeIfBranch::Elif(branch) => breaks_loop(&branch.body),
+                    NodeIfBranch::Else(branch) => breaks_loop(&branch.body),
+                })
+        }
+        NodeStmnt::TryCatch(node) => breaks_loop(&node.try_body) || breaks_loop(&node.catch_body),
+        /* the `break` statements inside nested loops terminate only them */
+        _ => false,
+    })
+}
@@ -1,14 +1,19 @@
+use super::consts::const_eval;
+use super::flow::{block_flow, Flow};
 use super::ToBytecode;
 
 use crate::error::{ChalError, CompileError, CompileErrorKind};
 use crate::interpreter::{ArgAnnotation, Chalcedony, SafetyScope};
-use crate::parser::ast::{NodeFuncCall, NodeFuncDef, NodeStmnt};
+use crate::parser::ast::{
+    NodeAttribute, NodeExpr, NodeExprInner, NodeFuncCall, NodeFuncDef, NodeValue,
+};
 
+use crate::common::format::{parse_format, FmtSegment, FmtSpec};
 use crate::common::{Bytecode, Type};
 use itertools::izip;
 use std::collections::VecDeque;
-
-use ahash::AHashMap;
+use std::iter::zip;
+use std::rc::Rc;
 
 fn arg_exists(args: &[ArgAnnotation], arg_name: &str) -> bool {
     for arg in args {
@@ -50,6 +55,11 @@ impl ToBytecode for NodeFuncDef {
         }
 
         interpreter.create_function(&self, args);
+        /* the locals of the outer scope are linted separately */
+        let outer_locals = std::mem::take(&mut interpreter.lints.unused_locals);
+        for (idx, arg) in self.args.iter().enumerate() {
+            interpreter.declare_arg(&arg.name, idx, arg.span.clone());
+        }
 
         /* if the function is safe, disable all unsafe oprations */
         if !self.name.ends_with('!') {
@@ -59,44 +69,36 @@ impl ToBytecode for NodeFuncDef {
         /* compile the bytecode for each statement in the body */
         let mut body = Vec::<Bytecode>::new();
         let mut errors = Vec::<ChalError>::new();
-        let mut returned = false;
+        let flow = block_flow(&self.body, &self.span);
+        interpreter.lint_unreachable(&self.body);
         for stmnt in self.body {
-            if let NodeStmnt::RetStmnt(_) = stmnt {
-                returned = true;
-            }
-
             match stmnt.to_bytecode(interpreter) {
                 Ok(bytecode) => body.extend(bytecode),
                 Err(err) => errors.push(err),
             }
         }
 
+        // check whether every path through the function returns, and if it is
+        // a void function, append `Bytecode::ReturnVoid` at the end if not
+        match (&self.ret_type, flow) {
+            (_, Flow::Diverges) => {}
+            (Type::Void, _) if body.is_empty() => errors.push(
+                CompileError::new(CompileErrorKind::NoDefaultReturnStmnt, self.span.clone()).into(),
+            ),
+            (Type::Void, _) => body.push(Bytecode::ReturnVoid),
+            (_, Flow::FallsThrough(span)) => {
+                errors.push(CompileError::new(CompileErrorKind::MissingReturn, span).into())
+            }
+        }
+
         if !errors.is_empty() {
             interpreter.current_func = None;
-            interpreter.locals = AHashMap::new();
+            interpreter.reset_locals();
+            interpreter.lints.unused_locals = outer_locals;
             interpreter.safety_scope = SafetyScope::Normal;
             return Err(errors.into());
         }
 
-        // check whether the function has returned, and if it is a void
-        // functionm, append `Bytecode::ReturnVoid` at the end if there is not
-        match self.ret_type {
-            Type::Void if body.is_empty() && !returned => {
-                return Err(
-                    CompileError::new(CompileErrorKind::NoDefaultReturnStmnt, self.span).into(),
-                )
-            }
-            Type::Void if !returned => {
-                body.push(Bytecode::ReturnVoid);
-            }
-            _ if !returned => {
-                return Err(
-                    CompileError::new(CompileErrorKind::NoDefaultReturnStmnt, self.span).into(),
-                )
-            }
-            _ => {}
-        }
-
         let Some(annotation) = interpreter.current_func.clone() else {
             panic!("Chalcedony::create_function() did not set the annotation properly");
         };
@@ -105,8 +107,14 @@ impl ToBytecode for NodeFuncDef {
         result.push(Bytecode::CreateFunc(annotation.args.len()));
         result.append(&mut body);
 
+        interpreter.finish_locals();
+        interpreter.lints.unused_locals = outer_locals;
+        if self.namespace.is_none() {
+            interpreter.declare_func(annotation.id, &self.name, self.span);
+        }
+
         interpreter.current_func = None;
-        interpreter.locals = AHashMap::new();
+        interpreter.reset_locals();
         interpreter.safety_scope = SafetyScope::Normal;
         Ok(result)
     }
@@ -128,10 +136,7 @@ pub fn compile_func_call_inner(
         .iter()
         .map(|expr| expr.as_type(interpreter))
         .collect();
-    let mut arg_types = match arg_types {
-        Ok(ok) => ok,
-        Err(err) => return Err(err),
-    };
+    let mut arg_types = arg_types?;
 
     /* the function is called as a method */
     if let Some(ty) = &parent_type {
@@ -154,6 +159,16 @@ pub fn compile_func_call_inner(
         }
     }
 
+    /* user-defined functions take precedence */
+    if node.name == "format"
+        && node.namespace.is_none()
+        && interpreter
+            .get_function(&node.name, &arg_types, None)
+            .is_none()
+    {
+        return compile_format(node, interpreter);
+    }
+
     /* SAFETY: the function must be checked before being compiled */
     let mut annotation = interpreter
         .get_function_universal(&node.name, &arg_types, node.namespace.as_ref())
@@ -162,6 +177,18 @@ pub fn compile_func_call_inner(
     if node.name.ends_with('!') && interpreter.safety_scope == SafetyScope::Safe {
         return Err(CompileError::new(CompileErrorKind::UnsafeOpInSafeBlock, node.span).into());
     }
+    if node.name.ends_with('!') && interpreter.safety_scope == SafetyScope::Guarded {
+        interpreter.lints.guarded_unsafe = true;
+    }
+    if let [Bytecode::CallFunc(id)] = annotation.bytecode.as_slice() {
+        interpreter.use_func(*id);
+    }
+    if interpreter
+        .get_function(&node.name, &arg_types, node.namespace.as_ref())
+        .is_some_and(|func| !func.is_pure())
+    {
+        interpreter.mark_impure();
+    }
 
     if parent_type.is_some() {
         arg_types.pop_front();
@@ -182,3 +209,162 @@ pub fn compile_func_call_inner(
 
     Ok(result)
 }
+
+/// Compiles the expression if it consists only of a call to `json_parse!()`.
+/// The type of the parsed value could not be inferred from the function's
+/// arguments, so it is taken from the destination's type, i.e.
+/// `let val: Point = json_parse!(src)` or `val = json_parse!(src)`.
+pub fn compile_json_parse(
+    expr: &NodeExpr,
+    ty: &Type,
+    interpreter: &mut Chalcedony,
+) -> Option<Result<Vec<Bytecode>, ChalError>> {
+    let node = match expr.expr.iter().collect::<Vec<_>>().as_slice() {
+        [NodeExprInner::Resolution(res)] => match res.resolution.as_slice() {
+            [NodeAttribute::FuncCall(node)] => node,
+            _ => return None,
+        },
+        _ => return None,
+    };
+    if node.name != "json_parse!" || node.namespace.is_some() {
+        return None;
+    }
+
+    /* user-defined functions take precedence */
+    let str_arg = VecDeque::from([Type::Str]);
+    if interpreter
+        .get_function(&node.name, &str_arg, None)
+        .is_some()
+    {
+        return None;
+    }
+
+    Some(compile_json_parse_inner(node, ty, interpreter))
+}
+
+fn compile_json_parse_inner(
+    node: &NodeFuncCall,
+    ty: &Type,
+    interpreter: &mut Chalcedony,
+) -> Result<Vec<Bytecode>, ChalError> {
+    if interpreter.safety_scope == SafetyScope::Safe {
+        return Err(
+            CompileError::new(CompileErrorKind::UnsafeOpInSafeBlock, node.span.clone()).into(),
+        );
+    }
+
+    if interpreter.safety_scope == SafetyScope::Guarded {
+        interpreter.lints.guarded_unsafe = true;
+    }
+
+    if *ty == Type::Any {
+        return Err(
+            CompileError::new(CompileErrorKind::UntypedJsonParse, node.span.clone()).into(),
+        );
+    }
+
+    let schema = match interpreter.json_schema(ty) {
+        Ok(schema) => schema,
+        Err(invalid_ty) => {
+            return Err(CompileError::new(
+                CompileErrorKind::InvalidJsonType(invalid_ty),
+                node.span.clone(),
+            )
+            .into())
+        }
+    };
+
+    let arg_types: Result<Vec<Type>, ChalError> = node
+        .args
+        .iter()
+        .map(|expr| expr.as_type(interpreter))
+        .collect();
+    let arg_types = arg_types?;
+    if arg_types != [Type::Str] {
+        let arg_types: Vec<String> = arg_types.iter().map(|ty| ty.to_string()).collect();
+        return Err(CompileError::new(
+            CompileErrorKind::UnknownFunction(format!("json_parse!({})", arg_types.join(", "))),
+            node.span.clone(),
+        )
+        .into());
+    }
+
+    let mut result = node
+        .args
+        .first()
+        .unwrap()
+        .clone()
+        .to_bytecode(interpreter)?;
+    result.push(Bytecode::JsonParse(Rc::new(schema)));
+    Ok(result)
+}
+
+/// Compiles a call to `format()`. The format string is parsed and checked
+/// against the arguments' types during compilation, so it must be a literal.
+fn compile_format(
+    node: NodeFuncCall,
+    interpreter: &mut Chalcedony,
+) -> Result<Vec<Bytecode>, ChalError> {
+    let mut args = node.args.into_iter();
+    let fmt = args.next().expect("improper func arg checks");
+
+    /* the format string could also be built from constants */
+    let fmt_src = match const_eval(&fmt, interpreter)? {
+        Some(NodeValue::Str(src)) => src,
+        _ => {
+            return Err(
+                CompileError::new(CompileErrorKind::NonLiteralFormatString, fmt.span).into(),
+            )
+        }
+    };
+
+    let segments = match parse_format(&fmt_src) {
+        Ok(segments) => segments,
+        Err(reason) => {
+            return Err(
+                CompileError::new(CompileErrorKind::InvalidFormatString(reason), fmt.span).into(),
+            )
+        }
+    };
+    let specs: Vec<&FmtSpec> = segments
+        .iter()
+        .filter_map(|segment| match segment {
+            FmtSegment::Arg(spec) => Some(spec),
+            FmtSegment::Str(_) => None,
+        })
+        .collect();
+
+    let args: Vec<NodeExpr> = args.collect();
+    if specs.len() != args.len() {
+        return Err(CompileError::new(
+            CompileErrorKind::FormatArgCount(specs.len(), args.len()),
+            node.span,
+        )
+        .into());
+    }
+
+    let mut result = Vec::<Bytecode>::new();
+    for (arg, spec) in zip(args, specs) {
+        let arg_ty = arg.as_type(interpreter)?;
+        match arg_ty {
+            Type::Int | Type::Uint | Type::Bool | Type::Exception if spec.precision.is_some() => {
+                let reason = format!("precision is not applicable to `{}`", arg_ty);
+                return Err(CompileError::new(
+                    CompileErrorKind::InvalidFormatString(reason),
+                    arg.span,
+                )
+                .into());
+            }
+            Type::Int | Type::Uint | Type::Float | Type::Str | Type::Bool | Type::Exception => {}
+            _ => {
+                return Err(
+                    CompileError::new(CompileErrorKind::InvalidFormatArg(arg_ty), arg.span).into(),
+                )
+            }
+        }
+        result.extend(arg.to_bytecode(interpreter)?);
+    }
+
+    result.push(Bytecode::Format(segments.into()));
+    Ok(result)
+}
@@ -2,19 +2,22 @@
 //! into the bytecode instructions, executed by the `CVM`.
 
 pub mod class;
+pub mod consts;
 pub mod expr;
+pub mod flow;
 pub mod func;
 pub mod stmnt;
 pub mod var;
 
 use crate::common::{Bytecode, Type};
-use crate::error::{ChalError, CompileError, CompileErrorKind};
-use crate::parser::ast::{NodeFuncCallStmnt, NodeImport, NodeProg};
+use crate::error::{span::Span, ChalError, CompileError, CompileErrorKind};
+use crate::parser::ast::{ImportKind, NodeFuncCallStmnt, NodeImport, NodeProg};
 use func::compile_func_call_inner;
 
-use super::{Chalcedony, ScriptType};
+use super::{BuiltinAnnotation, Chalcedony, ClassNamespace, FuncAnnotation, VarAnnotation};
 
-use std::path::Path;
+use std::collections::VecDeque;
+use std::rc::Rc;
 
 pub trait ToBytecode {
     fn to_bytecode(self, interpreter: &mut Chalcedony) -> Result<Vec<Bytecode>, ChalError>;
@@ -39,53 +42,251 @@ impl ToBytecode for NodeProg {
 
 impl ToBytecode for NodeImport {
     fn to_bytecode(self, interpreter: &mut Chalcedony) -> Result<Vec<Bytecode>, ChalError> {
-        let script_path = interpreter.current_path.join(&self.path);
-
-        if !script_path.exists() {
-            return Err(CompileError::new(
-                CompileErrorKind::ScriptNotFound(script_path.to_str().unwrap().to_string()),
-                self.span,
-            )
-            .into());
+        /* the native modules are registered as builtins under `std::<name>` */
+        if self.path.starts_with("std::") && interpreter.builtins.contains_key(&self.path) {
+            return import_native_module(self, interpreter);
         }
 
-        if interpreter.imported_scripts.contains(&script_path) {
-            return Ok(Vec::new());
-        }
-        interpreter.imported_scripts.insert(script_path.clone());
-
-        let parent_script_type = interpreter.script_type;
-        interpreter.script_type = ScriptType::Imported;
-        let parent_path = interpreter.current_path.clone();
-        interpreter.current_path = script_path.parent().unwrap_or(Path::new("")).into();
-
-        let script_const_id = interpreter.get_global_id_internal("__name__", Type::Str, true);
-        interpreter.vm.execute(vec![
-            Bytecode::ConstS(
-                interpreter
-                    .current_path
-                    .to_str()
-                    .unwrap()
-                    .to_string()
-                    .into(),
-            ),
-            Bytecode::SetGlobal(script_const_id),
-        ]);
-
-        interpreter.interpret_script(script_path.to_str().unwrap().to_string());
-
-        interpreter.script_type = parent_script_type;
-        interpreter.current_path = parent_path;
-
-        let name_value = match interpreter.script_type {
-            ScriptType::Main => "__main__".to_string(),
-            ScriptType::Imported => interpreter.current_path.to_str().unwrap().to_string(),
+        let exports = match self.path.strip_prefix("std::") {
+            Some(module) => interpreter.import_std_module(module, &self.span)?,
+            None => {
+                let Some(script_path) = interpreter.resolve_script(&self.path) else {
+                    let script_path = interpreter.current_path.join(&self.path);
+                    return Err(CompileError::new(
+                        CompileErrorKind::ScriptNotFound(script_path.to_str().unwrap().to_string()),
+                        self.span,
+                    )
+                    .into());
+                };
+                interpreter.import_script(script_path, &self.span)?
+            }
         };
 
-        interpreter.vm.execute(vec![
-            Bytecode::ConstS(name_value.into()),
-            Bytecode::SetGlobal(script_const_id),
-        ]);
+        match self.kind {
+            ImportKind::Global => {
+                /* the uses of classes are not tracked, so they are never warned */
+                if exports.classes.is_empty() {
+                    let funcs = exports.funcs.values().flatten().map(|el| el.id).collect();
+                    let globals = exports.globals.values().map(|el| el.id).collect();
+                    interpreter.declare_import(funcs, globals, self.path, self.span.clone());
+                }
+                for (name, funcs) in exports.funcs {
+                    merge_funcs(interpreter, name, funcs, &self.span)?;
+                }
+                for (name, var) in exports.globals {
+                    merge_global(interpreter, name, var, &self.span)?;
+                }
+            }
+
+            ImportKind::Namespaced(alias) => {
+                if interpreter.namespaces.contains_key(&alias)
+                    || interpreter.builtins.contains_key(&alias)
+                {
+                    return Err(CompileError::new(
+                        CompileErrorKind::NamespaceAlreadyExists(alias),
+                        self.span,
+                    )
+                    .into());
+                }
+                let funcs = exports.funcs.values().flatten().map(|el| el.id).collect();
+                let globals = exports.globals.values().map(|el| el.id).collect();
+                interpreter.declare_import(funcs, globals, self.path.clone(), self.span.clone());
+                /* the globals are resolved as `<alias>::<name>` */
+                for (name, var) in exports.globals {
+                    let name = format!("{}::{}", alias, name);
+                    merge_global(interpreter, name, var, &self.span)?;
+                }
+                interpreter.namespaces.insert(
+                    alias.clone(),
+                    ClassNamespace {
+                        members: Vec::new(),
+                        methods: exports.funcs,
+                    },
+                );
+                interpreter.module_namespaces.insert(alias);
+            }
+
+            ImportKind::Selective(items) => {
+                for item in items {
+                    if let Some(funcs) = exports.funcs.get(&item.name) {
+                        let ids = funcs.iter().map(|el| el.id).collect();
+                        interpreter.declare_import(
+                            ids,
+                            vec![],
+                            item.name.clone(),
+                            item.span.clone(),
+                        );
+                        merge_funcs(interpreter, item.name, funcs.clone(), &item.span)?;
+                    } else if let Some(var) = exports.globals.get(&item.name) {
+                        let ids = vec![var.id];
+                        interpreter.declare_import(
+                            vec![],
+                            ids,
+                            item.name.clone(),
+                            item.span.clone(),
+                        );
+                        merge_global(interpreter, item.name, var.clone(), &item.span)?;
+                    } else if !exports.classes.contains_key(&item.name) {
+                        return Err(CompileError::new(
+                            CompileErrorKind::UnknownImportItem(self.path, item.name),
+                            item.span,
+                        )
+                        .into());
+                    }
+                }
+            }
+        }
+
+        /* the classes are always imported globally */
+        for (name, class) in exports.classes {
+            merge_class(interpreter, name, class, &self.span)?;
+        }
+
         Ok(Vec::new())
     }
 }
+
+fn import_native_module(
+    node: NodeImport,
+    interpreter: &mut Chalcedony,
+) -> Result<Vec<Bytecode>, ChalError> {
+    let module = interpreter
+        .builtins
+        .get(&node.path)
+        .cloned()
+        .unwrap_or_default();
+
+    match node.kind {
+        ImportKind::Global => {
+            for (name, funcs) in module {
+                merge_builtins(interpreter, name, funcs, &node.span)?;
+            }
+        }
+
+        ImportKind::Namespaced(alias) => {
+            if interpreter.namespaces.contains_key(&alias)
+                || interpreter.builtins.contains_key(&alias)
+            {
+                return Err(CompileError::new(
+                    CompileErrorKind::NamespaceAlreadyExists(alias),
+                    node.span,
+                )
+                .into());
+            }
+            interpreter.builtins.insert(alias, module);
+        }
+
+        ImportKind::Selective(items) => {
+            for item in items {
+                let Some(funcs) = module.get(&item.name) else {
+                    return Err(CompileError::new(
+                        CompileErrorKind::UnknownImportItem(node.path, item.name),
+                        item.span,
+                    )
+                    .into());
+                };
+                merge_builtins(interpreter, item.name, funcs.clone(), &item.span)?;
+            }
+        }
+    }
+
+    Ok(Vec::new())
+}
+
+/* brings the native functions into the global scope, unless an overload collides */
+fn merge_builtins(
+    interpreter: &mut Chalcedony,
+    name: String,
+    funcs: Vec<BuiltinAnnotation>,
+    span: &Span,
+) -> Result<(), ChalError> {
+    for func in funcs {
+        let arg_types: VecDeque<Type> = func.args.iter().map(|arg| arg.ty.clone()).collect();
+        let existing = interpreter.get_builtin(&name, &arg_types, None);
+        /* the same native could already be in scope, i.e. imported twice */
+        if existing
+            .as_ref()
+            .is_some_and(|existing| existing.bytecode == func.bytecode)
+        {
+            continue;
+        }
+        if existing.is_some() || interpreter.get_function(&name, &arg_types, None).is_some() {
+            return Err(
+                CompileError::new(CompileErrorKind::ImportCollision(name), span.clone()).into(),
+            );
+        }
+
+        interpreter
+            .builtins
+            .entry("Global".to_string())
+            .or_default()
+            .entry(name.clone())
+            .or_default()
+            .push(func);
+    }
+    Ok(())
+}
+
+/* brings the imported functions into scope, unless an overload collides */
+fn merge_funcs(
+    interpreter: &mut Chalcedony,
+    name: String,
+    funcs: Vec<Rc<FuncAnnotation>>,
+    span: &Span,
+) -> Result<(), ChalError> {
+    let bucket = interpreter.func_symtable.entry(name.clone()).or_default();
+    for func in funcs {
+        match bucket.iter().find(|el| el.args == func.args) {
+            Some(existing) if existing.id == func.id => {}
+            Some(_) => {
+                return Err(CompileError::new(
+                    CompileErrorKind::ImportCollision(name),
+                    span.clone(),
+                )
+                .into())
+            }
+            None => bucket.push(func),
+        }
+    }
+    Ok(())
+}
+
+fn merge_class(
+    interpreter: &mut Chalcedony,
+    name: String,
+    class: ClassNamespace,
+    span: &Span,
+) -> Result<(), ChalError> {
+    match interpreter.namespaces.get(&name) {
+        /* the same class, imported through another script */
+        Some(existing) if *existing == class => Ok(()),
+        Some(_) => {
+            Err(CompileError::new(CompileErrorKind::ImportCollision(name), span.clone()).into())
+        }
+        None if interpreter.builtins.contains_key(&name) => {
+            Err(CompileError::new(CompileErrorKind::ImportCollision(name), span.clone()).into())
+        }
+        None => {
+            interpreter.namespaces.insert(name, class);
+            Ok(())
+        }
+    }
+}
+
+fn merge_global(
+    interpreter: &mut Chalcedony,
+    name: String,
+    var: VarAnnotation,
+    span: &Span,
+) -> Result<(), ChalError> {
+    match interpreter.globals.get(&name) {
+        Some(existing) if existing.id == var.id => Ok(()),
+        Some(_) => {
+            Err(CompileError::new(CompileErrorKind::ImportCollision(name), span.clone()).into())
+        }
+        None => {
+            interpreter.globals.insert(name, var);
+            Ok(())
+        }
+    }
+}
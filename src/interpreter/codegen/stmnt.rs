@@ -1,7 +1,9 @@
+use super::flow::block_flow;
+use super::func::compile_json_parse;
 use super::var::var_exists;
 use super::ToBytecode;
 
-use crate::error::{ChalError, CompileError, CompileErrorKind};
+use crate::error::{ChalError, CompileError, CompileErrorKind, WarningKind};
 use crate::interpreter::{Chalcedony, LoopScope, SafetyScope, VarAnnotation};
 use crate::parser::ast::{
     NodeAssign, NodeBreakStmnt, NodeContStmnt, NodeElifStmnt, NodeElseStmnt, NodeExprInner,
@@ -14,6 +16,8 @@ use crate::common::{Bytecode, Type};
 
 use std::collections::VecDeque;
 
+use ahash::AHashSet;
+
 /// Used for easier manipulation over the current while scope.
 fn increment_loop_scope(interpreter: &mut Chalcedony, val: usize) {
     if let Some(loop_scope) = interpreter.current_loop.as_mut() {
@@ -46,7 +50,7 @@ fn get_loop_scope_len(interpreter: &mut Chalcedony) -> usize {
     }
 }
 
-fn fix_unfinished_breaks(interpreter: &Chalcedony, code: &mut Vec<Bytecode>) {
+fn fix_unfinished_breaks(interpreter: &Chalcedony, code: &mut [Bytecode]) {
     let scope = interpreter
         .current_loop
         .as_ref()
@@ -66,6 +70,10 @@ impl ToBytecode for NodeStmnt {
             // `NodeStmnt::VarDef()` represents a local variable, where the
             // `NodeProg::VarDef()` represents a global variable
             NodeStmnt::VarDef(mut node) => {
+                if node.is_mut {
+                    return Err(CompileError::new(CompileErrorKind::MutableLocal, node.span).into());
+                }
+
                 /* the empty variable is ignored */
                 if node.name == "_" {
                     /* check for any potential invalid code */
@@ -73,7 +81,8 @@ impl ToBytecode for NodeStmnt {
                     return Ok(vec![]);
                 }
 
-                if interpreter.locals.contains_key(&node.name) {
+                /* the variables of the outer blocks could be shadowed */
+                if interpreter.is_local_in_block(&node.name) {
                     return Err(CompileError::new(
                         CompileErrorKind::RedefiningVariable,
                         node.span.clone(),
@@ -83,7 +92,9 @@ impl ToBytecode for NodeStmnt {
 
                 /* check whether the variable exists as a function's argument */
                 if let Some(func) = interpreter.current_func.clone() {
-                    if func.arg_lookup.get(&node.name).is_some() {
+                    if func.arg_lookup.get(&node.name).is_some()
+                        && interpreter.local_scopes.is_empty()
+                    {
                         return Err(CompileError::new(
                             CompileErrorKind::RedefiningFunctionArg,
                             node.span,
@@ -92,29 +103,52 @@ impl ToBytecode for NodeStmnt {
                     }
                 }
 
-                let mut result = node.value.clone().to_bytecode(interpreter)?;
-
-                let value_type = node.value.as_type(interpreter)?;
-                if node.ty != Type::Any {
-                    Type::verify(
-                        node.ty.clone(),
-                        value_type,
-                        &mut result,
-                        node.value.span.clone(),
-                    )?;
-                } else if value_type.root_type() == Type::Any {
-                    return Err(CompileError::new(
-                        CompileErrorKind::UninferableType(value_type),
-                        node.value.span,
-                    )
-                    .into());
-                } else {
-                    node.ty = value_type;
+                /* the variable is assigned later, i.e. `let x: int` */
+                if node.value.expr.is_empty() {
+                    if node.ty == Type::Any || node.is_const {
+                        return Err(CompileError::new(
+                            CompileErrorKind::MissingInitializer,
+                            node.span,
+                        )
+                        .into());
+                    }
+                    interpreter.verify_type(&node.ty, &node.span)?;
+                    let var_id = interpreter.get_local_id(&node);
+                    interpreter.unassigned.insert(var_id);
+                    interpreter.declare_local(&node.name, var_id, node.span);
+                    return Ok(vec![]);
                 }
 
+                let mut result = match compile_json_parse(&node.value, &node.ty, interpreter) {
+                    Some(result) => result?,
+                    None => {
+                        let mut result = node.value.clone().to_bytecode(interpreter)?;
+
+                        let value_type = node.value.as_type(interpreter)?;
+                        if node.ty != Type::Any {
+                            Type::verify(
+                                node.ty.clone(),
+                                value_type,
+                                &mut result,
+                                node.value.span.clone(),
+                            )?;
+                        } else if value_type.root_type() == Type::Any {
+                            return Err(CompileError::new(
+                                CompileErrorKind::UninferableType(value_type),
+                                node.value.span,
+                            )
+                            .into());
+                        } else {
+                            node.ty = value_type;
+                        }
+                        result
+                    }
+                };
+
                 /* this implicitly adds the variable to the locals symtable */
                 let var_id = interpreter.get_local_id(&node);
                 result.push(Bytecode::SetLocal(var_id));
+                interpreter.declare_local(&node.name, var_id, node.span);
 
                 result
             }
@@ -161,6 +195,7 @@ impl ToBytecode for NodeIfBranch {
 impl ToBytecode for NodeElifStmnt {
     fn to_bytecode(self, interpreter: &mut Chalcedony) -> Result<Vec<Bytecode>, ChalError> {
         let prev_loop_scope_len = get_loop_scope_len(interpreter);
+        interpreter.lint_condition(&self.condition, false);
 
         let mut result = self.condition.clone().to_bytecode(interpreter)?;
 
@@ -197,12 +232,16 @@ impl ToBytecode for Vec<NodeStmnt> {
         let mut result = Vec::<Bytecode>::new();
         let mut errors = Vec::<ChalError>::new();
 
+        interpreter.lint_unreachable(&self);
+        /* each block has it's own scope for the local variables */
+        interpreter.push_scope();
         for stmnt in self {
             match stmnt.to_bytecode(interpreter) {
                 Ok(bytecode) => result.extend(bytecode),
                 Err(err) => errors.push(err),
             }
         }
+        interpreter.pop_scope();
 
         if !errors.is_empty() {
             return Err(errors.into());
@@ -215,6 +254,7 @@ impl ToBytecode for Vec<NodeStmnt> {
 impl ToBytecode for NodeIfStmnt {
     fn to_bytecode(self, interpreter: &mut Chalcedony) -> Result<Vec<Bytecode>, ChalError> {
         let prev_loop_scope_len = get_loop_scope_len(interpreter);
+        interpreter.lint_condition(&self.condition, false);
 
         let mut result = self.condition.clone().to_bytecode(interpreter)?;
         let cond_ty = self.condition.as_type(interpreter)?;
@@ -227,13 +267,32 @@ impl ToBytecode for NodeIfStmnt {
 
         increment_loop_scope(interpreter, result.len() + 1);
 
+        // a variable is definitely assigned after the statement only if every
+        // branch, which could reach the end of the statement, assigns it
+        let unassigned = interpreter.unassigned.clone();
+        let mut branches_unassigned = AHashSet::<usize>::new();
+        let mut has_else = false;
+
+        let body_flow = block_flow(&self.body, &self.condition.span);
         let body = self.body.to_bytecode(interpreter)?;
+        if !body_flow.diverges() {
+            branches_unassigned.extend(interpreter.unassigned.drain());
+        }
 
         increment_loop_scope(interpreter, body.len() + 1);
 
         let mut branches: Vec<Vec<Bytecode>> = Vec::new();
         let mut errors: Vec<ChalError> = Vec::new();
         for branch in self.branches {
+            let branch_flow = match &branch {
+                NodeIfBranch::Elif(node) => block_flow(&node.body, &node.condition.span),
+                NodeIfBranch::Else(node) => {
+                    has_else = true;
+                    block_flow(&node.body, &self.condition.span)
+                }
+            };
+
+            interpreter.unassigned = unassigned.clone();
             match branch.to_bytecode(interpreter) {
                 Ok(bytecode) => {
                     increment_loop_scope(interpreter, bytecode.len() + 1);
@@ -241,7 +300,16 @@ impl ToBytecode for NodeIfStmnt {
                 }
                 Err(err) => errors.push(err),
             }
+            if !branch_flow.diverges() {
+                branches_unassigned.extend(interpreter.unassigned.drain());
+            }
+        }
+
+        /* without an `else` the condition could be false */
+        if !has_else {
+            branches_unassigned.extend(unassigned);
         }
+        interpreter.unassigned = branches_unassigned;
 
         set_loop_scope(interpreter, prev_loop_scope_len + 1);
 
@@ -279,6 +347,7 @@ impl ToBytecode for NodeIfStmnt {
 impl ToBytecode for NodeWhileLoop {
     fn to_bytecode(self, interpreter: &mut Chalcedony) -> Result<Vec<Bytecode>, ChalError> {
         let prev_loop_scope = update_loop_scope(interpreter);
+        interpreter.lint_condition(&self.condition, true);
         let mut result = self.condition.clone().to_bytecode(interpreter)?;
 
         let cond_ty = self.condition.as_type(interpreter)?;
@@ -291,7 +360,10 @@ impl ToBytecode for NodeWhileLoop {
 
         increment_loop_scope(interpreter, result.len() + 1);
 
+        /* the body could never be executed, so it assigns nothing definitely */
+        let unassigned = interpreter.unassigned.clone();
         let body = self.body.to_bytecode(interpreter)?;
+        interpreter.unassigned = unassigned;
         let body_len = body.len() + 1; // taking into account the jump backwards
 
         result.push(Bytecode::If(body_len));
@@ -333,6 +405,13 @@ impl ToBytecode for NodeAssign {
                 annotation = VarAnnotation::new(arg.id, arg.ty.clone(), false);
                 set_instr = Bytecode::SetLocal(annotation.id);
 
+            /* only the globals, declared with `let mut`, could be mutated */
+            } else if let Some(var) = interpreter.globals.get(&root.name).filter(|var| var.is_mut) {
+                annotation = var.clone();
+                set_instr = Bytecode::SetGlobal(annotation.id);
+                interpreter.use_global(annotation.id);
+                interpreter.mark_impure();
+
             /* the variable is global, but is mutated inside a statement */
             } else {
                 return Err(
@@ -344,6 +423,7 @@ impl ToBytecode for NodeAssign {
         } else if let Some(var) = interpreter.globals.get(&root.name) {
             annotation = var.clone();
             set_instr = Bytecode::SetGlobal(annotation.id);
+            interpreter.use_global(annotation.id);
         } else {
             /* this is necessary for the proper compilation */
             unreachable!();
@@ -383,14 +463,31 @@ impl ToBytecode for NodeAssign {
             }
         }
 
-        result.extend(self.rhs.clone().to_bytecode(interpreter)?);
-
-        let rhs_ty = self.rhs.as_type(interpreter)?;
         let lhs_ty = self.lhs.as_type(interpreter)?;
-        Type::verify(lhs_ty, rhs_ty, &mut result, self.rhs.span)?;
+        /* the type of the parsed value is taken from the assigned variable */
+        let json_parse = match self.opr {
+            AssignOprType::Eq => compile_json_parse(&self.rhs, &lhs_ty, interpreter),
+            _ => None,
+        };
+        match json_parse {
+            Some(rhs) => result.extend(rhs?),
+            None => {
+                result.extend(self.rhs.clone().to_bytecode(interpreter)?);
+                let rhs_ty = self.rhs.as_type(interpreter)?;
+                Type::verify(lhs_ty, rhs_ty, &mut result, self.rhs.span)?;
+            }
+        }
 
         result.push(set_instr);
 
+        /* the deferred local variable is assigned its first value */
+        if self.lhs.resolution.len() == 1 && self.opr == AssignOprType::Eq {
+            if let Some(var) = interpreter.locals.get(&root.name) {
+                let var_id = var.id;
+                interpreter.unassigned.remove(&var_id);
+            }
+        }
+
         Ok(result)
     }
 }
@@ -403,6 +500,12 @@ impl ToBytecode for NodeRetStmnt {
             );
         };
 
+        if let Some(result) = compile_json_parse(&self.value, &func.ret_type, interpreter) {
+            let mut result = result?;
+            result.push(Bytecode::Return);
+            return Ok(result);
+        }
+
         let recv_type = self.value.as_type(interpreter)?;
         let exp_type = func.ret_type.clone();
 
@@ -413,6 +516,16 @@ impl ToBytecode for NodeRetStmnt {
         let mut result = self.value.clone().to_bytecode(interpreter)?;
 
         Type::verify(exp_type, recv_type, &mut result, self.value.span)?;
+
+        /* the returned call reuses the current call frame, unless an exception
+         * thrown inside it has to be caught by the enclosing `try` block */
+        if let Some(Bytecode::CallFunc(id)) = result.last() {
+            if interpreter.safety_scope != SafetyScope::Guarded {
+                let id = *id;
+                *result.last_mut().unwrap() = Bytecode::TailCall(id);
+                return Ok(result);
+            }
+        }
         result.push(Bytecode::Return);
 
         Ok(result)
@@ -451,25 +564,6 @@ impl ToBytecode for NodeForLoop {
     fn to_bytecode(self, interpreter: &mut Chalcedony) -> Result<Vec<Bytecode>, ChalError> {
         let prev_loop_scope = update_loop_scope(interpreter);
 
-        if interpreter.locals.contains_key(&self.iter.name) {
-            return Err(CompileError::new(
-                CompileErrorKind::RedefiningVariable,
-                self.iter.span.clone(),
-            )
-            .into());
-        }
-
-        /* check whether the variable exists as a function's argument */
-        if let Some(func) = interpreter.current_func.clone() {
-            if func.arg_lookup.get(&self.iter.name).is_some() {
-                return Err(CompileError::new(
-                    CompileErrorKind::RedefiningVariable,
-                    self.iter.span,
-                )
-                .into());
-            }
-        }
-
         let iterable_ty = self.iterable.as_type(interpreter)?;
         let iterable_class = iterable_ty.as_class();
 
@@ -500,8 +594,15 @@ impl ToBytecode for NodeForLoop {
             .into());
         };
 
+        let mut init_iterator = Vec::<Bytecode>::new();
+        init_iterator.extend(self.iterable.to_bytecode(interpreter)?);
+        init_iterator.extend(iter_fn_ann.bytecode);
+
+        /* the iterator variable could shadow any outer variable */
+        interpreter.push_scope();
         let iterator_type = next_fn_ann.ret_type;
         let iterator_id = interpreter.get_local_id_internal(&self.iter.name, iterator_type, false);
+        interpreter.declare_local(&self.iter.name, iterator_id, self.iter.span.clone());
         let iterator_gen_id = interpreter.globals_id_counter;
         interpreter.globals_id_counter += 1;
 
@@ -514,9 +615,6 @@ impl ToBytecode for NodeForLoop {
         // <body>
         // <jump-to-try-block>
 
-        let mut init_iterator = Vec::<Bytecode>::new();
-        init_iterator.extend(self.iterable.to_bytecode(interpreter)?);
-        init_iterator.extend(iter_fn_ann.bytecode);
         init_iterator.push(Bytecode::SetGlobal(iterator_gen_id));
 
         let mut for_loop = Vec::<Bytecode>::new();
@@ -535,7 +633,13 @@ impl ToBytecode for NodeForLoop {
 
         increment_loop_scope(interpreter, for_loop.len());
 
-        let body = self.body.to_bytecode(interpreter)?;
+        let unassigned = interpreter.unassigned.clone();
+        let body = self.body.to_bytecode(interpreter);
+        interpreter.unassigned = unassigned;
+        interpreter.pop_scope();
+        interpreter.globals_id_counter -= 1;
+
+        let body = body?;
         let body_len = body.len() as isize;
         for_loop.extend(body);
 
@@ -545,8 +649,6 @@ impl ToBytecode for NodeForLoop {
         for_loop.push(Bytecode::Jmp(-(for_loop.len() as isize) - 1));
         *for_loop.get_mut(jump_over_idx).unwrap() = Bytecode::Jmp(body_len + 1);
 
-        interpreter.remove_local(&self.iter.name);
-        interpreter.globals_id_counter -= 1;
         init_iterator.extend(for_loop);
 
         Ok(init_iterator)
@@ -560,25 +662,41 @@ impl ToBytecode for NodeTryCatch {
         }
 
         interpreter.safety_scope = SafetyScope::Guarded;
+        interpreter.lints.guarded_unsafe = false;
+
+        /* the `try` block could throw before assigning any variable */
+        let unassigned = interpreter.unassigned.clone();
+        let mut result_unassigned = AHashSet::<usize>::new();
+        let try_flow = block_flow(&self.try_body, &self.try_span);
+        let catch_flow = block_flow(&self.catch_body, &self.exception_var.span);
+
         /* this instruction will be overwritten by `Bytecode::TryScope()` */
         let mut result = vec![Bytecode::Nop];
         result.extend(self.try_body.to_bytecode(interpreter)?);
+        if !interpreter.lints.guarded_unsafe {
+            interpreter.push_warning(WarningKind::RedundantTry, self.try_span);
+        }
+        if !try_flow.diverges() {
+            result_unassigned.extend(interpreter.unassigned.drain());
+        }
+        interpreter.unassigned = unassigned;
 
         interpreter.safety_scope = SafetyScope::Safe;
-        if var_exists(&self.exception_var.name, interpreter) {
-            return Err(CompileError::new(
-                CompileErrorKind::RedefiningVariable,
-                self.exception_var.span,
-            )
-            .into());
-        }
         /* create the variable, holding the exception */
+        interpreter.push_scope();
         let exc_id =
             interpreter.get_local_id_internal(&self.exception_var.name, Type::Exception, false);
+        interpreter.declare_local(&self.exception_var.name, exc_id, self.exception_var.span);
         let mut catch_body = vec![Bytecode::SetLocal(exc_id)];
 
-        catch_body.extend(self.catch_body.to_bytecode(interpreter)?);
-        interpreter.remove_local(&self.exception_var.name);
+        let catch_res = self.catch_body.to_bytecode(interpreter);
+        interpreter.pop_scope();
+        catch_body.extend(catch_res?);
+
+        if !catch_flow.diverges() {
+            result_unassigned.extend(interpreter.unassigned.drain());
+        }
+        interpreter.unassigned = result_unassigned;
 
         result.push(Bytecode::CatchJmp(catch_body.len()));
         *result.get_mut(0).unwrap() = Bytecode::TryScope(result.len() - 1);
@@ -617,6 +735,10 @@ impl ToBytecode for NodeThrow {
             .into());
         }
 
+        if interpreter.safety_scope == SafetyScope::Guarded {
+            interpreter.lints.guarded_unsafe = true;
+        }
+
         let mut result = exception.to_bytecode(interpreter)?;
         result.push(Bytecode::ThrowException);
         Ok(result)
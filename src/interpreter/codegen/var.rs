@@ -1,3 +1,5 @@
+use super::consts::{cast_const, const_eval};
+use super::func::compile_json_parse;
 use super::ToBytecode;
 
 use crate::error::{ChalError, CompileError, CompileErrorKind};
@@ -8,16 +10,34 @@ use crate::common::{Bytecode, Type};
 
 impl ToBytecode for NodeVarCall {
     fn to_bytecode(self, interpreter: &mut Chalcedony) -> Result<Vec<Bytecode>, ChalError> {
+        /* the locals declared in inner blocks could shadow the arguments */
+        if let Some(var) = interpreter.locals.get(&self.name) {
+            let var_id = var.id;
+            if interpreter.unassigned.contains(&var_id) {
+                return Err(CompileError::new(
+                    CompileErrorKind::PossiblyUnassigned(self.name),
+                    self.span,
+                )
+                .into());
+            }
+            interpreter.use_local(var_id);
+            return Ok(vec![Bytecode::GetLocal(var_id)]);
+        }
         if let Some(func) = interpreter.current_func.clone() {
             if let Some(var) = func.arg_lookup.get(&self.name) {
+                interpreter.use_local(var.id);
                 return Ok(vec![Bytecode::GetLocal(var.id)]);
             }
         }
-        if let Some(var) = interpreter.locals.get(&self.name) {
-            return Ok(vec![Bytecode::GetLocal(var.id)]);
-        }
         if let Some(var) = interpreter.globals.get(&self.name) {
-            return Ok(vec![Bytecode::GetGlobal(var.id)]);
+            let var_id = var.id;
+            /* the lists and the objects could be modified in place through the
+             * reference, even if the global itself is never reassigned */
+            if matches!(var.ty, Type::List(_) | Type::Custom(_)) {
+                interpreter.mark_impure();
+            }
+            interpreter.use_global(var_id);
+            return Ok(vec![Bytecode::GetGlobal(var_id)]);
         }
         Err(CompileError::new(CompileErrorKind::UnknownVariable(self.name), self.span).into())
     }
@@ -34,6 +54,13 @@ impl ToBytecode for NodeVarDef {
             return Ok(vec![]);
         }
 
+        /* the globals could be read from any function, so they need a value */
+        if self.value.expr.is_empty() {
+            return Err(
+                CompileError::new(CompileErrorKind::MissingInitializer, self.span.clone()).into(),
+            );
+        }
+
         if var_exists(&self.name, interpreter) {
             return Err(
                 CompileError::new(CompileErrorKind::RedefiningVariable, self.span.clone()).into(),
@@ -48,6 +75,13 @@ impl ToBytecode for NodeVarDef {
 
         interpreter.verify_type(&self.ty, &self.span)?;
 
+        if let Some(result) = compile_json_parse(&self.value, &self.ty, interpreter) {
+            let mut result = result?;
+            let var_id = interpreter.get_global_id(&self);
+            result.push(Bytecode::SetGlobal(var_id));
+            return Ok(result);
+        }
+
         let mut result = self.value.clone().to_bytecode(interpreter)?;
 
         let value_type = self.value.clone().as_type(interpreter)?;
@@ -66,19 +100,28 @@ impl ToBytecode for NodeVarDef {
             .into());
         }
 
-        if value_type != Type::Void {
-            self.ty = value_type;
-        } else {
-            /* check whether no value was provided */
+        /* check whether no value was provided */
+        if value_type == Type::Void {
             return Err(CompileError::new(
                 CompileErrorKind::InvalidType(Type::Any, Type::Void),
                 self.span.clone(),
             )
             .into());
         }
+        /* the value is already cast to the annotated type */
+        if self.ty == Type::Any {
+            self.ty = value_type;
+        }
 
         let var_id = interpreter.get_global_id(&self);
         result.push(Bytecode::SetGlobal(var_id));
+
+        if self.is_const {
+            let value = const_eval(&self.value, interpreter)?;
+            if let Some(var) = interpreter.globals.get_mut(&self.name) {
+                var.value = value.and_then(|value| cast_const(value, &self.ty));
+            }
+        }
         Ok(result)
     }
 }
@@ -0,0 +1,250 @@
+//! The lints, computed while compiling the scripts. The resulting warnings do
+//! not stop the execution of the script, unless they are denied (via
+//! `Chalcedony::set_deny_warnings()`), in which case they are treated as
+//! errors.
+//!
+//! A warning could be suppressed with the comment `# chal: allow(<lint>, ...)`
+//! either at the end of the warned line or on the line above it.
+
+use super::codegen::flow::stmnt_flow;
+use super::Chalcedony;
+
+use crate::error::span::Span;
+use crate::error::{Warning, WarningKind};
+use crate::parser::ast::{NodeExpr, NodeExprInner, NodeStmnt, NodeValue};
+
+use ahash::{AHashMap, AHashSet};
+
+/// The definitions brought into scope by a single import.
+struct ImportLint {
+    funcs: Vec<usize>,
+    globals: Vec<usize>,
+    warning: Warning,
+}
+
+/// The lint information of the currently compiled script. Each imported
+/// script is linted with it's own state.
+#[derive(Default)]
+pub struct ScriptLints {
+    /// Whether the script's warnings are discarded, i.e. the standard library.
+    muted: bool,
+    /// Whether unused functions are reported. When interpreting code through
+    /// `Chalcedony::interpret()` the host could call any function, so they are
+    /// reported only for the executed script.
+    pub check_funcs: bool,
+    /// The warnings for the local variables and arguments, which are not read
+    /// yet, keyed by their stack slot, emitted if the variable goes out of scope unused.
+    pub unused_locals: AHashMap<usize, Warning>,
+    funcs: Vec<(usize, Warning)>,
+    imports: Vec<ImportLint>,
+    used_funcs: AHashSet<usize>,
+    used_globals: AHashSet<usize>,
+    /// Whether the currently compiled `try` block contains an unsafe operation.
+    pub guarded_unsafe: bool,
+}
+
+impl ScriptLints {
+    pub fn muted() -> Self {
+        ScriptLints {
+            muted: true,
+            ..Default::default()
+        }
+    }
+}
+
+impl Chalcedony {
+    pub(super) fn push_warning(&mut self, kind: WarningKind, span: Span) {
+        self.emit_warning(Warning::new(kind, span));
+    }
+
+    fn emit_warning(&mut self, mut warning: Warning) {
+        if self.lints.muted || is_suppressed(&warning) {
+            return;
+        }
+        if self.deny_warnings {
+            warning.denied = true;
+            self.failed = true;
+        }
+        self.warnings.push(warning);
+    }
+
+    /* tracks the local variable until it is read */
+    pub(super) fn declare_local(&mut self, name: &str, id: usize, span: Span) {
+        if name.starts_with('_') {
+            return;
+        }
+        if self.globals.contains_key(name) {
+            self.push_warning(
+                WarningKind::ShadowedVariable(name.to_string()),
+                span.clone(),
+            );
+        }
+        let warning = Warning::new(WarningKind::UnusedVariable(name.to_string()), span);
+        self.lints.unused_locals.insert(id, warning);
+    }
+
+    pub(super) fn declare_arg(&mut self, name: &str, id: usize, span: Span) {
+        /* the instance of methods is not required to be used */
+        if name.starts_with('_') || name == "self" {
+            return;
+        }
+        let warning = Warning::new(WarningKind::UnusedArgument(name.to_string()), span);
+        self.lints.unused_locals.insert(id, warning);
+    }
+
+    pub(super) fn use_local(&mut self, id: usize) {
+        self.lints.unused_locals.remove(&id);
+    }
+
+    pub(super) fn use_global(&mut self, id: usize) {
+        self.lints.used_globals.insert(id);
+    }
+
+    /* recursive calls do not count as uses */
+    pub(super) fn use_func(&mut self, id: usize) {
+        if self.current_func.as_ref().map(|func| func.id) != Some(id) {
+            self.lints.used_funcs.insert(id);
+        }
+    }
+
+    pub(super) fn declare_func(&mut self, id: usize, name: &str, span: Span) {
+        if !self.lints.check_funcs || name.starts_with('_') {
+            return;
+        }
+        let warning = Warning::new(WarningKind::UnusedFunction(name.to_string()), span);
+        self.lints.funcs.push((id, warning));
+    }
+
+    pub(super) fn declare_import(
+        &mut self,
+        funcs: Vec<usize>,
+        globals: Vec<usize>,
+        name: String,
+        span: Span,
+    ) {
+        if funcs.is_empty() && globals.is_empty() {
+            return;
+        }
+        self.lints.imports.push(ImportLint {
+            funcs,
+            globals,
+            warning: Warning::new(WarningKind::UnusedImport(name), span),
+        });
+    }
+
+    /* the variable goes out of scope */
+    pub(super) fn finish_local(&mut self, id: usize) {
+        if let Some(warning) = self.lints.unused_locals.remove(&id) {
+            self.emit_warning(warning);
+        }
+    }
+
+    /* all of the tracked variables go out of scope */
+    pub(super) fn finish_locals(&mut self) {
+        let mut unused: Vec<Warning> = self.lints.unused_locals.drain().map(|el| el.1).collect();
+        unused.sort_by_key(|warning| (warning.span().start.ln, warning.span().start.col));
+        for warning in unused {
+            self.emit_warning(warning);
+        }
+    }
+
+    /* emits the lints, which require the whole script to be compiled */
+    pub(super) fn finish_script_lints(&mut self) {
+        self.finish_locals();
+        let lints = std::mem::take(&mut self.lints);
+
+        for (id, warning) in lints.funcs {
+            if !lints.used_funcs.contains(&id) {
+                self.emit_warning(warning);
+            }
+        }
+
+        for import in lints.imports {
+            let used = import.funcs.iter().any(|id| lints.used_funcs.contains(id))
+                || import
+                    .globals
+                    .iter()
+                    .any(|id| lints.used_globals.contains(id));
+            if !used {
+                self.emit_warning(import.warning);
+            }
+        }
+        self.lints.muted = lints.muted;
+    }
+
+    /* checks for statements after any statement, which never finishes */
+    pub(super) fn lint_unreachable(&mut self, body: &[NodeStmnt]) {
+        let terminator = body.iter().position(|stmnt| stmnt_flow(stmnt).diverges());
+        if let Some(next) = terminator.and_then(|pos| body.get(pos + 1)) {
+            self.push_warning(WarningKind::UnreachableCode, next.span());
+        }
+    }
+
+    /* `while true` is the idiomatic infinite loop, so it is not warned */
+    pub(super) fn lint_condition(&mut self, condition: &NodeExpr, is_loop: bool) {
+        let is_constant = condition.expr.iter().all(|el| {
+            matches!(
+                el,
+                NodeExprInner::Value(_) | NodeExprInner::BinOpr(_) | NodeExprInner::UnaryOpr(_)
+            )
+        });
+        let is_infinite_loop = is_loop
+            && condition.expr.len() == 1
+            && condition.expr.front() == Some(&NodeExprInner::Value(NodeValue::Bool(true)));
+
+        if is_constant && !is_infinite_loop {
+            self.push_warning(WarningKind::ConstantCondition, condition.span.clone());
+        }
+    }
+}
+
+fn is_suppressed(warning: &Warning) -> bool {
+    let span = warning.span();
+    let lint = warning.kind().lint();
+    let ln = span.start.ln;
+
+    if let Some(line) = span.spanner.line(ln) {
+        if allows_lint(&line, lint) {
+            return true;
+        }
+    }
+
+    /* a comment on it's own line applies to the line below it */
+    match span.spanner.line(ln.saturating_sub(1)) {
+        Some(line) if line.trim_start().starts_with('#') => allows_lint(&line, lint),
+        _ => false,
+    }
+}
+
+/* checks whether the line's comment is `# chal: allow(<lint>, ...)` */
+fn allows_lint(line: &str, lint: &str) -> bool {
+    let Some(comment) = line_comment(line) else {
+        return false;
+    };
+    let Some(lints) = comment
+        .trim_start()
+        .strip_prefix("chal:")
+        .and_then(|rest| rest.trim_start().strip_prefix("allow("))
+        .and_then(|rest| rest.split_once(')'))
+    else {
+        return false;
+    };
+    lints.0.split(',').any(|el| el.trim() == lint)
+}
+
+/* returns the text after the `#`, ignoring any inside string literals */
+fn line_comment(line: &str) -> Option<&str> {
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    for (idx, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quote.is_some() => escaped = true,
+            '"' | '\'' if quote.is_none() => quote = Some(c),
+            _ if quote == Some(c) => quote = None,
+            '#' if quote.is_none() => return Some(&line[idx + 1..]),
+            _ => {}
+        }
+    }
+    None
+}
@@ -7,14 +7,32 @@ pub use codegen::ToBytecode;
 
 mod type_eval;
 
-use crate::error::{err, span::Span, ChalError, CompileError, CompileErrorKind};
-use crate::parser::ast::{NodeFuncDef, NodeProg, NodeVarDef};
+mod stdlib;
+
+mod value;
+pub use value::Value;
+
+mod natives;
+
+mod lint;
+use lint::ScriptLints;
+
+mod optimizer;
+pub use optimizer::{optimize, OptLevel};
+
+use crate::error::{
+    err, span::Span, ChalError, CompileError, CompileErrorKind, ScriptError, Warning,
+};
+use crate::parser::ast::{NodeFuncDef, NodeProg, NodeValue, NodeVarDef};
 use crate::parser::Parser;
-use crate::vm::Cvm;
+use crate::vm::{Cvm, CvmObject, Heap};
 
-use crate::common::{Bytecode, Type};
+use crate::common::{Bytecode, JsonSchema, MathFn, Type};
 
+use std::cell::Cell;
 use std::collections::VecDeque;
+use std::env;
+use std::io::{BufRead, Write};
 use std::iter::zip;
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
@@ -35,22 +53,36 @@ impl ArgAnnotation {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 struct VarAnnotation {
     id: usize,
     ty: Type,
     is_const: bool,
+    /* whether the global variable could be mutated inside functions */
+    is_mut: bool,
+    /* the compile-time value of global constants, computed from constant expressions */
+    value: Option<NodeValue>,
 }
 
 impl VarAnnotation {
     fn new(id: usize, ty: Type, is_const: bool) -> Self {
-        VarAnnotation { id, ty, is_const }
+        VarAnnotation {
+            id,
+            ty,
+            is_const,
+            is_mut: false,
+            value: None,
+        }
     }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct FuncAnnotation {
     is_unsafe: bool,
+    // Whether the function does not mutate any global variable, either
+    // directly, through a reference to a global list or object, or by calling
+    // an impure function. Set while compiling the function's body.
+    is_pure: Cell<bool>,
     id: usize,
     args: Vec<ArgAnnotation>,
     arg_lookup: AHashMap<String, ArgAnnotation>,
@@ -65,12 +97,20 @@ impl FuncAnnotation {
         }
         FuncAnnotation {
             is_unsafe,
+            is_pure: Cell::new(true),
             id,
             args,
             arg_lookup,
             ret_type,
         }
     }
+
+    /// Whether the function leaves the global state untouched. A function is
+    /// impure if it assigns to a `let mut` global, references a global list or
+    /// object (which it could modify in place) or calls an impure function.
+    pub fn is_pure(&self) -> bool {
+        self.is_pure.get()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -153,6 +193,19 @@ impl ClassNamespace {
     }
 }
 
+/// The global constants, which are accessible inside every script.
+const BUILTIN_GLOBALS: [&str; 3] = ["__name__", "PI", "E"];
+
+/// The definitions of a compiled script, which could be exposed to the scripts
+/// importing it. The exports of each script are cached, so importing the same
+/// script multiple times does not recompile it.
+#[derive(Default, Clone)]
+struct ScriptExports {
+    funcs: AHashMap<String, Vec<Rc<FuncAnnotation>>>,
+    globals: AHashMap<String, VarAnnotation>,
+    classes: AHashMap<String, ClassNamespace>,
+}
+
 /// The structure representing the interpreter, used to compile the received
 /// `AST` into a stream of `Bytecode` instructions and respectively interpret
 /// the instructions via the Chalcedony Virtual Machine (CVM).
@@ -167,7 +220,10 @@ pub struct Chalcedony {
 
     // Used to resolve issues around multiple imports of the same script
     // (similar to the Multiple Inheritance problem).
-    imported_scripts: AHashSet<PathBuf>,
+    imported_scripts: AHashMap<PathBuf, ScriptExports>,
+    // The chain of scripts which are currently being compiled, used to detect
+    // import cycles.
+    import_stack: Vec<PathBuf>,
 
     // Used to keep track of the globally declared variables.
     globals: AHashMap<String, VarAnnotation>,
@@ -190,6 +246,9 @@ pub struct Chalcedony {
     // corresponding methods' definitions. This design approach is used to serve
     // as a base for future implementation a complete namespace system.
     namespaces: AHashMap<String, ClassNamespace>,
+    // The namespaces, created by importing a script under an alias. They are
+    // not classes, so they could not be used as types.
+    module_namespaces: AHashSet<String>,
 
     // Contains the necessary information about the current function if inside a
     // function scope.
@@ -206,6 +265,16 @@ pub struct Chalcedony {
     // Keeps track of the current scope's local variables.
     locals: AHashMap<String, VarAnnotation>,
 
+    // The local variables declared in each of the currently compiled blocks,
+    // alongside the variables they shadow, restored once the block ends.
+    local_scopes: Vec<Vec<(String, Option<VarAnnotation>)>>,
+
+    // The amount of stack slots, taken by the currently visible locals.
+    locals_count: usize,
+
+    // The ids of the local variables, which are not definitely assigned yet.
+    unassigned: AHashSet<usize>,
+
     // Keeps track whether the currently compiled scope is a statement - used
     // to perform checks such as wether a `void` function is used inside an
     // expression.
@@ -214,6 +283,14 @@ pub struct Chalcedony {
     // Whether the interpreter has encountered an error, so even if an error is
     // encountered the rest of the script is still statically checked.
     failed: bool,
+
+    // The warnings of the currently compiled script, displayed after it is
+    // interpreted, and the state required to compute them.
+    warnings: Vec<Warning>,
+    lints: ScriptLints,
+    // Whether the warnings are treated as errors.
+    deny_warnings: bool,
+    opt_level: OptLevel,
 }
 
 trait InterpreterVisitor {
@@ -222,34 +299,73 @@ trait InterpreterVisitor {
 
 impl InterpreterVisitor for Chalcedony {
     fn interpret_node(&mut self, node: NodeProg) -> Result<(), ChalError> {
-        let bytecode = node.to_bytecode(self)?;
+        let (func_id, global_id) = (self.func_id_counter, self.globals_id_counter);
+        let bytecode = match node.to_bytecode(self) {
+            Ok(bytecode) => bytecode,
+            Err(err) => {
+                self.unload_definitions(func_id, global_id);
+                return Err(err);
+            }
+        };
+
         /* this is so all of the errors in the code are displayed */
-        if !self.failed {
-            self.vm.execute(bytecode);
+        if self.failed {
+            self.unload_definitions(func_id, global_id);
+            return Ok(());
         }
+        let bytecode = self.optimized(bytecode);
+        self.vm.execute(bytecode);
         Ok(())
     }
 }
 
 impl Chalcedony {
     pub fn new() -> Self {
+        Chalcedony::with_vm(Cvm::new())
+    }
+
+    /// Creates an interpreter, whose scripts write their output to `stdout`,
+    /// read their input from `stdin`, and whose errors are written to
+    /// `stderr`.
+    pub fn with_io(
+        stdout: impl Write + 'static,
+        stderr: impl Write + 'static,
+        stdin: impl BufRead + 'static,
+    ) -> Self {
+        Chalcedony::with_vm(Cvm::with_io(
+            Box::new(stdout),
+            Box::new(stderr),
+            Box::new(stdin),
+        ))
+    }
+
+    fn with_vm(vm: Cvm) -> Self {
         let mut res = Chalcedony {
-            vm: Cvm::new(),
+            vm,
             script_type: ScriptType::Main,
             current_path: PathBuf::new(),
-            imported_scripts: AHashSet::new(),
+            imported_scripts: AHashMap::new(),
+            import_stack: Vec::new(),
             globals: AHashMap::new(),
             globals_id_counter: 1, // the `__main__` constant
             builtins: get_builtins(),
             func_symtable: AHashMap::new(),
             func_id_counter: 0,
             namespaces: AHashMap::new(),
+            module_namespaces: AHashSet::new(),
             current_func: None,
             safety_scope: SafetyScope::Normal,
             current_loop: None,
             locals: AHashMap::default(),
+            local_scopes: Vec::new(),
+            locals_count: 0,
+            unassigned: AHashSet::new(),
             inside_stmnt: false,
             failed: false,
+            warnings: Vec::new(),
+            lints: ScriptLints::default(),
+            deny_warnings: false,
+            opt_level: OptLevel::default(),
         };
 
         let script_const_id = res.get_global_id_internal("__name__", Type::Str, true);
@@ -258,10 +374,26 @@ impl Chalcedony {
             Bytecode::SetGlobal(script_const_id),
         ]);
 
+        let pi_id = res.get_global_id_internal("PI", Type::Float, true);
+        let e_id = res.get_global_id_internal("E", Type::Float, true);
+        res.vm.execute(vec![
+            Bytecode::ConstF(std::f64::consts::PI),
+            Bytecode::SetGlobal(pi_id),
+            Bytecode::ConstF(std::f64::consts::E),
+            Bytecode::SetGlobal(e_id),
+        ]);
+        for (name, value) in [("PI", std::f64::consts::PI), ("E", std::f64::consts::E)] {
+            if let Some(var) = res.globals.get_mut(name) {
+                var.value = Some(NodeValue::Float(value));
+            }
+        }
+
+        natives::register_natives(&mut res).expect("the natives should not collide");
         res
     }
 
     pub fn interpret(&mut self, code: &str) {
+        self.vm.reset_exit();
         let mut parser = Parser::new(code);
         self.interpret_internal(&mut parser);
     }
@@ -274,18 +406,385 @@ impl Chalcedony {
             );
             std::process::exit(1);
         };
-        self.current_path = PathBuf::from(filename)
-            .parent()
-            .unwrap_or(Path::new(""))
-            .to_owned();
+        self.vm.reset_exit();
+        let script_path = PathBuf::from(filename);
+        self.current_path = script_path.parent().unwrap_or(Path::new("")).to_owned();
+
+        self.import_stack
+            .push(script_path.canonicalize().unwrap_or(script_path));
+        self.lints.check_funcs = true;
         self.interpret_internal(&mut parser);
+        self.import_stack.pop();
+    }
+
+    /// Treats the warnings as errors, so a script with any warnings is not
+    /// executed.
+    pub fn set_deny_warnings(&mut self, deny: bool) {
+        self.deny_warnings = deny;
+    }
+
+    /// Sets the optimizations, applied to the generated bytecode before it is
+    /// executed.
+    pub fn set_opt_level(&mut self, level: OptLevel) {
+        self.opt_level = level;
+    }
+
+    fn optimized(&self, code: Vec<Bytecode>) -> Vec<Bytecode> {
+        match self.opt_level {
+            OptLevel::O0 => code,
+            OptLevel::O1 => optimize(code),
+        }
+    }
+
+    /// Whether the last interpreted script has encountered any errors (or
+    /// denied warnings), or has been stopped by an unhandled exception.
+    pub fn has_failed(&self) -> bool {
+        self.failed
+    }
+
+    /// The exit code, passed to `exit()` by the last interpreted script. The
+    /// rest of the script is not executed after the call.
+    pub fn exit_code(&self) -> Option<i32> {
+        self.vm.exit_code()
+    }
+
+    /// Sets the maximum number of nested function calls inside the scripts.
+    /// Exceeding it raises the exception `maximum recursion depth exceeded`,
+    /// which could be caught by the scripts. Defaults to
+    /// `vm::DEFAULT_RECURSION_LIMIT`.
+    pub fn set_recursion_limit(&mut self, limit: usize) {
+        self.vm.set_recursion_limit(limit);
+    }
+
+    /// Sets the maximum number of values on the `CVM`'s stack. A function call
+    /// exceeding it raises the exception `maximum stack size exceeded`.
+    /// Defaults to `vm::DEFAULT_STACK_LIMIT`.
+    pub fn set_stack_limit(&mut self, limit: usize) {
+        self.vm.set_stack_limit(limit);
+    }
+
+    /// Sets the command line arguments, returned by the builtin `argv()`.
+    pub fn set_argv(&mut self, args: Vec<String>) {
+        let arg_count = args.len();
+        let mut bytecode: Vec<Bytecode> = args
+            .into_iter()
+            .map(|arg| Bytecode::ConstS(arg.into()))
+            .collect();
+        bytecode.push(Bytecode::ConstL(arg_count));
+
+        let argv = self
+            .builtins
+            .get_mut("Global")
+            .and_then(|global| global.get_mut("argv"))
+            .and_then(|bucket| bucket.first_mut())
+            .expect("expected the builtin `argv()`");
+        argv.bytecode = bytecode;
+    }
+
+    /// Exposes the host function `func` to the scripts under the name `name`.
+    /// If a `namespace` is passed, the function is called via
+    /// `<namespace>::<name>()`. Just like the script functions, host functions
+    /// whose name ends with `!` are unsafe and only they should return `Err()`,
+    /// since safe code does not guard against exceptions. The functions
+    /// registered under the namespace `std::<module>` form a native module,
+    /// which the scripts bring into scope via `import std::<module>`. Fails
+    /// if a function with the same name and argument types already exists.
+    pub fn register_fn<F>(
+        &mut self,
+        namespace: Option<&str>,
+        name: &str,
+        arg_types: Vec<Type>,
+        ret_type: Type,
+        func: F,
+    ) -> Result<(), ScriptError>
+    where
+        F: Fn(&[CvmObject]) -> Result<CvmObject, String> + 'static,
+    {
+        self.register_heap_fn(namespace, name, arg_types, ret_type, move |_, args| {
+            func(args)
+        })
+    }
+
+    /* registers a host function, which creates or modifies objects on the heap */
+    pub(crate) fn register_heap_fn<F>(
+        &mut self,
+        namespace: Option<&str>,
+        name: &str,
+        arg_types: Vec<Type>,
+        ret_type: Type,
+        func: F,
+    ) -> Result<(), ScriptError>
+    where
+        F: Fn(&mut Heap, &[CvmObject]) -> Result<CvmObject, String> + 'static,
+    {
+        let namespace = namespace.unwrap_or("Global");
+        let arg_lookup: VecDeque<Type> = arg_types.clone().into();
+        if self
+            .get_builtin(name, &arg_lookup, Some(namespace))
+            .is_some()
+        {
+            return Err(ScriptError::FunctionAlreadyExists(name.to_string()));
+        }
+
+        let native_id =
+            self.vm
+                .register_heap_native(arg_types.len(), ret_type == Type::Void, Box::new(func));
+        let args = arg_types
+            .into_iter()
+            .enumerate()
+            .map(|(idx, ty)| ArgAnnotation::new(idx, format!("arg{}", idx), ty))
+            .collect();
+
+        let annotation = BuiltinAnnotation {
+            args,
+            ret_type,
+            bytecode: vec![Bytecode::CallNative(native_id)],
+        };
+        self.builtins
+            .entry(namespace.to_string())
+            .or_default()
+            .entry(name.to_string())
+            .or_default()
+            .push(annotation);
+        Ok(())
+    }
+
+    /// Calls the script function `name` with the passed arguments, resolving
+    /// it's overloads just like a call inside the script would. Class methods
+    /// and namespaced functions are called as `<namespace>::<name>`.
+    pub fn call(&mut self, name: &str, args: &[Value]) -> Result<Value, ScriptError> {
+        let (namespace, func_name) = match name.rsplit_once("::") {
+            Some((namespace, func_name)) => (Some(namespace.to_string()), func_name),
+            None => (None, name),
+        };
+
+        let arg_types: VecDeque<Type> = args.iter().map(Value::as_type).collect();
+        let Some(func) = self
+            .get_function(func_name, &arg_types, namespace.as_ref())
+            .cloned()
+        else {
+            return Err(ScriptError::UnknownFunction(name.to_string()));
+        };
+
+        let mut cvm_args = Vec::<CvmObject>::with_capacity(args.len());
+        for (arg, annotation) in zip(args, &func.args) {
+            cvm_args.push(self.value_to_object(arg, &annotation.ty)?);
+        }
+
+        self.vm.reset_exit();
+        let result = self.vm.call(func.id, cvm_args);
+        if let Some(code) = self.vm.exit_code() {
+            return Err(ScriptError::Exit(code));
+        }
+        match result {
+            Ok(Some(obj)) => Ok(self.object_to_value(obj, &func.ret_type)),
+            Ok(None) => Ok(Value::Void),
+            Err(exc) => Err(ScriptError::Exception(exc)),
+        }
+    }
+
+    /// Whether the script function `name`, taking arguments of the passed
+    /// types, leaves the global state untouched, i.e. neither it nor any
+    /// function it calls assigns to a `let mut` global or references a global
+    /// list or object. Reading the other globals is pure. The overloads and the
+    /// namespaces are resolved just like in `Chalcedony::call()`.
+    pub fn is_pure(&self, name: &str, arg_types: &[Type]) -> Result<bool, ScriptError> {
+        let (namespace, func_name) = match name.rsplit_once("::") {
+            Some((namespace, func_name)) => (Some(namespace.to_string()), func_name),
+            None => (None, name),
+        };
+
+        let arg_types: VecDeque<Type> = arg_types.iter().cloned().collect();
+        match self.get_function(func_name, &arg_types, namespace.as_ref()) {
+            Some(func) => Ok(func.is_pure()),
+            None => Err(ScriptError::UnknownFunction(name.to_string())),
+        }
+    }
+
+    /* searches for the script relative to the current one and inside `CHAL_PATH` */
+    fn resolve_script(&self, path: &str) -> Option<PathBuf> {
+        let mut candidates = vec![self.current_path.join(path)];
+        if let Some(search_path) = env::var_os("CHAL_PATH") {
+            for dir in env::split_paths(&search_path) {
+                candidates.push(dir.join(path));
+            }
+        }
+
+        for candidate in candidates {
+            if candidate.is_file() {
+                return Some(candidate);
+            }
+
+            let mut with_ext = candidate.into_os_string();
+            with_ext.push(".ch");
+            let with_ext = PathBuf::from(with_ext);
+            if with_ext.is_file() {
+                return Some(with_ext);
+            }
+        }
+        None
+    }
+
+    /// Compiles and executes the script in an isolated scope, returning it's
+    /// definitions. Scripts, which are already imported, are not recompiled.
+    fn import_script(
+        &mut self,
+        script_path: PathBuf,
+        span: &Span,
+    ) -> Result<ScriptExports, ChalError> {
+        let script_key = script_path
+            .canonicalize()
+            .unwrap_or_else(|_| script_path.clone());
+        if let Some(exports) = self.cached_import(&script_key, span)? {
+            return Ok(exports);
+        }
+
+        let Some(parser) = Parser::from_file(script_path.to_str().unwrap().to_string()) else {
+            return Err(CompileError::new(
+                CompileErrorKind::ScriptNotFound(script_path.display().to_string()),
+                span.clone(),
+            )
+            .into());
+        };
+
+        let script_dir = script_path.parent().unwrap_or(Path::new("")).to_owned();
+        let script_name = script_dir.to_str().unwrap().to_string();
+        self.import_internal(
+            script_key,
+            parser,
+            script_dir,
+            script_name,
+            ScriptLints::default(),
+        )
+    }
+
+    /// Same as `Chalcedony::import_script()`, but for the embedded standard
+    /// library modules.
+    fn import_std_module(&mut self, name: &str, span: &Span) -> Result<ScriptExports, ChalError> {
+        let Some(source) = stdlib::get_std_module(name) else {
+            return Err(CompileError::new(
+                CompileErrorKind::UnknownStdModule(name.to_string()),
+                span.clone(),
+            )
+            .into());
+        };
+
+        let module_name = format!("std::{}", name);
+        let module_key = PathBuf::from(&module_name);
+        if let Some(exports) = self.cached_import(&module_key, span)? {
+            return Ok(exports);
+        }
+
+        let parser = Parser::from_source(source, module_name.clone());
+        /* the standard library is not linted */
+        self.import_internal(
+            module_key,
+            parser,
+            PathBuf::new(),
+            module_name,
+            ScriptLints::muted(),
+        )
+    }
+
+    /* checks whether the script is already imported or is part of an import cycle */
+    fn cached_import(
+        &self,
+        script_key: &PathBuf,
+        span: &Span,
+    ) -> Result<Option<ScriptExports>, ChalError> {
+        if let Some(exports) = self.imported_scripts.get(script_key) {
+            return Ok(Some(exports.clone()));
+        }
+
+        if let Some(pos) = self.import_stack.iter().position(|el| el == script_key) {
+            let mut chain: Vec<String> = self.import_stack[pos..]
+                .iter()
+                .map(|el| el.display().to_string())
+                .collect();
+            chain.push(script_key.display().to_string());
+            return Err(
+                CompileError::new(CompileErrorKind::ImportCycle(chain), span.clone()).into(),
+            );
+        }
+        Ok(None)
+    }
+
+    fn import_internal(
+        &mut self,
+        script_key: PathBuf,
+        mut parser: Parser,
+        script_dir: PathBuf,
+        script_name: String,
+        lints: ScriptLints,
+    ) -> Result<ScriptExports, ChalError> {
+        /* the imported script is compiled with it's own symbol tables */
+        let parent_funcs = std::mem::take(&mut self.func_symtable);
+        let mut script_globals = AHashMap::<String, VarAnnotation>::new();
+        for name in BUILTIN_GLOBALS {
+            if let Some(var) = self.globals.get(name) {
+                script_globals.insert(name.to_string(), var.clone());
+            }
+        }
+        let parent_globals = std::mem::replace(&mut self.globals, script_globals);
+
+        let parent_script_type = self.script_type;
+        self.script_type = ScriptType::Imported;
+        let parent_path = std::mem::replace(&mut self.current_path, script_dir);
+        let parent_failed = self.failed;
+        let parent_lints = std::mem::replace(&mut self.lints, lints);
+        /* the classes are brought back by the importing script, which checks
+         * them for collisions with it's own */
+        let parent_namespaces = std::mem::take(&mut self.namespaces);
+        let parent_modules = std::mem::take(&mut self.module_namespaces);
+
+        let script_const_id = self.get_global_id_internal("__name__", Type::Str, true);
+        self.vm.execute(vec![
+            Bytecode::ConstS(script_name.into()),
+            Bytecode::SetGlobal(script_const_id),
+        ]);
+
+        self.import_stack.push(script_key.clone());
+        self.interpret_internal(&mut parser);
+        self.import_stack.pop();
+
+        self.failed |= parent_failed;
+        self.lints = parent_lints;
+        self.script_type = parent_script_type;
+        self.current_path = parent_path;
+
+        let name_value = match self.script_type {
+            ScriptType::Main => "__main__".to_string(),
+            ScriptType::Imported => self.current_path.to_str().unwrap().to_string(),
+        };
+        self.vm.execute(vec![
+            Bytecode::ConstS(name_value.into()),
+            Bytecode::SetGlobal(script_const_id),
+        ]);
+
+        let script_modules = std::mem::replace(&mut self.module_namespaces, parent_modules);
+        let mut exports = ScriptExports {
+            funcs: std::mem::replace(&mut self.func_symtable, parent_funcs),
+            globals: std::mem::replace(&mut self.globals, parent_globals),
+            classes: std::mem::replace(&mut self.namespaces, parent_namespaces)
+                .into_iter()
+                .filter(|(name, _)| !script_modules.contains(name))
+                .collect(),
+        };
+        for name in BUILTIN_GLOBALS {
+            exports.globals.remove(name);
+        }
+
+        self.imported_scripts.insert(script_key, exports.clone());
+        Ok(exports)
     }
 
     fn interpret_internal(&mut self, parser: &mut Parser) {
         let mut errors = Vec::<ChalError>::new();
         self.failed = false;
 
-        while !parser.is_empty() {
+        /* nothing is compiled after `exit()` or an unhandled exception, since
+         * the script has stopped */
+        while !parser.is_empty() && !self.vm.is_stopped() {
             match parser.advance() {
                 Ok(node) => {
                     if let Err(err) = self.interpret_node(node) {
@@ -300,13 +799,39 @@ impl Chalcedony {
             }
         }
 
+        if self.vm.unhandled_exception().is_some() {
+            self.failed = true;
+        }
+        self.finish_script_lints();
+        for warning in std::mem::take(&mut self.warnings) {
+            write!(self.vm.stderr, "{}", warning).expect("could not write to stderr");
+        }
+
         if !errors.is_empty() {
             for err in errors {
-                eprint!("{}", err);
+                write!(self.vm.stderr, "{}", err).expect("could not write to stderr");
             }
         }
     }
 
+    /* removes the functions and the globals, declared since the passed ids,
+     * whose code is never executed, so neither the scripts nor the host could
+     * call into them */
+    fn unload_definitions(&mut self, func_id: usize, global_id: usize) {
+        let is_loaded = |func: &Rc<FuncAnnotation>| func.id < func_id;
+        for bucket in self.func_symtable.values_mut() {
+            bucket.retain(is_loaded);
+        }
+        self.func_symtable.retain(|_, bucket| !bucket.is_empty());
+        for namespace in self.namespaces.values_mut() {
+            for bucket in namespace.methods.values_mut() {
+                bucket.retain(is_loaded);
+            }
+            namespace.methods.retain(|_, bucket| !bucket.is_empty());
+        }
+        self.globals.retain(|_, var| var.id < global_id);
+    }
+
     /* builds the function and sets the currennt function scope */
     fn create_function(&mut self, node: &NodeFuncDef, args: Vec<ArgAnnotation>) {
         let func = Rc::new(FuncAnnotation::new(
@@ -318,7 +843,7 @@ impl Chalcedony {
         self.func_id_counter += 1;
 
         self.current_func = Some(func.clone());
-        self.locals = AHashMap::new();
+        self.reset_locals();
 
         let mut func_symtable = &mut self.func_symtable;
         if let Some(class) = node.namespace.clone() {
@@ -345,6 +870,22 @@ impl Chalcedony {
     ) -> Option<BuiltinAnnotation> {
         let namespace = namespace.unwrap_or("Global");
         let bucket = self.builtins.get(namespace)?.get(name)?;
+
+        /* `format()` is variadic, so its arguments depend on the call */
+        if namespace == "Global" && name == "format" {
+            if arg_types.front() != Some(&Type::Str) {
+                return None;
+            }
+            let mut args = vec![ArgAnnotation::new(0, "fmt".to_string(), Type::Str)];
+            for idx in 1..arg_types.len() {
+                args.push(ArgAnnotation::new(idx, format!("arg{}", idx), Type::Any));
+            }
+            return Some(BuiltinAnnotation {
+                args,
+                ret_type: Type::Str,
+                bytecode: vec![],
+            });
+        }
         /* inlining the clippy suggestion does not help due to the Rc inside */
         #[allow(clippy::manual_find)]
         for annotation in bucket {
@@ -382,6 +923,27 @@ impl Chalcedony {
                     };
                 }
 
+                if namespace == "Random" && name == "choice!" {
+                    let Type::List(inner_ty) = arg_types.get(1).unwrap() else {
+                        panic!("improper func arg checks")
+                    };
+                    return Some(BuiltinAnnotation {
+                        args: annotation.args.clone(),
+                        ret_type: *inner_ty.clone(),
+                        bytecode: annotation.bytecode.clone(),
+                    });
+                }
+
+                /* the layout of the serialized value depends on its type */
+                if namespace == "Global" && name == "json_stringify" {
+                    let schema = self.json_schema(arg_types.front().unwrap()).ok()?;
+                    return Some(BuiltinAnnotation {
+                        args: annotation.args.clone(),
+                        ret_type: annotation.ret_type.clone(),
+                        bytecode: vec![Bytecode::JsonStringify(Rc::new(schema))],
+                    });
+                }
+
                 if namespace == "General" && name == "copy" {
                     return Some(BuiltinAnnotation {
                         args: annotation.args.clone(),
@@ -395,6 +957,13 @@ impl Chalcedony {
         None
     }
 
+    /* the current function can no longer guarantee it's purity */
+    fn mark_impure(&self) {
+        if let Some(func) = &self.current_func {
+            func.is_pure.set(false);
+        }
+    }
+
     /* receives the proper overloaded function annotation from the passed argument types */
     fn get_function(
         &self,
@@ -435,7 +1004,11 @@ impl Chalcedony {
 
     /* retrieves the global variable's id and creates it if it does not exist */
     fn get_global_id(&mut self, node: &NodeVarDef) -> usize {
-        self.get_global_id_internal(&node.name, node.ty.clone(), node.is_const)
+        let id = self.get_global_id_internal(&node.name, node.ty.clone(), node.is_const);
+        if let Some(var) = self.globals.get_mut(&node.name) {
+            var.is_mut = node.is_mut;
+        }
+        id
     }
 
     fn get_global_id_internal(&mut self, name: &str, ty: Type, is_const: bool) -> usize {
@@ -450,34 +1023,90 @@ impl Chalcedony {
         self.globals_id_counter - 1
     }
 
+    /* retrieves the compile-time value of the constant, visible under the name */
+    fn get_const_value(&mut self, name: &str) -> Option<NodeValue> {
+        /* only the globals could be constants, but they could be shadowed */
+        if self.locals.contains_key(name) {
+            return None;
+        }
+        if let Some(func) = &self.current_func {
+            if func.arg_lookup.contains_key(name) {
+                return None;
+            }
+        }
+        let var = self.globals.get(name)?;
+        let (id, value) = (var.id, var.value.clone()?);
+        self.use_global(id);
+        Some(value)
+    }
+
     /* retrieves the local variable's id and creates it if it does not exist */
     fn get_local_id(&mut self, node: &NodeVarDef) -> usize {
         self.get_local_id_internal(&node.name, node.ty.clone(), node.is_const)
     }
 
+    /* every declaration takes a new slot, so inner blocks could shadow variables */
     fn get_local_id_internal(&mut self, name: &str, ty: Type, is_const: bool) -> usize {
         let mut arg_count = 0;
         if let Some(func) = &self.current_func {
             arg_count = func.args.len();
         }
-        if let Some(var) = self.locals.get(name) {
-            return var.id;
-        }
 
-        let next_id = self.locals.len() + arg_count;
-        self.locals
+        let next_id = self.locals_count + arg_count;
+        self.locals_count += 1;
+        let shadowed = self
+            .locals
             .insert(name.to_string(), VarAnnotation::new(next_id, ty, is_const));
+        if let Some(scope) = self.local_scopes.last_mut() {
+            scope.push((name.to_string(), shadowed));
+        }
         next_id
     }
 
-    fn remove_local(&mut self, name: &str) {
-        self.locals.remove(name);
+    /* checks whether the variable is declared in the innermost block */
+    fn is_local_in_block(&self, name: &str) -> bool {
+        match self.local_scopes.last() {
+            Some(scope) => scope.iter().any(|(el, _)| el == name),
+            None => self.locals.contains_key(name),
+        }
+    }
+
+    fn push_scope(&mut self) {
+        self.local_scopes.push(Vec::new());
+    }
+
+    /* the block's variables go out of scope and their slots are reused */
+    fn pop_scope(&mut self) {
+        let Some(scope) = self.local_scopes.pop() else {
+            return;
+        };
+        for (name, shadowed) in scope.into_iter().rev() {
+            if let Some(var) = self.locals.remove(&name) {
+                self.finish_local(var.id);
+                self.unassigned.remove(&var.id);
+            }
+            if let Some(shadowed) = shadowed {
+                self.locals.insert(name, shadowed);
+            }
+            self.locals_count -= 1;
+        }
+    }
+
+    fn reset_locals(&mut self) {
+        self.locals = AHashMap::new();
+        self.local_scopes = Vec::new();
+        self.locals_count = 0;
+        self.unassigned = AHashSet::new();
+    }
+
+    fn is_class(&self, name: &str) -> bool {
+        self.namespaces.contains_key(name) && !self.module_namespaces.contains(name)
     }
 
     fn verify_type(&self, ty: &Type, span: &Span) -> Result<(), ChalError> {
         match ty {
             Type::Custom(ty) => {
-                if !(self.builtins.contains_key(&**ty) || self.namespaces.contains_key(&**ty)) {
+                if !(self.builtins.contains_key(&**ty) || self.is_class(ty)) {
                     return Err(CompileError::new(
                         CompileErrorKind::TypeDoesNotExits(*ty.clone()),
                         span.clone(),
@@ -494,6 +1123,48 @@ impl Chalcedony {
             _ => Ok(()),
         }
     }
+
+    /* on failure returns the type, which could not be represented as JSON */
+    fn json_schema(&self, ty: &Type) -> Result<JsonSchema, Type> {
+        self.json_schema_internal(ty, &mut vec![])
+    }
+
+    fn json_schema_internal(
+        &self,
+        ty: &Type,
+        classes: &mut Vec<String>,
+    ) -> Result<JsonSchema, Type> {
+        match ty {
+            Type::Int => Ok(JsonSchema::Int),
+            Type::Uint => Ok(JsonSchema::Uint),
+            Type::Float => Ok(JsonSchema::Float),
+            Type::Str => Ok(JsonSchema::Str),
+            Type::Bool => Ok(JsonSchema::Bool),
+            Type::List(el_ty) => Ok(JsonSchema::List(Box::new(
+                self.json_schema_internal(el_ty, classes)?,
+            ))),
+            /* a recursive class refers back to it's enclosing schema */
+            Type::Custom(class) if classes.contains(class) => Ok(JsonSchema::Recursive(
+                classes.iter().position(|el| el == &**class).unwrap(),
+            )),
+            Type::Custom(class) if self.is_class(class) => {
+                let namespace = self.namespaces.get(&**class).unwrap();
+                classes.push(*class.clone());
+
+                let mut members = namespace.members.clone();
+                members.sort_by_key(|member| member.id);
+                let mut result = Vec::<(String, JsonSchema)>::with_capacity(members.len());
+                for member in members {
+                    let member_schema = self.json_schema_internal(&member.ty, classes)?;
+                    result.push((member.name, member_schema));
+                }
+
+                classes.pop();
+                Ok(JsonSchema::Object(result))
+            }
+            _ => Err(ty.clone()),
+        }
+    }
 }
 
 /// Wrapper functions, used for tests.
@@ -529,12 +1200,61 @@ fn get_builtins() -> AHashMap<String, AHashMap<String, Vec<BuiltinAnnotation>>>
         bytecode: vec![Bytecode::Print],
     };
 
+    let input = BuiltinAnnotation {
+        args: vec![],
+        ret_type: Type::Str,
+        bytecode: vec![Bytecode::ConstS(String::new().into()), Bytecode::Input],
+    };
+    let input_prompt = BuiltinAnnotation {
+        args: vec![ArgAnnotation::new(0, "prompt".to_string(), Type::Str)],
+        ret_type: Type::Str,
+        bytecode: vec![Bytecode::Input],
+    };
+
+    let exit = BuiltinAnnotation {
+        args: vec![ArgAnnotation::new(0, "code".to_string(), Type::Int)],
+        ret_type: Type::Void,
+        bytecode: vec![Bytecode::Exit],
+    };
+    let argv = BuiltinAnnotation {
+        args: vec![],
+        ret_type: Type::List(Box::new(Type::Str)),
+        bytecode: vec![Bytecode::ConstL(0)],
+    };
+
+    // the arguments are resolved in `get_builtin()` and the format string is
+    // compiled in `compile_func_call_inner()`
+    let format = BuiltinAnnotation {
+        args: vec![ArgAnnotation::new(0, "fmt".to_string(), Type::Str)],
+        ret_type: Type::Str,
+        bytecode: vec![],
+    };
+
+    // the schema is resolved from the argument's type in `get_builtin()`
+    let json_stringify = BuiltinAnnotation {
+        args: vec![ArgAnnotation::new(0, "val".to_string(), Type::Any)],
+        ret_type: Type::Str,
+        bytecode: vec![],
+    };
+
     let assert = BuiltinAnnotation {
         args: vec![ArgAnnotation::new(0, "expr".to_string(), Type::Bool)],
         ret_type: Type::Void,
         bytecode: vec![Bytecode::Assert],
     };
 
+    // diagnostics of the garbage collector
+    let gc_collect = BuiltinAnnotation {
+        args: vec![],
+        ret_type: Type::Uint,
+        bytecode: vec![Bytecode::GcCollect],
+    };
+    let gc_stats = BuiltinAnnotation {
+        args: vec![],
+        ret_type: Type::Str,
+        bytecode: vec![Bytecode::GcStats],
+    };
+
     let utoi = BuiltinAnnotation {
         args: vec![ArgAnnotation::new(0, "val".to_string(), Type::Uint)],
         ret_type: Type::Int,
@@ -686,9 +1406,16 @@ fn get_builtins() -> AHashMap<String, AHashMap<String, Vec<BuiltinAnnotation>>>
         bytecode: vec![Bytecode::ConstI(0), Bytecode::ListRemove],
     };
 
-    let global_map = builtin_map!(
+    let mut global_map = builtin_map!(
         "print" => vec![print],
+        "input" => vec![input, input_prompt],
         "assert" => vec![assert],
+        "exit" => vec![exit],
+        "argv" => vec![argv],
+        "format" => vec![format],
+        "json_stringify" => vec![json_stringify],
+        "gc_collect" => vec![gc_collect],
+        "gc_stats" => vec![gc_stats],
         "utoi" => vec![utoi],
         "ftoi" => vec![ftoi],
         "itou" => vec![itou],
@@ -698,6 +1425,7 @@ fn get_builtins() -> AHashMap<String, AHashMap<String, Vec<BuiltinAnnotation>>>
         "len" => vec![len_list.clone(), len_str],
         "copy" => vec![copy.clone()],
     );
+    global_map.extend(get_math_builtins());
     let list_map = builtin_map!(
         "len" => vec![len_list],
         "copy" => vec![copy],
@@ -720,6 +1448,111 @@ fn get_builtins() -> AHashMap<String, AHashMap<String, Vec<BuiltinAnnotation>>>
     )
 }
 
+fn math_annotation(args: &[Type], ret_type: Type, bytecode: Vec<Bytecode>) -> BuiltinAnnotation {
+    let args = args
+        .iter()
+        .enumerate()
+        .map(|(idx, ty)| ArgAnnotation::new(idx, format!("x{}", idx), ty.clone()))
+        .collect();
+    BuiltinAnnotation {
+        args,
+        ret_type,
+        bytecode,
+    }
+}
+
+// The overloads are ordered so the exact types are matched before the implicit
+// cast from `uint` to `int`.
+fn get_math_builtins() -> AHashMap<String, Vec<BuiltinAnnotation>> {
+    let mut result = AHashMap::<String, Vec<BuiltinAnnotation>>::new();
+
+    let float_funcs = [
+        ("sqrt", MathFn::Sqrt),
+        ("exp", MathFn::Exp),
+        ("ln", MathFn::Ln),
+        ("log10", MathFn::Log10),
+        ("sin", MathFn::Sin),
+        ("cos", MathFn::Cos),
+        ("tan", MathFn::Tan),
+        ("asin", MathFn::Asin),
+        ("acos", MathFn::Acos),
+        ("atan", MathFn::Atan),
+    ];
+    for (name, func) in float_funcs {
+        result.insert(
+            name.to_string(),
+            vec![math_annotation(
+                &[Type::Float],
+                Type::Float,
+                vec![Bytecode::Math(func)],
+            )],
+        );
+    }
+
+    let float_checks = [
+        ("floor", MathFn::Floor, Type::Int),
+        ("ceil", MathFn::Ceil, Type::Int),
+        ("round", MathFn::Round, Type::Int),
+        ("is_nan", MathFn::IsNan, Type::Bool),
+        ("is_inf", MathFn::IsInf, Type::Bool),
+    ];
+    for (name, func, ret_type) in float_checks {
+        result.insert(
+            name.to_string(),
+            vec![math_annotation(
+                &[Type::Float],
+                ret_type,
+                vec![Bytecode::Math(func)],
+            )],
+        );
+    }
+
+    let pow = Bytecode::Math(MathFn::Pow);
+    result.insert(
+        "pow".to_string(),
+        vec![
+            math_annotation(&[Type::Uint, Type::Uint], Type::Uint, vec![pow.clone()]),
+            math_annotation(&[Type::Int, Type::Uint], Type::Int, vec![pow.clone()]),
+            math_annotation(&[Type::Float, Type::Float], Type::Float, vec![pow.clone()]),
+            math_annotation(
+                &[Type::Float, Type::Int],
+                Type::Float,
+                vec![Bytecode::CastF, pow],
+            ),
+        ],
+    );
+
+    /* unlike `pow()`, which saturates, an integer overflow raises an exception */
+    let checked_pow = Bytecode::Math(MathFn::CheckedPow);
+    result.insert(
+        "pow!".to_string(),
+        vec![
+            math_annotation(
+                &[Type::Uint, Type::Uint],
+                Type::Uint,
+                vec![checked_pow.clone()],
+            ),
+            math_annotation(&[Type::Int, Type::Uint], Type::Int, vec![checked_pow]),
+        ],
+    );
+
+    let numeric_funcs = [
+        ("abs", MathFn::Abs, 1),
+        ("min", MathFn::Min, 2),
+        ("max", MathFn::Max, 2),
+        ("clamp", MathFn::Clamp, 3),
+    ];
+    for (name, func, arg_count) in numeric_funcs {
+        let overloads = [Type::Uint, Type::Int, Type::Float]
+            .into_iter()
+            .map(|ty| math_annotation(&vec![ty.clone(); arg_count], ty, vec![Bytecode::Math(func)]))
+            .collect();
+        result.insert(name.to_string(), overloads);
+    }
+
+    result
+}
+
 /* checks whether the passed arguments match the function annotation */
 fn valid_annotation(args: &[ArgAnnotation], received: &VecDeque<Type>) -> bool {
     if args.len() != received.len() {
@@ -0,0 +1,276 @@
+use super::{export_module, str_arg, str_list, uint_arg, void};
+use crate::common::Type;
+use crate::error::ScriptError;
+use crate::interpreter::Chalcedony;
+use crate::vm::CvmObject;
+
+use std::cell::RefCell;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::rc::Rc;
+
+// Inside the scripts a `File` is represented by the index of it's slot in the
+// lower 32 bits and the slot's generation in the upper ones. The handle is
+// removed once the file is closed and the slot is reused by the next opened
+// file, so a closed `File` does not alias it. The handles, which are never
+// closed, are released along with the interpreter.
+enum FileHandle {
+    Reader(BufReader<File>),
+    /* the writes are not buffered, so their failures raise an exception
+     * instead of being lost on closing */
+    Writer(File),
+}
+
+#[derive(Default)]
+struct FileSlot {
+    generation: u32,
+    handle: Option<FileHandle>,
+}
+
+#[derive(Default)]
+struct Files {
+    slots: Vec<FileSlot>,
+    free: Vec<u32>,
+}
+
+impl Files {
+    fn open(&mut self, handle: FileHandle) -> u64 {
+        let idx = match self.free.pop() {
+            Some(idx) => idx,
+            None => {
+                self.slots.push(FileSlot::default());
+                (self.slots.len() - 1) as u32
+            }
+        };
+        let slot = &mut self.slots[idx as usize];
+        slot.handle = Some(handle);
+        ((slot.generation as u64) << 32) | idx as u64
+    }
+
+    fn get_mut(&mut self, file: u64) -> Option<&mut FileHandle> {
+        self.slots
+            .get_mut((file & u32::MAX as u64) as usize)
+            .filter(|slot| slot.generation as u64 == file >> 32)
+            .and_then(|slot| slot.handle.as_mut())
+    }
+
+    fn close(&mut self, file: u64) {
+        let idx = (file & u32::MAX as u64) as u32;
+        if self.get_mut(file).is_some() {
+            let slot = &mut self.slots[idx as usize];
+            slot.handle = None;
+            slot.generation = slot.generation.wrapping_add(1);
+            self.free.push(idx);
+        }
+    }
+}
+
+type FileTable = Rc<RefCell<Files>>;
+
+fn file_type() -> Type {
+    Type::Custom(Box::new("File".to_string()))
+}
+
+fn io_err(action: &str, path: &str, err: std::io::Error) -> String {
+    format!("could not {} `{}`: {}", action, path, err)
+}
+
+pub fn register(interpreter: &mut Chalcedony) -> Result<(), ScriptError> {
+    interpreter.register_fn(None, "read_file!", vec![Type::Str], Type::Str, |args| {
+        let path = str_arg(args, 0);
+        match fs::read_to_string(&path) {
+            Ok(data) => Ok(CvmObject::Str(data.into())),
+            Err(err) => Err(io_err("read", &path, err)),
+        }
+    })?;
+
+    interpreter.register_fn(
+        None,
+        "write_file!",
+        vec![Type::Str, Type::Str],
+        Type::Void,
+        |args| {
+            let path = str_arg(args, 0);
+            match fs::write(&path, str_arg(args, 1)) {
+                Ok(_) => Ok(void()),
+                Err(err) => Err(io_err("write to", &path, err)),
+            }
+        },
+    )?;
+
+    interpreter.register_fn(
+        None,
+        "append_file!",
+        vec![Type::Str, Type::Str],
+        Type::Void,
+        |args| {
+            let path = str_arg(args, 0);
+            let res = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .and_then(|mut file| file.write_all(str_arg(args, 1).as_bytes()));
+            match res {
+                Ok(_) => Ok(void()),
+                Err(err) => Err(io_err("append to", &path, err)),
+            }
+        },
+    )?;
+
+    interpreter.register_fn(
+        None,
+        "read_lines!",
+        vec![Type::Str],
+        Type::List(Box::new(Type::Str)),
+        |args| {
+            let path = str_arg(args, 0);
+            match fs::read_to_string(&path) {
+                Ok(data) => Ok(str_list(data.lines().map(String::from).collect())),
+                Err(err) => Err(io_err("read", &path, err)),
+            }
+        },
+    )?;
+
+    interpreter.register_fn(None, "exists", vec![Type::Str], Type::Bool, |args| {
+        Ok(CvmObject::Bool(fs::metadata(str_arg(args, 0)).is_ok()))
+    })?;
+
+    interpreter.register_fn(
+        None,
+        "list_dir!",
+        vec![Type::Str],
+        Type::List(Box::new(Type::Str)),
+        |args| {
+            let path = str_arg(args, 0);
+            let entries = fs::read_dir(&path).and_then(|dir| {
+                dir.map(|entry| entry.map(|entry| entry.file_name().to_string_lossy().to_string()))
+                    .collect::<Result<Vec<String>, _>>()
+            });
+            match entries {
+                Ok(mut entries) => {
+                    /* the order of the entries is platform-dependent */
+                    entries.sort();
+                    Ok(str_list(entries))
+                }
+                Err(err) => Err(io_err("list", &path, err)),
+            }
+        },
+    )?;
+
+    register_file_handles(interpreter)
+}
+
+/// The file handles are opened via `File::open!(path, mode)`, where the mode is
+/// either `"r"` (read), `"w"` (write) or `"a"` (append).
+fn register_file_handles(interpreter: &mut Chalcedony) -> Result<(), ScriptError> {
+    let files = FileTable::default();
+
+    let table = files.clone();
+    interpreter.register_fn(
+        Some("File"),
+        "open!",
+        vec![Type::Str, Type::Str],
+        file_type(),
+        move |args| {
+            let path = str_arg(args, 0);
+            let handle = match str_arg(args, 1).as_str() {
+                "r" => File::open(&path).map(|file| FileHandle::Reader(BufReader::new(file))),
+                "w" => File::create(&path).map(FileHandle::Writer),
+                "a" => OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&path)
+                    .map(FileHandle::Writer),
+                mode => return Err(format!("invalid file mode `{}`", mode)),
+            };
+
+            match handle {
+                Ok(handle) => Ok(CvmObject::Uint(table.borrow_mut().open(handle))),
+                Err(err) => Err(io_err("open", &path, err)),
+            }
+        },
+    )?;
+
+    let table = files.clone();
+    interpreter.register_fn(
+        Some("File"),
+        "read_line!",
+        vec![file_type()],
+        Type::Str,
+        move |args| {
+            let mut table = table.borrow_mut();
+            match table.get_mut(uint_arg(args, 0)) {
+                Some(FileHandle::Reader(reader)) => {
+                    let mut line = String::new();
+                    match reader.read_line(&mut line) {
+                        Ok(0) => Err("end of file".to_string()),
+                        Ok(_) => {
+                            if line.ends_with('\n') {
+                                line.pop();
+                                if line.ends_with('\r') {
+                                    line.pop();
+                                }
+                            }
+                            Ok(CvmObject::Str(line.into()))
+                        }
+                        Err(err) => Err(format!("could not read the file: {}", err)),
+                    }
+                }
+                Some(FileHandle::Writer(_)) => {
+                    Err("the file is not opened for reading".to_string())
+                }
+                None => Err("the file is closed".to_string()),
+            }
+        },
+    )?;
+
+    let table = files.clone();
+    interpreter.register_fn(
+        Some("File"),
+        "write!",
+        vec![file_type(), Type::Str],
+        Type::Void,
+        move |args| {
+            let mut table = table.borrow_mut();
+            match table.get_mut(uint_arg(args, 0)) {
+                Some(FileHandle::Writer(writer)) => {
+                    match writer.write_all(str_arg(args, 1).as_bytes()) {
+                        Ok(_) => Ok(void()),
+                        Err(err) => Err(format!("could not write to the file: {}", err)),
+                    }
+                }
+                Some(FileHandle::Reader(_)) => {
+                    Err("the file is not opened for writing".to_string())
+                }
+                None => Err("the file is closed".to_string()),
+            }
+        },
+    )?;
+
+    /* closing is safe, since there is no buffered data left to write */
+    let table = files;
+    interpreter.register_fn(
+        Some("File"),
+        "close",
+        vec![file_type()],
+        Type::Void,
+        move |args| {
+            table.borrow_mut().close(uint_arg(args, 0));
+            Ok(void())
+        },
+    )?;
+
+    export_module(
+        interpreter,
+        "fs",
+        &[
+            ("read!", "read_file!"),
+            ("write!", "write_file!"),
+            ("append!", "append_file!"),
+            ("read_lines!", "read_lines!"),
+            ("exists", "exists"),
+            ("list_dir!", "list_dir!"),
+        ],
+    );
+    Ok(())
+}
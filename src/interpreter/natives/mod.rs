@@ -0,0 +1,67 @@
+//! The builtin functions, which interact with the host system. They are
+//! implemented as native functions on top of `Chalcedony::register_fn()`.
+
+mod fs;
+mod random;
+mod sys;
+
+use super::{BuiltinAnnotation, Chalcedony};
+use crate::error::ScriptError;
+use crate::vm::CvmObject;
+
+use ahash::AHashMap;
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+pub fn register_natives(interpreter: &mut Chalcedony) -> Result<(), ScriptError> {
+    fs::register(interpreter)?;
+    random::register(interpreter)?;
+    sys::register(interpreter)
+}
+
+/* exposes the already registered global natives as the standard library module
+ * `std::<module>`, each pair being the name inside the module and the global name */
+fn export_module(interpreter: &mut Chalcedony, module: &str, funcs: &[(&str, &str)]) {
+    let mut exports = AHashMap::<String, Vec<BuiltinAnnotation>>::new();
+    for (name, global_name) in funcs {
+        let bucket = interpreter
+            .builtins
+            .get("Global")
+            .and_then(|global| global.get(*global_name))
+            .unwrap_or_else(|| panic!("expected the native `{}`", global_name));
+        exports.insert(name.to_string(), bucket.clone());
+    }
+    interpreter
+        .builtins
+        .insert(format!("std::{}", module), exports);
+}
+
+/* SAFETY: the argument types are checked during compilation */
+fn str_arg(args: &[CvmObject], idx: usize) -> String {
+    let Some(CvmObject::Str(val)) = args.get(idx) else {
+        panic!("native function typing failed");
+    };
+    val.to_string()
+}
+
+fn uint_arg(args: &[CvmObject], idx: usize) -> u64 {
+    let Some(CvmObject::Uint(val)) = args.get(idx) else {
+        panic!("native function typing failed");
+    };
+    *val
+}
+
+fn str_list(list: Vec<String>) -> CvmObject {
+    let list: VecDeque<CvmObject> = list
+        .into_iter()
+        .map(|el| CvmObject::Str(el.into()))
+        .collect();
+    CvmObject::List(Rc::new(RefCell::new(list)))
+}
+
+/* the returned value of void functions is ignored */
+fn void() -> CvmObject {
+    CvmObject::Bool(false)
+}
@@ -0,0 +1,129 @@
+use super::void;
+use crate::common::Type;
+use crate::error::ScriptError;
+use crate::interpreter::Chalcedony;
+use crate::vm::{CvmObject, Heap};
+
+// Inside the scripts a `Random` generator is an object, holding the generator's
+// state as it's only member. The generator is `SplitMix64`, so the results are
+// reproducible across platforms.
+const GOLDEN_GAMMA: u64 = 0x9e37_79b9_7f4a_7c15;
+
+fn random_type() -> Type {
+    Type::Custom(Box::new("Random".to_string()))
+}
+
+/* advances the generator's state and returns the next random number */
+fn next_u64(heap: &mut Heap, args: &[CvmObject]) -> u64 {
+    let Some(CvmObject::Object(obj)) = args.first() else {
+        panic!("native function typing failed");
+    };
+    let Some(CvmObject::Uint(state)) = heap.get_mut(*obj).get_mut(0) else {
+        panic!("invalid random generator state");
+    };
+
+    *state = state.wrapping_add(GOLDEN_GAMMA);
+    let mut res = *state;
+    res = (res ^ (res >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    res = (res ^ (res >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    res ^ (res >> 31)
+}
+
+/* returns a uniformly distributed number in the range [0, bound) */
+fn next_bounded(heap: &mut Heap, args: &[CvmObject], bound: u64) -> u64 {
+    /* rejecting the values above the last multiple of `bound` avoids the bias */
+    let limit = u64::MAX - (u64::MAX % bound);
+    loop {
+        let res = next_u64(heap, args);
+        if res < limit {
+            return res % bound;
+        }
+    }
+}
+
+pub fn register(interpreter: &mut Chalcedony) -> Result<(), ScriptError> {
+    interpreter.register_heap_fn(
+        Some("Random"),
+        "new",
+        vec![Type::Uint],
+        random_type(),
+        |heap, args| {
+            let Some(CvmObject::Uint(seed)) = args.first() else {
+                panic!("native function typing failed");
+            };
+            Ok(CvmObject::Object(heap.alloc(vec![CvmObject::Uint(*seed)])))
+        },
+    )?;
+
+    /* returns a number in the range [lo, hi] - swapping the bounds if needed */
+    interpreter.register_heap_fn(
+        Some("Random"),
+        "next_int",
+        vec![random_type(), Type::Int, Type::Int],
+        Type::Int,
+        |heap, args| {
+            let (CvmObject::Int(lo), CvmObject::Int(hi)) = (&args[1], &args[2]) else {
+                panic!("native function typing failed");
+            };
+            let (lo, hi) = ((*lo).min(*hi), (*lo).max(*hi));
+
+            let range = hi.wrapping_sub(lo) as u64;
+            let offset = match range.checked_add(1) {
+                Some(bound) => next_bounded(heap, args, bound),
+                /* the whole range of `int` */
+                None => next_u64(heap, args),
+            };
+            Ok(CvmObject::Int(lo.wrapping_add(offset as i64)))
+        },
+    )?;
+
+    /* returns a number in the range [0, 1) */
+    interpreter.register_heap_fn(
+        Some("Random"),
+        "next_float",
+        vec![random_type()],
+        Type::Float,
+        |heap, args| {
+            let res = (next_u64(heap, args) >> 11) as f64 / (1u64 << 53) as f64;
+            Ok(CvmObject::Float(res))
+        },
+    )?;
+
+    interpreter.register_heap_fn(
+        Some("Random"),
+        "choice!",
+        vec![random_type(), Type::List(Box::new(Type::Any))],
+        Type::Any,
+        |heap, args| {
+            let CvmObject::List(list) = &args[1] else {
+                panic!("native function typing failed");
+            };
+            let len = list.borrow().len() as u64;
+            if len == 0 {
+                return Err("choosing from an empty list".to_string());
+            }
+            let idx = next_bounded(heap, args, len) as usize;
+            Ok(list.borrow().get(idx).unwrap().clone())
+        },
+    )?;
+
+    /* shuffles the list in place via the Fisher-Yates algorithm */
+    interpreter.register_heap_fn(
+        Some("Random"),
+        "shuffle",
+        vec![random_type(), Type::List(Box::new(Type::Any))],
+        Type::Void,
+        |heap, args| {
+            let CvmObject::List(list) = &args[1] else {
+                panic!("native function typing failed");
+            };
+            let len = list.borrow().len();
+            for idx in (1..len).rev() {
+                let swap_idx = next_bounded(heap, args, idx as u64 + 1) as usize;
+                list.borrow_mut().swap(idx, swap_idx);
+            }
+            Ok(void())
+        },
+    )?;
+    Ok(())
+}
@@ -0,0 +1,51 @@
+use super::{export_module, str_arg, uint_arg, void};
+use crate::common::Type;
+use crate::error::ScriptError;
+use crate::interpreter::Chalcedony;
+use crate::vm::CvmObject;
+
+use std::env;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub fn register(interpreter: &mut Chalcedony) -> Result<(), ScriptError> {
+    /* there are no optional types (`str?`), so an unset variable results in
+     * an empty string, while `env_get!()` raises an exception */
+    interpreter.register_fn(None, "env_get", vec![Type::Str], Type::Str, |args| {
+        let val = env::var(str_arg(args, 0)).unwrap_or_default();
+        Ok(CvmObject::Str(val.into()))
+    })?;
+
+    interpreter.register_fn(None, "env_get!", vec![Type::Str], Type::Str, |args| {
+        let name = str_arg(args, 0);
+        match env::var(&name) {
+            Ok(val) => Ok(CvmObject::Str(val.into())),
+            Err(_) => Err(format!("the environment variable `{}` is not set", name)),
+        }
+    })?;
+
+    /* the milliseconds since the Unix epoch */
+    interpreter.register_fn(None, "time_ms", vec![], Type::Uint, |_| {
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        Ok(CvmObject::Uint(time.as_millis() as u64))
+    })?;
+
+    interpreter.register_fn(None, "sleep", vec![Type::Uint], Type::Void, |args| {
+        thread::sleep(Duration::from_millis(uint_arg(args, 0)));
+        Ok(void())
+    })?;
+
+    export_module(
+        interpreter,
+        "sys",
+        &[
+            ("env_get", "env_get"),
+            ("env_get!", "env_get!"),
+            ("time_ms", "time_ms"),
+            ("sleep", "sleep"),
+        ],
+    );
+    Ok(())
+}
@@ -0,0 +1,325 @@
+//! The peephole optimizer, run over the bytecode after it is generated by the
+//! `codegen`. The optimizations are applied until none of them changes the
+//! code:
+//!  - jumps to unconditional jumps are threaded to the final destination
+//!  - unreachable instructions are removed
+//!  - `Nop`s and jumps to the next instruction are removed
+//!  - operations over constants are folded into a single constant
+//!  - values, which are pushed and immediately popped, are removed
+//!
+//! Since the jump offsets are relative, the instructions are first decoded
+//! with absolute jump targets, which are converted back into offsets after
+//! the instructions are removed.
+
+use super::codegen::consts::{fold_bin_opr, fold_neg};
+use crate::common::operators::BinOprType;
+use crate::common::Bytecode;
+use crate::parser::ast::NodeValue;
+
+/// The optimizations, applied to the generated bytecode before it is
+/// executed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OptLevel {
+    /// The bytecode is executed as it is generated (`-O0`).
+    O0,
+    /// The peephole optimizations and the dead code elimination are applied
+    /// (`-O1`).
+    #[default]
+    O1,
+}
+
+/// Optimizes the bytecode of a top-level statement. The body of a function's
+/// definition is optimized on its own, since its jumps are relative to the
+/// body.
+pub fn optimize(code: Vec<Bytecode>) -> Vec<Bytecode> {
+    match code.first() {
+        Some(Bytecode::CreateFunc(arg_count)) => {
+            let mut result = vec![Bytecode::CreateFunc(*arg_count)];
+            result.extend(optimize_body(code[1..].to_vec()));
+            result
+        }
+        _ => optimize_body(code),
+    }
+}
+
+struct Instr {
+    code: Bytecode,
+    /* the absolute index, at which the jump lands */
+    target: Option<usize>,
+}
+
+fn optimize_body(code: Vec<Bytecode>) -> Vec<Bytecode> {
+    let mut instrs = decode(code);
+
+    loop {
+        let mut changed = thread_jumps(&mut instrs);
+
+        let mut removed: Vec<bool> = reachable(&instrs).into_iter().map(|r| !r).collect();
+        for (idx, instr) in instrs.iter_mut().enumerate() {
+            if removed[idx] {
+                continue;
+            }
+            match instr.code {
+                Bytecode::Nop => removed[idx] = true,
+                Bytecode::Jmp(_) if instr.target == Some(idx + 1) => removed[idx] = true,
+                /* the condition is still popped */
+                Bytecode::If(_) if instr.target == Some(idx + 1) => {
+                    instr.code = Bytecode::Pop;
+                    instr.target = None;
+                    changed = true;
+                }
+                _ => {}
+            }
+        }
+
+        changed |= fold_peepholes(&mut instrs, &mut removed);
+
+        if !changed && !removed.contains(&true) {
+            break;
+        }
+        instrs = compact(instrs, &removed);
+    }
+
+    encode(instrs)
+}
+
+fn decode(code: Vec<Bytecode>) -> Vec<Instr> {
+    code.into_iter()
+        .enumerate()
+        .map(|(idx, code)| {
+            let target = match code {
+                Bytecode::If(dist) | Bytecode::TryScope(dist) | Bytecode::CatchJmp(dist) => {
+                    Some(idx + 1 + dist)
+                }
+                Bytecode::Jmp(dist) => Some((idx as isize + 1 + dist) as usize),
+                _ => None,
+            };
+            Instr { code, target }
+        })
+        .collect()
+}
+
+fn encode(instrs: Vec<Instr>) -> Vec<Bytecode> {
+    instrs
+        .into_iter()
+        .enumerate()
+        .map(|(idx, instr)| match (instr.code, instr.target) {
+            (Bytecode::If(_), Some(target)) => Bytecode::If(target - idx - 1),
+            (Bytecode::TryScope(_), Some(target)) => Bytecode::TryScope(target - idx - 1),
+            (Bytecode::CatchJmp(_), Some(target)) => Bytecode::CatchJmp(target - idx - 1),
+            (Bytecode::Jmp(_), Some(target)) => Bytecode::Jmp(target as isize - idx as isize - 1),
+            (code, _) => code,
+        })
+        .collect()
+}
+
+/* removes the marked instructions, so each jump lands on the first remaining
+ * instruction at or after its previous target */
+fn compact(instrs: Vec<Instr>, removed: &[bool]) -> Vec<Instr> {
+    let mut new_idx = Vec::with_capacity(instrs.len() + 1);
+    let mut count = 0;
+    for is_removed in removed {
+        new_idx.push(count);
+        if !is_removed {
+            count += 1;
+        }
+    }
+    new_idx.push(count);
+
+    instrs
+        .into_iter()
+        .zip(removed)
+        .filter(|(_, is_removed)| !**is_removed)
+        .map(|(instr, _)| Instr {
+            target: instr.target.map(|target| new_idx[target]),
+            code: instr.code,
+        })
+        .collect()
+}
+
+/* redirects the jumps, which land on an unconditional jump, to its target */
+fn thread_jumps(instrs: &mut [Instr]) -> bool {
+    let mut changed = false;
+
+    for idx in 0..instrs.len() {
+        if !matches!(
+            instrs[idx].code,
+            Bytecode::Jmp(_) | Bytecode::If(_) | Bytecode::CatchJmp(_)
+        ) {
+            continue;
+        }
+        let Some(mut target) = instrs[idx].target else {
+            continue;
+        };
+
+        /* the chain of jumps could be an infinite loop, in which case the jump
+         * is left unchanged */
+        let mut hops = 0;
+        while let Some(Instr {
+            code: Bytecode::Jmp(_),
+            target: Some(next),
+        }) = instrs.get(target)
+        {
+            target = *next;
+            hops += 1;
+            if hops > instrs.len() {
+                break;
+            }
+        }
+        if hops > instrs.len() {
+            continue;
+        }
+
+        /* only `Jmp` could move backwards */
+        let is_forward = target > idx || matches!(instrs[idx].code, Bytecode::Jmp(_));
+        if Some(target) != instrs[idx].target && is_forward {
+            instrs[idx].target = Some(target);
+            changed = true;
+        }
+    }
+
+    changed
+}
+
+fn reachable(instrs: &[Instr]) -> Vec<bool> {
+    let mut reached = vec![false; instrs.len()];
+    let mut pending = vec![0];
+
+    while let Some(idx) = pending.pop() {
+        if idx >= instrs.len() || reached[idx] {
+            continue;
+        }
+        reached[idx] = true;
+
+        let instr = &instrs[idx];
+        match instr.code {
+            Bytecode::Return | Bytecode::ReturnVoid | Bytecode::TailCall(_) => {}
+            Bytecode::Jmp(_) | Bytecode::CatchJmp(_) => pending.extend(instr.target),
+            /* `TryScope` jumps to the `catch` block on an exception */
+            Bytecode::If(_) | Bytecode::TryScope(_) => {
+                pending.extend(instr.target);
+                pending.push(idx + 1);
+            }
+            _ => pending.push(idx + 1),
+        }
+    }
+
+    reached
+}
+
+fn fold_peepholes(instrs: &mut [Instr], removed: &mut [bool]) -> bool {
+    /* an instruction, which is a jump target, could not be merged with the
+     * ones before it */
+    let mut is_target = vec![false; instrs.len() + 1];
+    for (instr, is_removed) in instrs.iter().zip(removed.iter()) {
+        if let (Some(target), false) = (instr.target, is_removed) {
+            is_target[target] = true;
+        }
+    }
+
+    let mut changed = false;
+    let mut idx = 0;
+    while idx + 1 < instrs.len() {
+        if removed[idx] || removed[idx + 1] || is_target[idx + 1] {
+            idx += 1;
+            continue;
+        }
+
+        let first = const_value(&instrs[idx].code);
+        let folded = match (first, &instrs[idx + 1].code) {
+            /* values without side effects, which are discarded */
+            (Some(_), Bytecode::Pop) => Some((None, 2)),
+            (None, Bytecode::Pop) if is_pure_get(&instrs[idx].code) => Some((None, 2)),
+
+            (Some(value), Bytecode::Neg) => fold_neg(value).ok().flatten().map(|v| (Some(v), 2)),
+            (Some(NodeValue::Bool(val)), Bytecode::Not) => Some((Some(NodeValue::Bool(!val)), 2)),
+            (Some(value), Bytecode::CastI | Bytecode::CastF | Bytecode::CastU) => {
+                fold_cast(value, &instrs[idx + 1].code).map(|v| (Some(v), 2))
+            }
+
+            (Some(left), _)
+                if idx + 2 < instrs.len() && !removed[idx + 2] && !is_target[idx + 2] =>
+            {
+                match (
+                    const_value(&instrs[idx + 1].code),
+                    bin_opr(&instrs[idx + 2].code),
+                ) {
+                    (Some(right), Some(opr)) => fold_bin_opr(&opr, left, right)
+                        .ok()
+                        .flatten()
+                        .map(|v| (Some(v), 3)),
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+
+        let Some((value, len)) = folded else {
+            idx += 1;
+            continue;
+        };
+        match value {
+            Some(value) => {
+                instrs[idx].code = const_bytecode(value);
+                removed[idx + 1..idx + len].fill(true);
+            }
+            None => removed[idx..idx + len].fill(true),
+        }
+        changed = true;
+        idx += len;
+    }
+
+    changed
+}
+
+fn const_value(code: &Bytecode) -> Option<NodeValue> {
+    match code {
+        Bytecode::ConstI(val) => Some(NodeValue::Int(*val)),
+        Bytecode::ConstU(val) => Some(NodeValue::Uint(*val)),
+        Bytecode::ConstF(val) => Some(NodeValue::Float(*val)),
+        Bytecode::ConstS(val) => Some(NodeValue::Str(val.to_string())),
+        Bytecode::ConstB(val) => Some(NodeValue::Bool(*val)),
+        _ => None,
+    }
+}
+
+fn const_bytecode(value: NodeValue) -> Bytecode {
+    match value {
+        NodeValue::Int(val) => Bytecode::ConstI(val),
+        NodeValue::Uint(val) => Bytecode::ConstU(val),
+        NodeValue::Float(val) => Bytecode::ConstF(val),
+        NodeValue::Str(val) => Bytecode::ConstS(val.into()),
+        NodeValue::Bool(val) => Bytecode::ConstB(val),
+    }
+}
+
+fn is_pure_get(code: &Bytecode) -> bool {
+    matches!(
+        code,
+        Bytecode::Dup | Bytecode::GetLocal(_) | Bytecode::GetGlobal(_)
+    )
+}
+
+fn bin_opr(code: &Bytecode) -> Option<BinOprType> {
+    match code {
+        Bytecode::Add => Some(BinOprType::Add),
+        Bytecode::Sub => Some(BinOprType::Sub),
+        Bytecode::Mul => Some(BinOprType::Mul),
+        Bytecode::Div => Some(BinOprType::Div),
+        Bytecode::Mod => Some(BinOprType::Mod),
+        _ => None,
+    }
+}
+
+/* mirrors the casts inside the `Cvm` */
+fn fold_cast(value: NodeValue, cast: &Bytecode) -> Option<NodeValue> {
+    match (cast, value) {
+        (Bytecode::CastI, NodeValue::Uint(val)) => Some(NodeValue::Int(val as i64)),
+        (Bytecode::CastI, NodeValue::Float(val)) => Some(NodeValue::Int(val as i64)),
+        (Bytecode::CastF, NodeValue::Int(val)) => Some(NodeValue::Float(val as f64)),
+        (Bytecode::CastF, NodeValue::Uint(val)) => Some(NodeValue::Float(val as f64)),
+        (Bytecode::CastU, NodeValue::Int(val)) => Some(NodeValue::Uint(val as u64)),
+        (Bytecode::CastU, NodeValue::Float(val)) => Some(NodeValue::Uint(val as u64)),
+        _ => None,
+    }
+}
@@ -0,0 +1,19 @@
+//! The standard library of Chalcedony. Each module is either a regular script,
+//! which is embedded into the binary, or a set of native functions (`std::fs`
+//! and `std::sys`, registered in `interpreter::natives`), and is imported by
+//! name, i.e. `import std::math`. Standard library modules are resolved before
+//! any filesystem lookup.
+
+const MATH: &str = include_str!("../../std/math.ch");
+const STRINGS: &str = include_str!("../../std/strings.ch");
+const COLLECTIONS: &str = include_str!("../../std/collections.ch");
+
+/// Returns the source code of the standard library module `std::<name>`.
+pub fn get_std_module(name: &str) -> Option<&'static str> {
+    match name {
+        "math" => Some(MATH),
+        "strings" => Some(STRINGS),
+        "collections" => Some(COLLECTIONS),
+        _ => None,
+    }
+}
@@ -11,7 +11,7 @@ use crate::utils::Stack;
 use std::collections::VecDeque;
 
 impl NodeValue {
-    fn as_type(&self) -> Type {
+    pub(super) fn as_type(&self) -> Type {
         match self {
             NodeValue::Int(_) => Type::Int,
             NodeValue::Uint(_) => Type::Uint,
@@ -359,7 +359,7 @@ impl NodeExprInner {
             NodeExprInner::BinOpr(opr) => opr.as_type(eval_stack, span),
             NodeExprInner::UnaryOpr(opr) => opr.as_type(eval_stack, span),
             NodeExprInner::InlineClass(class) => {
-                if !interpreter.namespaces.contains_key(&class.class) {
+                if !interpreter.is_class(&class.class) {
                     return Err(CompileError::new(
                         CompileErrorKind::UnknownClass(class.class.clone()),
                         class.span.clone(),
@@ -419,6 +419,38 @@ impl NodeExpr {
     }
 }
 
+impl NodeExpr {
+    /// Evaluates the types of the operands of each binary operator in the
+    /// expression. The rest of the expression's elements, or all of them if
+    /// the types could not be evaluated, are `None`.
+    pub fn operand_types(&self, interpreter: &Chalcedony) -> Vec<Option<(Type, Type)>> {
+        let mut result = Vec::<Option<(Type, Type)>>::with_capacity(self.expr.len());
+        let mut eval_stack = Stack::<Type>::new();
+
+        for el in &self.expr {
+            let operands = match el {
+                NodeExprInner::BinOpr(_) => {
+                    let (Some(right), Some(left)) = (eval_stack.pop(), eval_stack.pop()) else {
+                        return vec![None; self.expr.len()];
+                    };
+                    eval_stack.push(left.clone());
+                    eval_stack.push(right.clone());
+                    Some((left, right))
+                }
+                _ => None,
+            };
+            result.push(operands);
+
+            let Ok(ty) = el.as_type(&self.span, &mut eval_stack, interpreter) else {
+                return vec![None; self.expr.len()];
+            };
+            eval_stack.push(ty);
+        }
+
+        result
+    }
+}
+
 impl NodeVarCall {
     pub fn as_type(
         &self,
@@ -446,6 +478,11 @@ impl NodeVarCall {
             return Ok(annotation.ty.clone());
         }
 
+        /* the locals shadow the arguments and the globals */
+        if let Some(annotation) = interpreter.locals.get(&self.name) {
+            return Ok(annotation.ty.clone());
+        }
+
         if let Some(func) = &interpreter.current_func {
             if let Some(annotation) = func.arg_lookup.get(&self.name) {
                 return Ok(annotation.ty.clone());
@@ -456,10 +493,6 @@ impl NodeVarCall {
             return Ok(annotation.ty.clone());
         }
 
-        if let Some(annotation) = interpreter.locals.get(&self.name) {
-            return Ok(annotation.ty.clone());
-        }
-
         Err(CompileError::new(
             CompileErrorKind::UnknownVariable(self.name.clone()),
             self.span.clone(),
@@ -479,10 +512,7 @@ impl NodeFuncCall {
             .iter()
             .map(|arg| arg.as_type(interpreter))
             .collect();
-        let mut arg_types = match arg_types {
-            Ok(ok) => ok,
-            Err(err) => return Err(err),
-        };
+        let mut arg_types = arg_types?;
 
         let mut namespace = self.namespace.clone();
 
@@ -511,6 +541,13 @@ impl NodeFuncCall {
             return Ok(ann.ret_type);
         }
 
+        /* the call is compiled separately in typed variables and returns */
+        if self.name == "json_parse!" && namespace.is_none() {
+            return Err(
+                CompileError::new(CompileErrorKind::UntypedJsonParse, self.span.clone()).into(),
+            );
+        }
+
         let mut func_name = self.name.clone() + "(";
         if let Some(ns) = namespace {
             func_name = ns + "::" + &func_name;
@@ -0,0 +1,206 @@
+//! The values, exchanged between the host and the scripts through the
+//! embedding API.
+
+use super::Chalcedony;
+
+use crate::common::Type;
+use crate::error::ScriptError;
+use crate::vm::CvmObject;
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use ahash::AHashMap;
+
+/// The representation of a script value, owned by the host. Class instances
+/// are represented as a map of their fields.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Int(i64),
+    Uint(u64),
+    Float(f64),
+    Str(String),
+    Bool(bool),
+    List(Vec<Value>),
+    Object {
+        class: String,
+        fields: AHashMap<String, Value>,
+    },
+    /// The result of calling a void function.
+    Void,
+}
+
+impl Value {
+    pub fn as_type(&self) -> Type {
+        match self {
+            Value::Int(_) => Type::Int,
+            Value::Uint(_) => Type::Uint,
+            Value::Float(_) => Type::Float,
+            Value::Str(_) => Type::Str,
+            Value::Bool(_) => Type::Bool,
+            Value::List(list) => match list.first() {
+                Some(el) => Type::List(Box::new(el.as_type())),
+                None => Type::List(Box::new(Type::Any)),
+            },
+            Value::Object { class, .. } => Type::Custom(Box::new(class.clone())),
+            Value::Void => Type::Void,
+        }
+    }
+}
+
+macro_rules! value_conversions {
+    ($($ty:ty => $variant:ident, $type:expr);* $(;)?) => {$(
+        impl From<$ty> for Value {
+            fn from(val: $ty) -> Self {
+                Value::$variant(val)
+            }
+        }
+
+        impl TryFrom<Value> for $ty {
+            type Error = ScriptError;
+
+            fn try_from(val: Value) -> Result<Self, Self::Error> {
+                match val {
+                    Value::$variant(val) => Ok(val),
+                    val => Err(ScriptError::InvalidValue($type, val.as_type())),
+                }
+            }
+        }
+    )*};
+}
+
+value_conversions!(
+    i64 => Int, Type::Int;
+    u64 => Uint, Type::Uint;
+    f64 => Float, Type::Float;
+    String => Str, Type::Str;
+    bool => Bool, Type::Bool;
+);
+
+impl From<&str> for Value {
+    fn from(val: &str) -> Self {
+        Value::Str(val.to_string())
+    }
+}
+
+impl<T: Into<Value>> From<Vec<T>> for Value {
+    fn from(val: Vec<T>) -> Self {
+        Value::List(val.into_iter().map(|el| el.into()).collect())
+    }
+}
+
+impl<T: TryFrom<Value, Error = ScriptError>> TryFrom<Value> for Vec<T> {
+    type Error = ScriptError;
+
+    fn try_from(val: Value) -> Result<Self, Self::Error> {
+        match val {
+            Value::List(list) => list.into_iter().map(T::try_from).collect(),
+            val => Err(ScriptError::InvalidValue(
+                Type::List(Box::new(Type::Any)),
+                val.as_type(),
+            )),
+        }
+    }
+}
+
+impl Chalcedony {
+    /* converts the value to an object of the expected type */
+    pub(super) fn value_to_object(
+        &mut self,
+        val: &Value,
+        ty: &Type,
+    ) -> Result<CvmObject, ScriptError> {
+        match (val, ty) {
+            /* the implicit type cast, same as in the scripts, unless the value
+             * does not fit */
+            (Value::Uint(val), Type::Int) => match i64::try_from(*val) {
+                Ok(val) => Ok(CvmObject::Int(val)),
+                Err(_) => Err(ScriptError::InvalidValue(Type::Int, Type::Uint)),
+            },
+            (Value::Int(val), _) => Ok(CvmObject::Int(*val)),
+            (Value::Uint(val), _) => Ok(CvmObject::Uint(*val)),
+            (Value::Float(val), _) => Ok(CvmObject::Float(*val)),
+            (Value::Str(val), _) => Ok(CvmObject::Str(val.clone().into())),
+            (Value::Bool(val), _) => Ok(CvmObject::Bool(*val)),
+
+            (Value::List(list), ty) => {
+                let el_ty = match ty {
+                    Type::List(el_ty) => el_ty,
+                    _ => &Type::Any,
+                };
+                let mut result = VecDeque::<CvmObject>::with_capacity(list.len());
+                for el in list {
+                    result.push_back(self.value_to_object(el, el_ty)?);
+                }
+                Ok(CvmObject::List(Rc::new(RefCell::new(result))))
+            }
+
+            (Value::Object { class, fields }, _) => {
+                let Some(namespace) = self.namespaces.get(class).filter(|_| self.is_class(class))
+                else {
+                    return Err(ScriptError::InvalidObject(class.clone()));
+                };
+                if namespace.members.len() != fields.len() {
+                    return Err(ScriptError::InvalidObject(class.clone()));
+                }
+
+                let members = namespace.members.clone();
+                let mut result = Vec::<CvmObject>::with_capacity(fields.len());
+                for member in &members {
+                    let Some(field) = fields.get(&member.name) else {
+                        return Err(ScriptError::InvalidObject(class.clone()));
+                    };
+                    result.push(self.value_to_object(field, &member.ty)?);
+                }
+                Ok(CvmObject::Object(self.vm.heap.alloc(result)))
+            }
+
+            (Value::Void, ty) => Err(ScriptError::InvalidValue(ty.clone(), Type::Void)),
+        }
+    }
+
+    /* since objects do not keep track of their class, the type is required */
+    pub(super) fn object_to_value(&self, obj: CvmObject, ty: &Type) -> Value {
+        match obj {
+            CvmObject::Int(val) => Value::Int(val),
+            CvmObject::Uint(val) => Value::Uint(val),
+            CvmObject::Float(val) => Value::Float(val),
+            CvmObject::Str(val) | CvmObject::Exception(val) => Value::Str(val.to_string()),
+            CvmObject::Bool(val) => Value::Bool(val),
+
+            CvmObject::List(list) => {
+                let el_ty = match ty {
+                    Type::List(el_ty) => el_ty,
+                    _ => &Type::Any,
+                };
+                let list = list.borrow();
+                Value::List(
+                    list.iter()
+                        .map(|el| self.object_to_value(el.clone(), el_ty))
+                        .collect(),
+                )
+            }
+
+            CvmObject::Object(obj) => {
+                let Type::Custom(class) = ty else {
+                    panic!("converting an object of a non-class type");
+                };
+                let namespace = self
+                    .namespaces
+                    .get(&**class)
+                    .expect("expected a valid class");
+
+                let mut fields = AHashMap::<String, Value>::new();
+                for member in &namespace.members {
+                    let field = self.vm.heap.get(obj).get(member.id).unwrap().clone();
+                    fields.insert(member.name.clone(), self.object_to_value(field, &member.ty));
+                }
+                Value::Object {
+                    class: *class.clone(),
+                    fields,
+                }
+            }
+        }
+    }
+}
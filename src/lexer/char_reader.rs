@@ -6,6 +6,8 @@ use std::collections::VecDeque;
 /// of the current position inside the source code.
 pub struct CharReader {
     pos: Position,
+    /// The byte offset of the next char inside the source.
+    offset: usize,
     src: VecDeque<char>,
 }
 
@@ -13,6 +15,7 @@ impl CharReader {
     pub fn new(source: String) -> CharReader {
         CharReader {
             pos: Position::new(1, 1),
+            offset: 0,
             src: source.chars().collect::<VecDeque<char>>(),
         }
     }
@@ -21,6 +24,10 @@ impl CharReader {
         &self.pos
     }
 
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
     pub fn advance_while(&mut self, cond: impl Fn(&char) -> bool) -> String {
         let mut result = Vec::<char>::new();
         while !self.is_empty() && cond(self.peek().unwrap()) {
@@ -33,6 +40,7 @@ impl CharReader {
         let result = self.src.pop_front();
 
         if let Some(val) = result {
+            self.offset += val.len_utf8();
             if val == '\n' {
                 self.pos.advance_ln();
             } else {
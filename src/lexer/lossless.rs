@@ -0,0 +1,200 @@
+//! The lossless mode of the lexer, used by tools such as formatters, which
+//! need to reproduce the source code exactly. Along with the tokens it keeps
+//! the comments and whitespace (the trivia), which the interpreter discards.
+
+use super::{CharReader, Lexer};
+
+use crate::error::span::InlineSpanner;
+use crate::error::{ChalError, LexerError, LexerErrorKind};
+use crate::lexer::{Token, TokenKind};
+use crate::utils::Stack;
+
+use std::ops::Range;
+use std::rc::Rc;
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum TriviaKind {
+    /// A sequence of spaces and tabs.
+    Whitespace,
+    /// A newline, which does not end a statement - after an empty or a
+    /// comment-only line or inside open delimiters.
+    Newline,
+    /// A comment, without its terminating newline.
+    Comment,
+    /// A `\`, followed by optional spaces and a newline.
+    LineContinuation,
+}
+
+/// A piece of the source code, which carries no meaning for the interpreter.
+#[derive(PartialEq, Debug, Clone)]
+pub struct Trivia {
+    pub kind: TriviaKind,
+    pub text: String,
+    /// The byte offsets inside the source code.
+    pub range: Range<usize>,
+}
+
+/// A token with all of the trivia preceding it.
+#[derive(PartialEq, Debug, Clone)]
+pub struct LosslessToken {
+    pub leading: Vec<Trivia>,
+    /// The token is `None` only for the last item, which holds the trivia at
+    /// the end of the source code.
+    pub token: Option<Token>,
+    /// The exact source of the token, since the `src` of some tokens (such as
+    /// the comments) differs from the source code.
+    pub text: String,
+    /// The byte offsets of the token inside the source code.
+    pub range: Range<usize>,
+}
+
+/// The iterator over the tokens of a script, which keeps every character of
+/// the source code, i.e. concatenating the text of each item's trivia and
+/// token reproduces the source exactly. Created with [`Lexer::lossless()`].
+pub struct LosslessLexer {
+    lexer: Lexer,
+    src: String,
+    /// Whether the current line has any tokens - the newlines of empty lines
+    /// are treated as trivia.
+    line_has_token: bool,
+    finished: bool,
+}
+
+impl Lexer {
+    /// Creates a lexer, which emits the comments and whitespace as trivia,
+    /// attached to the token following them. Unlike [`Lexer::new()`] the
+    /// source code is kept unchanged, so tabs are not converted to spaces.
+    pub fn lossless(code: &str, filename: Option<String>) -> LosslessLexer {
+        let lexer = Lexer {
+            delim_stack: Stack::<Token>::new(),
+            reader: CharReader::new(code.to_string()),
+            spanner: Rc::new(InlineSpanner::new(code, filename)),
+            prev: None,
+        };
+
+        LosslessLexer {
+            lexer,
+            src: code.to_string(),
+            line_has_token: false,
+            finished: false,
+        }
+    }
+}
+
+impl LosslessLexer {
+    fn offset(&self) -> usize {
+        self.lexer.reader.offset()
+    }
+
+    fn push_trivia(&self, result: &mut Vec<Trivia>, kind: TriviaKind, start: usize) {
+        let end = self.offset();
+        result.push(Trivia {
+            kind,
+            text: self.src[start..end].to_string(),
+            range: start..end,
+        });
+    }
+
+    /* advances the trivia before the next token */
+    fn advance_trivia(&mut self) -> Result<Vec<Trivia>, ChalError> {
+        let mut result = Vec::<Trivia>::new();
+        loop {
+            let start = self.offset();
+            let reader = &mut self.lexer.reader;
+            match reader.peek() {
+                Some(' ' | '\t') => {
+                    reader.advance_while(|c: &char| *c == ' ' || *c == '\t');
+                    self.push_trivia(&mut result, TriviaKind::Whitespace, start);
+                }
+
+                Some('#') => {
+                    reader.advance_while(|c: &char| *c != '\n');
+                    self.push_trivia(&mut result, TriviaKind::Comment, start);
+                }
+
+                Some('\\') => {
+                    let mut idx = 1;
+                    while reader.peek_nth(idx) == Some(&' ') {
+                        idx += 1;
+                    }
+                    reader.advance();
+                    let pos = *reader.pos();
+                    for _ in 1..idx {
+                        reader.advance();
+                    }
+                    if reader.advance() != Some('\n') {
+                        let span = self.lexer.get_span(pos, pos);
+                        return Err(
+                            LexerError::new(LexerErrorKind::InvalidNewlineEscape, span).into()
+                        );
+                    }
+                    self.push_trivia(&mut result, TriviaKind::LineContinuation, start);
+                }
+
+                Some('\n') if !self.line_has_token || !self.lexer.delim_stack.is_empty() => {
+                    reader.advance();
+                    self.push_trivia(&mut result, TriviaKind::Newline, start);
+                }
+
+                _ => break,
+            }
+        }
+        Ok(result)
+    }
+}
+
+impl Iterator for LosslessLexer {
+    type Item = Result<LosslessToken, ChalError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        let leading = match self.advance_trivia() {
+            Ok(leading) => leading,
+            Err(err) => return Some(Err(err)),
+        };
+
+        let start = self.offset();
+        if self.lexer.is_empty() {
+            self.finished = true;
+
+            /* check for any unclosed delimiters */
+            let mut errors = Vec::<ChalError>::new();
+            while let Some(delim) = self.lexer.delim_stack.pop() {
+                errors.push(
+                    LexerError::new(LexerErrorKind::UnclosedDelimiter(delim.src), delim.span)
+                        .into(),
+                );
+            }
+            if !errors.is_empty() {
+                return Some(Err(errors.into()));
+            }
+
+            if leading.is_empty() {
+                return None;
+            }
+            return Some(Ok(LosslessToken {
+                leading,
+                token: None,
+                text: String::new(),
+                range: start..start,
+            }));
+        }
+
+        let token = match self.lexer.advance() {
+            Ok(token) => token,
+            Err(err) => return Some(Err(err)),
+        };
+        self.line_has_token = token.kind != TokenKind::Newline;
+
+        let end = self.offset();
+        Some(Ok(LosslessToken {
+            leading,
+            token: Some(token),
+            text: self.src[start..end].to_string(),
+            range: start..end,
+        }))
+    }
+}
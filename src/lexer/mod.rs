@@ -6,12 +6,14 @@
 //! fundamental role in the whole `Chalcedony` interpreter.
 
 mod line;
+mod lossless;
 mod tokens;
 
 mod char_reader;
 
 pub use line::Line;
-pub use tokens::{Delimiter, Keyword, Operator, Special, Token, TokenKind};
+pub use lossless::{LosslessLexer, LosslessToken, Trivia, TriviaKind};
+pub use tokens::{Delimiter, FmtStrPart, Keyword, Operator, Special, Token, TokenKind};
 
 use crate::error::span::{InlineSpanner, Position, Span, Spanning};
 use crate::error::{ChalError, LexerError, LexerErrorKind};
@@ -185,7 +187,7 @@ impl Lexer {
         let mut result = VecDeque::<Token>::new();
         let mut errors = Vec::<ChalError>::new();
 
-        if indent % 4 != 0 {
+        if !indent.is_multiple_of(4) {
             let pos = self.reader.pos();
             errors.push(
                 LexerError::new(
@@ -310,25 +312,46 @@ impl Lexer {
             // 'a-5' -> identifier(a), sub(-), uint(5)
             // 'a*-5' -> identifier(a), mul(*), int(-5)
             if current == '-' {
-                match &self.prev {
-                    Some(kind) => {
-                        if kind.is_terminal() || *kind == TokenKind::Delimiter(Delimiter::ClosePar)
-                        {
-                            return self.advance_tok(
-                                current.to_string(),
-                                start,
-                                *self.reader.pos(),
-                            );
-                        }
+                if let Some(kind) = &self.prev {
+                    if kind.is_terminal() || *kind == TokenKind::Delimiter(Delimiter::ClosePar) {
+                        return self.advance_tok(current.to_string(), start, *self.reader.pos());
                     }
-                    None => {}
                 }
             }
 
-            let src = String::from(current)
+            let mut src = String::from(current)
                 + &self
                     .reader
                     .advance_while(|c: &char| c.is_numeric() || *c == '.' || *c == '_');
+
+            let prefix = self.reader.peek().copied();
+            let exp_digit = |c: Option<&char>| c.is_some_and(|c| c.is_ascii_digit());
+            match prefix {
+                /* hexadecimal, octal and binary literals */
+                Some('x' | 'X' | 'o' | 'O' | 'b' | 'B') if src == "0" || src == "-0" => {
+                    src.push(self.reader.advance().unwrap());
+                    /* any invalid digits are consumed so they could be reported */
+                    src += &self
+                        .reader
+                        .advance_while(|c: &char| c.is_alphanumeric() || *c == '_');
+                }
+
+                /* the exponent of a float in scientific notation */
+                Some('e' | 'E')
+                    if exp_digit(self.reader.peek_nth(1))
+                        || (matches!(self.reader.peek_nth(1), Some('+' | '-'))
+                            && exp_digit(self.reader.peek_nth(2))) =>
+                {
+                    src.push(self.reader.advance().unwrap());
+                    src.push(self.reader.advance().unwrap());
+                    src += &self
+                        .reader
+                        .advance_while(|c: &char| c.is_ascii_digit() || *c == '_');
+                }
+
+                _ => {}
+            }
+
             return self.advance_tok(src, start, *self.reader.pos());
         }
 
@@ -339,6 +362,18 @@ impl Lexer {
                     .reader
                     .advance_while(|c: &char| c.is_alphanumeric() || *c == '_');
 
+            /* a formatted or a raw string literal */
+            if matches!(self.reader.peek(), Some('"') | Some('\'')) {
+                match src.as_str() {
+                    "f" => return self.advance_fmt_str(start),
+                    "r" => {
+                        let quote = self.reader.advance().unwrap();
+                        return self.advance_str(quote, start, true);
+                    }
+                    _ => {}
+                }
+            }
+
             /* only function names can end with a '!' */
             if self.reader.peek() == Some(&'!') && self.reader.peek_nth(1) == Some(&'(') {
                 src.push(self.reader.advance().unwrap());
@@ -402,14 +437,7 @@ impl Lexer {
 
         /* any string */
         if current == '"' || current == '\'' {
-            let mut src =
-                String::from(current) + &self.reader.advance_while(|c: &char| *c != current);
-            if let Some(c) = self.reader.advance() {
-                /* adds the `"` at the end */
-                src.push(c);
-            }
-
-            return self.advance_tok(src, start, *self.reader.pos());
+            return self.advance_str(current, start, false);
         }
 
         Err(LexerError::new(
@@ -419,6 +447,255 @@ impl Lexer {
         .into())
     }
 
+    /* advances a string literal, whose opening quote is already consumed */
+    fn advance_str(&mut self, quote: char, start: Position, raw: bool) -> Result<Token, ChalError> {
+        let mut src = String::new();
+        if raw {
+            src.push('r');
+        }
+        src.push(quote);
+
+        /* triple-quoted strings could span multiple lines */
+        let triple = self.reader.peek() == Some(&quote) && self.reader.peek_nth(1) == Some(&quote);
+        if triple {
+            src.push(self.reader.advance().unwrap());
+            src.push(self.reader.advance().unwrap());
+        }
+
+        let mut val = String::new();
+        /* the string is consumed completely even if an escape is invalid */
+        let mut error: Option<ChalError> = None;
+        loop {
+            match self.reader.peek() {
+                /* only the opening quote is spanned, since the end of file is not */
+                None => {
+                    let span = self.get_span(start, start);
+                    return Err(LexerError::new(LexerErrorKind::UnclosedString, span).into());
+                }
+                Some('\n') if !triple => {
+                    let span = self.get_span(start, *self.reader.pos());
+                    return Err(LexerError::new(LexerErrorKind::UnclosedString, span).into());
+                }
+                _ => {}
+            }
+
+            let current = self.reader.advance().unwrap();
+            src.push(current);
+
+            if current == quote {
+                if !triple {
+                    break;
+                }
+                if self.reader.peek() == Some(&quote) && self.reader.peek_nth(1) == Some(&quote) {
+                    src.push(self.reader.advance().unwrap());
+                    src.push(self.reader.advance().unwrap());
+                    break;
+                }
+            }
+
+            if current == '\\' && !raw {
+                match self.advance_escape(&mut src) {
+                    Ok(escaped) => val.push(escaped),
+                    Err(err) => _ = error.get_or_insert(err),
+                }
+                continue;
+            }
+            val.push(current);
+        }
+
+        if let Some(err) = error {
+            return Err(err);
+        }
+
+        let kind = TokenKind::Str(val);
+        self.prev = Some(kind.clone());
+        Ok(Token {
+            kind,
+            span: self.get_span(start, *self.reader.pos()),
+            src,
+        })
+    }
+
+    /* advances the escape sequence, whose leading `\` is consumed */
+    fn advance_escape(&mut self, src: &mut String) -> Result<char, ChalError> {
+        let start = *self.reader.pos();
+        let Some(current) = self.reader.peek().copied() else {
+            return Err(LexerError::new(
+                LexerErrorKind::UnclosedString,
+                self.get_span(start, start),
+            )
+            .into());
+        };
+        /* the newline is left for the closing quote checks */
+        if current == '\n' {
+            return Err(LexerError::new(
+                LexerErrorKind::InvalidEscapeSequence(current),
+                self.get_span(start, start),
+            )
+            .into());
+        }
+        src.push(self.reader.advance().unwrap());
+
+        match current {
+            'n' => Ok('\n'),
+            't' => Ok('\t'),
+            'r' => Ok('\r'),
+            '0' => Ok('\0'),
+            '\\' => Ok('\\'),
+            '\'' => Ok('\''),
+            '"' => Ok('"'),
+            'u' => {
+                let mut code = String::new();
+                let valid_open = self.reader.peek() == Some(&'{');
+                if valid_open {
+                    src.push(self.reader.advance().unwrap());
+                    code = self.reader.advance_while(|c: &char| c.is_ascii_hexdigit());
+                    src.push_str(&code);
+                }
+                let valid_close = valid_open && self.reader.peek() == Some(&'}');
+                if valid_close {
+                    src.push(self.reader.advance().unwrap());
+                }
+
+                let escaped = u32::from_str_radix(&code, 16).ok().and_then(char::from_u32);
+                match escaped {
+                    Some(escaped) if valid_close && code.len() <= 6 => Ok(escaped),
+                    _ => Err(LexerError::new(
+                        LexerErrorKind::InvalidUnicodeEscape,
+                        self.get_span(start, *self.reader.pos()),
+                    )
+                    .into()),
+                }
+            }
+            _ => Err(LexerError::new(
+                LexerErrorKind::InvalidEscapeSequence(current),
+                self.get_span(start, *self.reader.pos()),
+            )
+            .into()),
+        }
+    }
+
+    /* advances a formatted string literal, whose leading `f` is consumed */
+    fn advance_fmt_str(&mut self, start: Position) -> Result<Token, ChalError> {
+        let quote = self.reader.advance().unwrap();
+        let mut src = format!("f{}", quote);
+        let mut parts = Vec::<FmtStrPart>::new();
+        let mut text = String::new();
+
+        loop {
+            if matches!(self.reader.peek(), None | Some('\n')) {
+                let span = self.get_span(start, *self.reader.pos());
+                return Err(LexerError::new(LexerErrorKind::UnclosedString, span).into());
+            }
+            let current = self.reader.advance().unwrap();
+            src.push(current);
+
+            match current {
+                _ if current == quote => break,
+                '\\' => match self.advance_escape(&mut src) {
+                    Ok(escaped) => text.push(escaped),
+                    Err(err) => {
+                        self.reader.advance_while(|c: &char| *c != '\n');
+                        return Err(err);
+                    }
+                },
+                '{' | '}' if self.reader.peek() == Some(&current) => {
+                    src.push(self.reader.advance().unwrap());
+                    text.push(current);
+                }
+                '}' => {
+                    let pos = *self.reader.pos();
+                    self.reader.advance_while(|c: &char| *c != '\n');
+                    return Err(LexerError::new(
+                        LexerErrorKind::UnmatchedFmtBrace,
+                        self.get_span(pos, pos),
+                    )
+                    .into());
+                }
+                '{' => {
+                    if !text.is_empty() {
+                        parts.push(FmtStrPart::Str(std::mem::take(&mut text)));
+                    }
+                    let delim_depth = self.delim_stack.len();
+                    match self.advance_fmt_expr(quote, &mut src) {
+                        Ok(part) => parts.push(part),
+                        Err(err) => {
+                            /* skip the rest of the line to avoid cascading errors */
+                            self.delim_stack.truncate(delim_depth);
+                            self.reader.advance_while(|c: &char| *c != '\n');
+                            return Err(err);
+                        }
+                    }
+                }
+                _ => text.push(current),
+            }
+        }
+
+        if !text.is_empty() {
+            parts.push(FmtStrPart::Str(text));
+        }
+
+        let kind = TokenKind::FmtStr(parts);
+        self.prev = Some(kind.clone());
+        Ok(Token {
+            kind,
+            span: self.get_span(start, *self.reader.pos()),
+            src,
+        })
+    }
+
+    /* advances the expression inside a formatted string up to the closing `}` */
+    fn advance_fmt_expr(&mut self, quote: char, src: &mut String) -> Result<FmtStrPart, ChalError> {
+        let start = *self.reader.pos();
+        let delim_depth = self.delim_stack.len();
+        let mut tokens = VecDeque::<Token>::new();
+        let mut spec = String::new();
+
+        /* so a leading `-` is treated as a negation */
+        self.prev = Some(TokenKind::Delimiter(Delimiter::OpenPar));
+
+        loop {
+            src.push_str(&self.reader.advance_while(|c: &char| *c == ' '));
+            let at_top_level = self.delim_stack.len() == delim_depth;
+
+            match (self.reader.peek(), self.reader.peek_nth(1)) {
+                (Some('}'), _) if at_top_level => {
+                    src.push(self.reader.advance().unwrap());
+                    break;
+                }
+                (Some(':'), next) if at_top_level && next != Some(&':') && next != Some(&'=') => {
+                    src.push(self.reader.advance().unwrap());
+                    spec = self
+                        .reader
+                        .advance_while(|c: &char| *c != '}' && *c != quote && *c != '\n');
+                    src.push_str(&spec);
+                }
+                (Some(current), _) if *current != quote && *current != '\n' => {
+                    let tok = self.advance()?;
+                    src.push_str(&tok.src);
+                    tokens.push_back(tok);
+                }
+                _ => {
+                    let span = self.get_span(start, *self.reader.pos());
+                    return Err(LexerError::new(LexerErrorKind::UnclosedFmtExpr, span).into());
+                }
+            }
+        }
+
+        let end = *self.reader.pos();
+        if tokens.is_empty() {
+            return Err(
+                LexerError::new(LexerErrorKind::EmptyFmtExpr, self.get_span(start, end)).into(),
+            );
+        }
+
+        Ok(FmtStrPart::Expr {
+            tokens,
+            spec,
+            span: self.get_span(start, end),
+        })
+    }
+
     fn remove_trailing_space(&mut self) {
         if self.reader.is_empty() {
             return;
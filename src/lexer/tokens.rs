@@ -3,6 +3,8 @@ use crate::error::{ChalError, LexerError, LexerErrorKind};
 
 use crate::common::Type;
 
+use std::collections::VecDeque;
+
 #[derive(PartialEq, Debug, Clone)]
 pub enum Keyword {
     Let,
@@ -21,6 +23,7 @@ pub enum Keyword {
     Throw,
     Import,
     Const,
+    Mut,
     Class,
 }
 
@@ -108,6 +111,18 @@ pub fn is_operator(c: &char) -> bool {
     )
 }
 
+/// A piece of a formatted string literal (`f"{name} is {age:>3} years"`).
+#[derive(PartialEq, Debug, Clone)]
+pub enum FmtStrPart {
+    Str(String),
+    /// An interpolated expression with its (potentially empty) format spec.
+    Expr {
+        tokens: VecDeque<Token>,
+        spec: String,
+        span: Span,
+    },
+}
+
 /// The types of tokens which could be built from the source code.
 #[derive(PartialEq, Debug, Clone)]
 pub enum TokenKind {
@@ -115,6 +130,7 @@ pub enum TokenKind {
     Uint(u64),
     Float(f64),
     Str(String),
+    FmtStr(Vec<FmtStrPart>),
     Bool(bool),
 
     Keyword(Keyword),
@@ -162,6 +178,7 @@ impl TokenKind {
             "throw" => return Ok(TokenKind::Keyword(Keyword::Throw)),
             "import" => return Ok(TokenKind::Keyword(Keyword::Import)),
             "const" => return Ok(TokenKind::Keyword(Keyword::Const)),
+            "mut" => return Ok(TokenKind::Keyword(Keyword::Mut)),
             "class" => return Ok(TokenKind::Keyword(Keyword::Class)),
 
             /* Delimiters */
@@ -216,19 +233,12 @@ impl TokenKind {
             _ => (),
         };
 
-        /* this way digits such as `123_456` are supported */
-        let potential_digit = src.replace('_', "");
-
-        if let Ok(val) = potential_digit.parse::<u64>() {
-            return Ok(TokenKind::Uint(val));
-        }
-
-        if let Ok(val) = potential_digit.parse::<i64>() {
-            return Ok(TokenKind::Int(val));
-        }
-
-        if let Ok(val) = potential_digit.parse::<f64>() {
-            return Ok(TokenKind::Float(val));
+        let mut chars = src.chars();
+        let first = chars.next().unwrap();
+        if first.is_ascii_digit()
+            || (first == '-' && chars.next().is_some_and(|c| c.is_ascii_digit()))
+        {
+            return TokenKind::new_number(src, span);
         }
 
         if (src.starts_with('"') && src.ends_with('"'))
@@ -243,6 +253,60 @@ impl TokenKind {
         Ok(TokenKind::Identifier(src.to_string()))
     }
 
+    fn new_number(src: &str, span: &Span) -> Result<TokenKind, ChalError> {
+        let err = |kind: LexerErrorKind| Err(LexerError::new(kind, span.clone()).into());
+
+        let (is_neg, body) = match src.strip_prefix('-') {
+            Some(body) => (true, body),
+            None => (false, src),
+        };
+        /* this way digits such as `123_456` are supported */
+        let digits = body.replace('_', "");
+
+        let radix = match digits.get(..2) {
+            Some("0x" | "0X") => 16,
+            Some("0o" | "0O") => 8,
+            Some("0b" | "0B") => 2,
+            _ => 10,
+        };
+
+        let val = if radix != 10 {
+            let (prefix, digits) = digits.split_at(2);
+            if digits.is_empty() {
+                return err(LexerErrorKind::MissingDigits(prefix.to_string()));
+            }
+            if let Some(digit) = digits.chars().find(|c| !c.is_digit(radix)) {
+                return err(LexerErrorKind::InvalidDigit(digit, radix));
+            }
+            match u64::from_str_radix(digits, radix) {
+                Ok(val) => val,
+                Err(_) => return err(LexerErrorKind::IntegerOverflow(src.to_string())),
+            }
+        } else if digits.contains(['.', 'e', 'E']) {
+            return match src.replace('_', "").parse::<f64>() {
+                Ok(val) => Ok(TokenKind::Float(val)),
+                Err(_) => err(LexerErrorKind::InvalidNumber(src.to_string())),
+            };
+        } else {
+            if let Some(digit) = digits.chars().find(|c| !c.is_ascii_digit()) {
+                return err(LexerErrorKind::InvalidDigit(digit, radix));
+            }
+            match digits.parse::<u64>() {
+                Ok(val) => val,
+                Err(_) => return err(LexerErrorKind::IntegerOverflow(src.to_string())),
+            }
+        };
+
+        if !is_neg {
+            return Ok(TokenKind::Uint(val));
+        }
+        /* `i64::MIN` has no positive counterpart */
+        match i64::try_from(-(val as i128)) {
+            Ok(val) => Ok(TokenKind::Int(val)),
+            Err(_) => err(LexerErrorKind::IntegerOverflow(src.to_string())),
+        }
+    }
+
     /* used to perform checks such as checking whether an `-` is unary or binary */
     pub fn is_terminal(&self) -> bool {
         matches!(
@@ -251,6 +315,7 @@ impl TokenKind {
                 | TokenKind::Uint(_)
                 | TokenKind::Float(_)
                 | TokenKind::Str(_)
+                | TokenKind::FmtStr(_)
                 | TokenKind::Identifier(_)
         )
     }
@@ -1,6 +1,7 @@
 pub mod error;
 
 pub mod common;
+pub mod formatter;
 pub mod interpreter;
 pub mod lexer;
 pub mod parser;
@@ -3,12 +3,12 @@
 //!
 //! Commonly used acronyms:
 //!  - `AST` - Abstract Syntax Tree - the intermediate representaion `IR`,
-//!            generated by the parser
+//!    generated by the parser
 //!  - `CVM` - Chalcedony Virtual Machine - the structure, used to execute the
-//!            script's bytecode instructions.
+//!    script's bytecode instructions.
 //!  - `RPN` - Reverse Polish Notation - a mathematical notation where instead
-//!            of the operator being between the two operands, it is after the
-//!            operands.
+//!    of the operator being between the two operands, it is after the
+//!    operands.
 //!
 //! For more information on each type of the interpreting process refer to the
 //! modules `lexer`, `parser`, `vm` and `interpreter` (in that order).
@@ -21,6 +21,8 @@
 //!     `insert!()` and `remove!())
 //! [x] `for in` loops over lists and iterators
 //! [x] constants - the value can still be computed at runtime
+//! [x] compile-time folding of constant expressions
+//! [x] peephole optimizations over the bytecode (`-O0` disables them)
 //! [x] classes and methods
 //! [x] `len()` function for strings and lists
 //! [x] functions for type casts (such as `ftoi()`, `itou()`, `utof()`, etc.)
@@ -28,24 +30,105 @@
 //! [ ] traits
 //! [ ] generic functions and members, and type bounds
 //! [ ] hashmaps and  functions for them (`get!()`, `set!()`, etc.)
+//! [ ] parsing JSON objects into hashmaps - until hashmaps exist, `json_parse!()`
+//!     maps the JSON objects only to classes
+//! [ ] default function arguments and list repeat counts - once they exist, the
+//!     folded constants are the values they would accept
 //! [ ] short circuit logic operators
 //! [ ] a Chalcedony `shell` for direct execution of user commands
 //! [ ] parallelism between lexer, parser, interpreter and vm
 
-use chalcedony::interpreter::Chalcedony;
+use chalcedony::error::err;
+use chalcedony::formatter::Formatter;
+use chalcedony::interpreter::{Chalcedony, OptLevel};
 
 extern crate ahash;
 extern crate itertools;
 
 use std::env;
+use std::fs;
 
 fn main() {
     let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
-        eprintln!("Error: expected only 1 argument - a file to execute");
+    if args.len() < 2 {
+        eprintln!("Error: expected a file to execute");
         std::process::exit(1);
     }
 
+    if args[1] == "fmt" {
+        std::process::exit(format_scripts(&args[2..]));
+    }
+
     let mut interpreter = Chalcedony::new();
-    interpreter.interpret_script(args[1].clone());
+    let mut script_args = &args[1..];
+    while let Some(flag) = script_args.first() {
+        match flag.as_str() {
+            "--deny-warnings" => interpreter.set_deny_warnings(true),
+            "-O0" => interpreter.set_opt_level(OptLevel::O0),
+            "-O1" => interpreter.set_opt_level(OptLevel::O1),
+            _ => break,
+        }
+        script_args = &script_args[1..];
+    }
+    let Some(script) = script_args.first() else {
+        eprintln!("{}", err("expected a file to execute"));
+        std::process::exit(1);
+    };
+
+    /* the script's arguments start with the script itself */
+    interpreter.set_argv(script_args.to_vec());
+    interpreter.interpret_script(script.clone());
+    if let Some(code) = interpreter.exit_code() {
+        std::process::exit(code);
+    }
+    if interpreter.has_failed() {
+        std::process::exit(1);
+    }
+}
+
+/// Formats the scripts in place. With `--check` the scripts are left unchanged
+/// and a non-zero exit code is returned if any of them is not formatted.
+fn format_scripts(args: &[String]) -> i32 {
+    let check = args.iter().any(|arg| arg == "--check");
+    let files: Vec<&String> = args.iter().filter(|arg| *arg != "--check").collect();
+    if files.is_empty() {
+        eprintln!("{}", err("expected a file to format"));
+        return 1;
+    }
+
+    let mut exit_code = 0;
+    for file in files {
+        let Ok(code) = fs::read_to_string(file) else {
+            eprintln!("{}", err(&format!("could not open the script `{}`", file)));
+            exit_code = 1;
+            continue;
+        };
+
+        let formatted = match Formatter::format(&code, Some(file.clone())) {
+            Ok(formatted) => formatted,
+            Err(errors) => {
+                eprint!("{}", errors);
+                exit_code = 1;
+                continue;
+            }
+        };
+        if formatted == code {
+            continue;
+        }
+
+        if check {
+            eprintln!(
+                "{}",
+                err(&format!("the script `{}` is not formatted", file))
+            );
+            exit_code = 1;
+        } else if fs::write(file, formatted).is_err() {
+            eprintln!(
+                "{}",
+                err(&format!("could not write to the script `{}`", file))
+            );
+            exit_code = 1;
+        }
+    }
+    exit_code
 }
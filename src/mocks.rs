@@ -5,6 +5,8 @@
 use crate::error::span::{Position, Span, Spanning};
 use crate::lexer::{Token, TokenKind};
 
+use std::cell::RefCell;
+use std::io::{self, Write};
 use std::rc::Rc;
 
 pub struct SpanMock();
@@ -17,6 +19,10 @@ impl Spanning for SpanMock {
     fn filename(&self) -> Option<String> {
         None
     }
+
+    fn line(&self, _: usize) -> Option<String> {
+        None
+    }
 }
 
 impl SpanMock {
@@ -30,6 +36,32 @@ impl SpanMock {
     }
 }
 
+/// A `Write` sink, whose contents remain accessible after it is passed to the
+/// interpreter. Used to capture the output of the scripts.
+#[derive(Clone, Default)]
+pub struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+impl SharedBuffer {
+    pub fn new() -> Self {
+        SharedBuffer::default()
+    }
+
+    pub fn contents(&self) -> String {
+        String::from_utf8_lossy(&self.0.borrow()).to_string()
+    }
+}
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
 pub fn mock_token(kind: TokenKind, span: Span) -> Token {
     let src = "not used".to_string();
     Token { kind, span, src }
@@ -21,6 +21,7 @@ pub enum NodeAttribute {
 // Syntax:
 // \<attribute\>.\<attribute\>.\<attribute\>(...)
 // \<namespace\>::\<func-call-attribute\>.\<attribute\>.\<attribute\>(...)
+// \<namespace\>::\<var-call-attribute\>.\<attribute\>.\<attribute\>(...)
 //
 // where an `<attribute>` is either a function call (a method) or a variable
 // call (member call)
@@ -62,6 +63,23 @@ impl NodeAttribute {
             }
 
             TokenKind::Special(Special::Resolution) if first_iter => {
+                /* `<namespace>::<name>` without a call refers to a global of an
+                 * imported module */
+                let is_call = reader
+                    .peek_nth(3)
+                    .is_some_and(|tok| tok.kind == TokenKind::Delimiter(Delimiter::OpenPar));
+                if !is_call {
+                    let namespace = NodeVarCall::new(reader.advance().unwrap())?;
+                    reader.advance();
+                    let name = reader.expect_ident()?;
+                    let span =
+                        Span::new(namespace.span.start, reader.current().end, reader.spanner());
+                    return Ok(NodeAttribute::VarCall(NodeVarCall {
+                        name: format!("{}::{}", namespace.name, name),
+                        span,
+                    }));
+                }
+
                 let buffer = reader.advance_scope(
                     TokenKind::Delimiter(Delimiter::OpenPar),
                     TokenKind::Delimiter(Delimiter::ClosePar),
@@ -1,7 +1,7 @@
 use crate::error::span::Span;
 use crate::error::{ChalError, ParserError, ParserErrorKind};
-use crate::lexer::{Delimiter, Special, Token, TokenKind};
-use crate::parser::ast::{NodeAttrRes, NodeAttribute, NodeValue, NodeVarCall};
+use crate::lexer::{Delimiter, FmtStrPart, Special, Token, TokenKind};
+use crate::parser::ast::{NodeAttrRes, NodeAttribute, NodeFuncCall, NodeValue, NodeVarCall};
 use crate::{lexer, vecdeq};
 
 use crate::common::operators::{BinOprType, UnaryOprType};
@@ -263,6 +263,10 @@ impl NodeExpr {
                         current
                     );
                 }
+                TokenKind::FmtStr(parts) => {
+                    let node = fmt_str_call(parts, &current)?;
+                    push_terminal!(NodeExprInner::Resolution(node), output, prev_type, current);
+                }
                 TokenKind::Bool(val) => {
                     push_terminal!(
                         NodeExprInner::Value(NodeValue::Bool(*val)),
@@ -407,6 +411,47 @@ impl NodeExpr {
     }
 }
 
+/// Formatted strings are desugared into a call to `format()`, i.e.
+/// `f"{x} and {y:.2}"` is the same as `format("{} and {:.2}", x, y)`.
+fn fmt_str_call(parts: &[FmtStrPart], current: &Token) -> Result<NodeAttrRes, ChalError> {
+    let mut fmt = String::new();
+    let mut args = Vec::<NodeExpr>::new();
+
+    for part in parts {
+        match part {
+            FmtStrPart::Str(text) => fmt.push_str(&text.replace('{', "{{").replace('}', "}}")),
+            FmtStrPart::Expr { tokens, spec, span } => {
+                fmt.push('{');
+                if !spec.is_empty() {
+                    fmt.push(':');
+                    fmt.push_str(spec);
+                }
+                fmt.push('}');
+
+                let reader = TokenReader::new(tokens.clone(), span.clone());
+                args.push(NodeExpr::new(reader)?);
+            }
+        }
+    }
+
+    let fmt_expr = NodeExpr {
+        expr: vecdeq![NodeExprInner::Value(NodeValue::Str(fmt))],
+        span: current.span.clone(),
+    };
+    args.insert(0, fmt_expr);
+
+    let call = NodeFuncCall {
+        name: "format".to_string(),
+        args,
+        span: current.span.clone(),
+        namespace: None,
+    };
+    Ok(NodeAttrRes {
+        resolution: vec![NodeAttribute::FuncCall(call)],
+        span: current.span.clone(),
+    })
+}
+
 fn advance_inline_class(
     reader: &mut TokenReader,
     current: &Token,
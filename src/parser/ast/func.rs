@@ -11,6 +11,8 @@ use std::collections::VecDeque;
 pub struct Arg {
     pub name: String,
     pub ty: Type,
+    /* the span of the argument's name and type inside the header */
+    pub span: Span,
 }
 
 /// The node representing the creation of a function. The span refers to the function's
@@ -97,6 +99,7 @@ impl NodeFuncDef {
             }
 
             let name = header.expect_ident()?;
+            let arg_start = header.current().start;
             /* if the first argument of a method is `self`, the type could be implied */
             let ty: Type = if namespace.is_some()
                 && first_iter
@@ -109,7 +112,12 @@ impl NodeFuncDef {
                 header.expect_type()?
             };
 
-            args.push_back(Arg { name, ty });
+            let arg_span = Span::new(arg_start, header.current().end, reader.spanner());
+            args.push_back(Arg {
+                name,
+                ty,
+                span: arg_span,
+            });
             first_iter = false;
         }
 
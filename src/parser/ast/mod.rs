@@ -15,7 +15,7 @@ mod var;
 pub use class::{NodeAttrRes, NodeAttribute, NodeClass};
 pub use expr::{NodeExpr, NodeExprInner, NodeInlineClass, NodeList};
 pub use func::{NodeFuncCall, NodeFuncCallStmnt, NodeFuncDef};
-pub use program::{NodeImport, NodeProg};
+pub use program::{ImportKind, NodeImport, NodeProg};
 pub use stmnt::{
     NodeAssign, NodeBreakStmnt, NodeContStmnt, NodeElifStmnt, NodeElseStmnt, NodeForLoop,
     NodeIfBranch, NodeIfStmnt, NodeRetStmnt, NodeStmnt, NodeThrow, NodeTryCatch, NodeWhileLoop,
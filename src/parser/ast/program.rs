@@ -1,8 +1,9 @@
 use crate::error::span::{Span, Spanning};
 use crate::error::ChalError;
-use crate::lexer::{Keyword, Line, TokenKind};
+use crate::lexer::{Keyword, Line, Special, TokenKind};
 use crate::parser::ast::{
-    NodeAssign, NodeClass, NodeForLoop, NodeFuncDef, NodeIfStmnt, NodeVarDef, NodeWhileLoop,
+    NodeAssign, NodeClass, NodeForLoop, NodeFuncDef, NodeIfStmnt, NodeVarCall, NodeVarDef,
+    NodeWhileLoop,
 };
 
 use crate::parser::{LineReader, TokenReader};
@@ -34,29 +35,94 @@ pub enum NodeProg {
 ///
 /// Syntax:
 /// `import` \<path\>
+/// `import` \<path\> `as` \<namespace\>
+/// `from` \<path\> `import` \<name\>, \<name\>, ...
 ///
-/// where `<path>` is a string literal
-#[derive(Debug)]
+/// where `<path>` is either a string literal or a standard library module
+/// (`std::<module>`). The `.ch` extension could be omitted, and if the script is
+/// not found relative to the current one, the directories inside the
+/// `CHAL_PATH` environment variable are searched.
+#[derive(Debug, PartialEq)]
 pub struct NodeImport {
     pub path: String,
+    pub kind: ImportKind,
     pub span: Span,
 }
 
+/// The way the imported script's definitions are exposed to the importing one.
+#[derive(Debug, PartialEq)]
+pub enum ImportKind {
+    /// Every definition is added to the global scope.
+    Global,
+    /// The functions and the globals are accessible only through
+    /// `<namespace>::<func-name>()` and `<namespace>::<var-name>`.
+    Namespaced(String),
+    /// Only the listed definitions are added to the global scope.
+    Selective(Vec<NodeVarCall>),
+}
+
 impl NodeImport {
     pub fn new(mut reader: TokenReader) -> Result<Self, ChalError> {
         let start = reader.current().start;
+
+        /* `from` is not a keyword, so it could still be used as an identifier */
+        if reader.peek_is_exact(TokenKind::Identifier("from".to_string())) {
+            reader.advance();
+            let path = NodeImport::advance_path(&mut reader)?;
+            reader.expect_exact(TokenKind::Keyword(Keyword::Import))?;
+
+            let mut items = Vec::<NodeVarCall>::new();
+            loop {
+                items.push(NodeVarCall::new(
+                    reader.expect(TokenKind::Identifier(String::new()))?,
+                )?);
+                if !reader.peek_is_exact(TokenKind::Special(Special::Comma)) {
+                    break;
+                }
+                reader.advance();
+            }
+
+            let end = reader.current().end;
+            reader.expect_exact(TokenKind::Newline)?;
+            return Ok(NodeImport {
+                path,
+                kind: ImportKind::Selective(items),
+                span: Span::new(start, end, reader.spanner()),
+            });
+        }
+
         reader.expect_exact(TokenKind::Keyword(Keyword::Import))?;
+        let path = NodeImport::advance_path(&mut reader)?;
+
+        let mut kind = ImportKind::Global;
+        if reader.peek_is_exact(TokenKind::Identifier("as".to_string())) {
+            reader.advance();
+            kind = ImportKind::Namespaced(reader.expect_ident()?);
+        }
 
-        let TokenKind::Str(path) = reader.expect(TokenKind::Str(String::new()))?.kind else {
-            unreachable!()
-        };
         let end = reader.current().end;
+        reader.expect_exact(TokenKind::Newline)?;
 
         Ok(NodeImport {
             path,
+            kind,
             span: Span::new(start, end, reader.spanner()),
         })
     }
+
+    fn advance_path(reader: &mut TokenReader) -> Result<String, ChalError> {
+        /* standard library modules are referred to by name, i.e. `std::math` */
+        if reader.peek_is_exact(TokenKind::Identifier("std".to_string())) {
+            reader.advance();
+            reader.expect_exact(TokenKind::Special(Special::Resolution))?;
+            return Ok(format!("std::{}", reader.expect_ident()?));
+        }
+
+        let TokenKind::Str(path) = reader.expect(TokenKind::Str(String::new()))?.kind else {
+            unreachable!()
+        };
+        Ok(path)
+    }
 }
 
 /* a wrapper for building a node from a single line statement */
@@ -120,6 +186,17 @@ impl NodeProg {
                 multiline_stmnt!(Class, NodeClass, chunk, spanner)
             }
 
+            TokenKind::Identifier(ref ident)
+                if ident == "from"
+                    && match front_line.tokens.get(1).map(|tok| &tok.kind) {
+                        Some(TokenKind::Str(_)) => true,
+                        Some(TokenKind::Identifier(module)) => module == "std",
+                        _ => false,
+                    } =>
+            {
+                single_line_stmnt!(Import, NodeImport, chunk, spanner)
+            }
+
             TokenKind::Identifier(_) => {
                 let mut reader =
                     TokenReader::new(front_line.tokens.clone(), front_tok.span.clone());
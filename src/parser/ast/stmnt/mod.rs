@@ -37,6 +37,26 @@ pub enum NodeStmnt {
     Throw(NodeThrow),
 }
 
+impl NodeStmnt {
+    /// The span of the statement's most representative part, i.e. the
+    /// condition of `if` statements, the iterator of `for` loops, etc.
+    pub fn span(&self) -> Span {
+        match self {
+            NodeStmnt::VarDef(node) => node.span.clone(),
+            NodeStmnt::FuncCall(NodeFuncCallStmnt(node)) => node.span.clone(),
+            NodeStmnt::Assign(node) => node.lhs.span.clone(),
+            NodeStmnt::RetStmnt(node) => node.span.clone(),
+            NodeStmnt::IfStmnt(node) => node.condition.span.clone(),
+            NodeStmnt::WhileLoop(node) => node.condition.span.clone(),
+            NodeStmnt::ContStmnt(node) => node.span.clone(),
+            NodeStmnt::BreakStmnt(node) => node.span.clone(),
+            NodeStmnt::ForLoop(node) => node.iter.span.clone(),
+            NodeStmnt::TryCatch(node) => node.try_span.clone(),
+            NodeStmnt::Throw(NodeThrow(node)) => node.span.clone(),
+        }
+    }
+}
+
 /// Boils down to the `TokenKind::Keyword(Keyword::Continue)`. Can only be used
 /// in the context of a loop.
 #[derive(Debug, PartialEq)]
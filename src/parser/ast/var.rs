@@ -11,6 +11,9 @@ use crate::common::Type;
 /// Syntax:
 /// let \<var-name\> = \<expression\>
 /// let \<var-name\>: \<type\> = \<expression\>
+/// let \<var-name\>: \<type\>
+/// let mut \<var-name\> = \<expression\>
+/// let mut \<var-name\>: \<type\> = \<expression\>
 /// const \<var-name\> = \<expression\>
 /// const \<var-name\>: \<type\> = \<expression\>
 #[derive(Debug, PartialEq)]
@@ -19,6 +22,8 @@ pub struct NodeVarDef {
     pub name: String,
     pub value: NodeExpr,
     pub is_const: bool,
+    /// Only global variables could be mutable, i.e. assigned inside functions.
+    pub is_mut: bool,
     pub span: Span,
 }
 
@@ -43,6 +48,12 @@ impl NodeVarDef {
             reader.expect_exact(TokenKind::Keyword(Keyword::Let))?;
         }
 
+        let mut is_mut = false;
+        if !is_const && reader.peek_is_exact(TokenKind::Keyword(Keyword::Mut)) {
+            is_mut = true;
+            reader.advance();
+        }
+
         let name = reader.expect_ident()?;
         let span = reader.current();
 
@@ -53,6 +64,19 @@ impl NodeVarDef {
         {
             ty = reader.expect_type()?;
         }
+
+        /* the value is assigned later, checked while compiling */
+        if reader.peek_is_exact(TokenKind::Newline) {
+            reader.advance();
+            return Ok(NodeVarDef {
+                name,
+                ty,
+                value: NodeExpr::empty(span.clone()),
+                is_const,
+                is_mut,
+                span,
+            });
+        }
         reader.expect_exact(TokenKind::Operator(Operator::Eq))?;
 
         let rhs = reader.advance_until(|tk| tk == &TokenKind::Newline)?;
@@ -65,6 +89,7 @@ impl NodeVarDef {
             ty,
             value,
             is_const,
+            is_mut,
             span,
         })
     }
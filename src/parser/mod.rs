@@ -37,6 +37,14 @@ impl Parser {
         Parser { lexer, spanner }
     }
 
+    /// Used for scripts, which are not read from the filesystem, but still need
+    /// a name to be displayed in the error messages.
+    pub fn from_source(code: &str, filename: String) -> Self {
+        let lexer = Lexer::new(code, Some(filename));
+        let spanner = lexer.spanner();
+        Parser { lexer, spanner }
+    }
+
     pub fn from_file(filename: String) -> Option<Self> {
         let lexer = Lexer::from_file(filename)?;
         let spanner = lexer.spanner();
@@ -41,9 +41,7 @@ impl TokenReader {
     }
 
     pub fn advance(&mut self) -> Option<Token> {
-        let Some(res) = self.src.pop_front() else {
-            return None;
-        };
+        let res = self.src.pop_front()?;
 
         self.current.start = res.span.start;
         self.current.end = res.span.end;
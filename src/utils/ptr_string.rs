@@ -132,14 +132,16 @@ impl fmt::Debug for PtrString {
 impl From<String> for PtrString {
     fn from(val: String) -> PtrString {
         unsafe {
+            /* the string is stored as chars, so multibyte chars take one slot */
+            let val_len = val.chars().count();
+
             // SAFETY: the error value is upon isize overflow, which would be an
             // extremely rare case
-            let layout = Layout::array::<*const char>(val.len() + 1)
+            let layout = Layout::array::<*const char>(val_len + 1)
                 .expect("Error: creating a string with size greater than isize::MAX");
 
             let res = alloc(layout) as *mut char;
 
-            let val_len = val.len();
             for (idx, ch) in val.chars().enumerate() {
                 ptr::write(res.add(idx), ch);
             }
@@ -1,4 +1,5 @@
 use std::collections::VecDeque;
+use std::ops::Range;
 
 /// A wrapper around the default `Vec<T>` type, used for better code readability.
 #[derive(Debug, Default)]
@@ -44,10 +45,20 @@ impl<T> Stack<T> {
         self.values.truncate(len)
     }
 
+    // Removes the elements in the range, shifting the following ones down.
+    pub fn remove_range(&mut self, range: Range<usize>) {
+        self.values.drain(range);
+    }
+
     pub fn peek(&self) -> Option<&T> {
         self.values.last()
     }
 
+    // Iterates over the elements from the bottom to the top of the stack.
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.values.iter()
+    }
+
     pub fn is_empty(&self) -> bool {
         self.values.is_empty()
     }
@@ -52,14 +52,15 @@ macro_rules! apply_bin_operator {
 
 pub fn add(cvm: &mut Cvm, current_idx: usize) -> usize {
     fn add_str(cvm: &mut Cvm, lval: PtrString, rval: CvmObject) {
-        cvm.stack
-            .push(CvmObject::Str(format!("{}{}", lval, rval).into()))
+        let result = format!("{}{}", lval, cvm.heap.display(&rval));
+        cvm.stack.push(CvmObject::Str(result.into()))
     }
     fn add_list(cvm: &mut Cvm, list: CvmList, rval: CvmObject) {
         match rval {
             CvmObject::List(rhs_list) => {
                 for el in rhs_list.borrow().clone().into_iter() {
-                    list.borrow_mut().push_back(el.deep_copy());
+                    let el = cvm.heap.deep_copy(el);
+                    list.borrow_mut().push_back(el);
                 }
                 cvm.stack.push(CvmObject::List(list));
             }
@@ -97,12 +98,14 @@ pub fn mul(cvm: &mut Cvm, current_idx: usize) -> usize {
                         .push(CvmObject::List(Rc::new(RefCell::new(VecDeque::new()))));
                     return;
                 }
-                let CvmObject::List(iter) = CvmObject::List(list.clone()).deep_copy() else {
+                let CvmObject::List(iter) = cvm.heap.deep_copy(CvmObject::List(list.clone()))
+                else {
                     unreachable!();
                 };
                 for _ in 0..(rval - 1) {
                     for el in iter.borrow().clone().into_iter() {
-                        list.borrow_mut().push_back(el.deep_copy());
+                        let el = cvm.heap.deep_copy(el);
+                        list.borrow_mut().push_back(el);
                     }
                 }
                 cvm.stack.push(CvmObject::List(list));
@@ -263,9 +266,10 @@ fn eq_bool(cvm: &mut Cvm, lval: bool, right: CvmObject) {
 }
 
 fn eq_list(cvm: &mut Cvm, left: CvmList, right: CvmList) {
-    cvm.stack.push(CvmObject::Bool(
-        CvmObject::List(left) == CvmObject::List(right),
-    ));
+    let result = cvm
+        .heap
+        .equals(&CvmObject::List(left), &CvmObject::List(right));
+    cvm.stack.push(CvmObject::Bool(result));
 }
 
 pub fn lt(cvm: &mut Cvm, current_idx: usize) -> usize {
@@ -287,3 +291,78 @@ pub fn gt_eq(cvm: &mut Cvm, current_idx: usize) -> usize {
 pub fn eq(cvm: &mut Cvm, current_idx: usize) -> usize {
     apply_comp_operator!(cvm, current_idx, ==, eq_bool, eq_list)
 }
+
+/* the specialized operators fall back to the generic ones if the operands are
+ * not of the statically known type (e.g. the `uint` elements of an `[int]`
+ * list) */
+macro_rules! typed_operators {
+    ( $( $name:ident($variant:ident $opr:tt) -> $result:ident, $generic:ident; )* ) => {
+        $(
+            pub fn $name(cvm: &mut Cvm, current_idx: usize) -> usize {
+                let right = cvm.stack.pop().expect("expected an object on the stack");
+                let left = cvm.stack.top().expect("expected an object on the stack");
+                /* the result replaces the left operand in place */
+                match (&*left, &right) {
+                    (CvmObject::$variant(lval), CvmObject::$variant(rval)) => {
+                        *left = CvmObject::$result(*lval $opr *rval);
+                        current_idx
+                    }
+                    _ => {
+                        cvm.stack.push(right);
+                        $generic(cvm, current_idx)
+                    }
+                }
+            }
+        )*
+    };
+}
+
+typed_operators! {
+    add_i(Int +) -> Int, add;
+    sub_i(Int -) -> Int, sub;
+    mul_i(Int *) -> Int, mul;
+    div_i(Int /) -> Int, div;
+    mod_i(Int %) -> Int, modulo;
+    lt_i(Int <) -> Bool, lt;
+    gt_i(Int >) -> Bool, gt;
+    eq_i(Int ==) -> Bool, eq;
+    lt_eq_i(Int <=) -> Bool, lt_eq;
+    gt_eq_i(Int >=) -> Bool, gt_eq;
+
+    add_u(Uint +) -> Uint, add;
+    sub_u(Uint -) -> Uint, sub;
+    mul_u(Uint *) -> Uint, mul;
+    div_u(Uint /) -> Uint, div;
+    mod_u(Uint %) -> Uint, modulo;
+    lt_u(Uint <) -> Bool, lt;
+    gt_u(Uint >) -> Bool, gt;
+    eq_u(Uint ==) -> Bool, eq;
+    lt_eq_u(Uint <=) -> Bool, lt_eq;
+    gt_eq_u(Uint >=) -> Bool, gt_eq;
+
+    add_f(Float +) -> Float, add;
+    sub_f(Float -) -> Float, sub;
+    mul_f(Float *) -> Float, mul;
+    div_f(Float /) -> Float, div;
+    mod_f(Float %) -> Float, modulo;
+    lt_f(Float <) -> Bool, lt;
+    gt_f(Float >) -> Bool, gt;
+    eq_f(Float ==) -> Bool, eq;
+    lt_eq_f(Float <=) -> Bool, lt_eq;
+    gt_eq_f(Float >=) -> Bool, gt_eq;
+}
+
+pub fn concat_s(cvm: &mut Cvm, current_idx: usize) -> usize {
+    match get_operands(cvm) {
+        (CvmObject::Str(lval), rval) => {
+            let result = format!("{}{}", lval, cvm.heap.display(&rval));
+            cvm.stack.push(CvmObject::Str(result.into()));
+            current_idx
+        }
+        (left, right) => {
+            cvm.stack.push(left);
+            cvm.stack.push(right);
+            add(cvm, current_idx)
+        }
+    }
+}
@@ -0,0 +1,438 @@
+use crate::common::JsonSchema;
+use crate::vm::{Cvm, CvmObject, GcRef, Heap};
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::fmt::Write;
+use std::rc::Rc;
+
+/* returns the index of the next instruction */
+pub fn json_parse(cvm: &mut Cvm, schema: &JsonSchema, next_idx: usize) -> usize {
+    let CvmObject::Str(src) = cvm.stack.pop().expect("expected an object on the stack") else {
+        panic!("json_parse typing failed");
+    };
+
+    let mut parser = JsonParser::new(&src.to_string(), &mut cvm.heap);
+    match parser.parse_root(schema) {
+        Ok(obj) => {
+            cvm.stack.push(obj);
+            next_idx
+        }
+        Err(msg) => {
+            cvm.stack.push(CvmObject::Exception(msg.into()));
+            cvm.handle_exception()
+        }
+    }
+}
+
+/* returns the index of the next instruction */
+pub fn json_stringify(cvm: &mut Cvm, schema: &JsonSchema, next_idx: usize) -> usize {
+    let obj = cvm.stack.pop().expect("expected an object on the stack");
+
+    let mut stringifier = Stringifier {
+        heap: &cvm.heap,
+        enclosing: Vec::new(),
+        printing: Vec::new(),
+        out: String::new(),
+    };
+    stringifier.stringify(&obj, schema);
+    let result = stringifier.out;
+    cvm.stack.push(CvmObject::Str(result.into()));
+    next_idx
+}
+
+struct JsonParser<'a> {
+    src: Vec<char>,
+    pos: usize,
+    /* the parsed objects are allocated on the heap */
+    heap: &'a mut Heap,
+}
+
+/* resolves the schema of a recursive class from the enclosing objects' ones */
+fn resolve<'s>(schema: &'s JsonSchema, enclosing: &[&'s JsonSchema]) -> &'s JsonSchema {
+    match schema {
+        JsonSchema::Recursive(depth) => enclosing[*depth],
+        schema => schema,
+    }
+}
+
+impl<'a> JsonParser<'a> {
+    fn new(src: &str, heap: &'a mut Heap) -> Self {
+        JsonParser {
+            src: src.chars().collect(),
+            pos: 0,
+            heap,
+        }
+    }
+
+    fn error(&self, msg: &str) -> String {
+        format!("invalid JSON at position {}: {}", self.pos, msg)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.src.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(' ' | '\t' | '\n' | '\r') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, exp: char) -> Result<(), String> {
+        self.skip_whitespace();
+        if self.peek() != Some(exp) {
+            return Err(self.error(&format!("expected `{}`", exp)));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    /* consumes the next char if it is the expected one */
+    fn consume(&mut self, exp: char) -> bool {
+        self.skip_whitespace();
+        if self.peek() == Some(exp) {
+            self.pos += 1;
+            return true;
+        }
+        false
+    }
+
+    fn parse_root(&mut self, schema: &JsonSchema) -> Result<CvmObject, String> {
+        let result = self.parse(schema, &mut Vec::new())?;
+        self.skip_whitespace();
+        if self.peek().is_some() {
+            return Err(self.error("unexpected trailing characters"));
+        }
+        Ok(result)
+    }
+
+    fn parse<'s>(
+        &mut self,
+        schema: &'s JsonSchema,
+        enclosing: &mut Vec<&'s JsonSchema>,
+    ) -> Result<CvmObject, String> {
+        self.skip_whitespace();
+        let schema = resolve(schema, enclosing);
+        match schema {
+            JsonSchema::Int => {
+                let start = self.pos;
+                let num = self.number()?;
+                match num.parse::<i64>() {
+                    Ok(val) => Ok(CvmObject::Int(val)),
+                    Err(_) => {
+                        self.pos = start;
+                        Err(self.error("expected an `int`"))
+                    }
+                }
+            }
+
+            JsonSchema::Uint => {
+                let start = self.pos;
+                let num = self.number()?;
+                match num.parse::<u64>() {
+                    Ok(val) => Ok(CvmObject::Uint(val)),
+                    Err(_) => {
+                        self.pos = start;
+                        Err(self.error("expected an `uint`"))
+                    }
+                }
+            }
+
+            JsonSchema::Float => {
+                let start = self.pos;
+                let num = self.number()?;
+                match num.parse::<f64>() {
+                    Ok(val) => Ok(CvmObject::Float(val)),
+                    Err(_) => {
+                        self.pos = start;
+                        Err(self.error("expected a `float`"))
+                    }
+                }
+            }
+
+            JsonSchema::Str => Ok(CvmObject::Str(self.string()?.into())),
+
+            JsonSchema::Bool => {
+                if self.literal("true") {
+                    return Ok(CvmObject::Bool(true));
+                }
+                if self.literal("false") {
+                    return Ok(CvmObject::Bool(false));
+                }
+                Err(self.error("expected a `bool`"))
+            }
+
+            JsonSchema::List(el_schema) => {
+                self.expect('[')?;
+                let mut result = VecDeque::<CvmObject>::new();
+                if !self.consume(']') {
+                    loop {
+                        result.push_back(self.parse(el_schema, enclosing)?);
+                        if self.consume(']') {
+                            break;
+                        }
+                        self.expect(',')?;
+                    }
+                }
+                Ok(CvmObject::List(Rc::new(RefCell::new(result))))
+            }
+
+            JsonSchema::Object(members) => {
+                self.expect('{')?;
+                enclosing.push(schema);
+                let mut result: Vec<Option<CvmObject>> = vec![None; members.len()];
+                if !self.consume('}') {
+                    loop {
+                        self.skip_whitespace();
+                        let key = self.string()?;
+                        self.expect(':')?;
+
+                        /* any keys, which are not members of the class are ignored */
+                        match members.iter().position(|(name, _)| *name == key) {
+                            Some(idx) => {
+                                let (_, member_schema) = members.get(idx).unwrap();
+                                *result.get_mut(idx).unwrap() =
+                                    Some(self.parse(member_schema, enclosing)?);
+                            }
+                            None => self.skip_value()?,
+                        }
+
+                        if self.consume('}') {
+                            break;
+                        }
+                        self.expect(',')?;
+                    }
+                }
+                enclosing.pop();
+
+                let mut data = Vec::<CvmObject>::with_capacity(members.len());
+                for ((name, _), member) in members.iter().zip(result) {
+                    let Some(member) = member else {
+                        return Err(self.error(&format!("missing member `{}`", name)));
+                    };
+                    data.push(member);
+                }
+                Ok(CvmObject::Object(self.heap.alloc(data)))
+            }
+
+            JsonSchema::Recursive(_) => unreachable!("the schema is already resolved"),
+        }
+    }
+
+    /* skips over any valid value, regardless of its type */
+    fn skip_value(&mut self) -> Result<(), String> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('"') => self.string().map(|_| ()),
+            Some('[') => {
+                self.pos += 1;
+                if self.consume(']') {
+                    return Ok(());
+                }
+                loop {
+                    self.skip_value()?;
+                    if self.consume(']') {
+                        return Ok(());
+                    }
+                    self.expect(',')?;
+                }
+            }
+            Some('{') => {
+                self.pos += 1;
+                if self.consume('}') {
+                    return Ok(());
+                }
+                loop {
+                    self.skip_whitespace();
+                    self.string()?;
+                    self.expect(':')?;
+                    self.skip_value()?;
+                    if self.consume('}') {
+                        return Ok(());
+                    }
+                    self.expect(',')?;
+                }
+            }
+            _ if self.literal("true") || self.literal("false") || self.literal("null") => Ok(()),
+            _ => self.number().map(|_| ()),
+        }
+    }
+
+    fn literal(&mut self, lit: &str) -> bool {
+        let end = self.pos + lit.len();
+        if end > self.src.len() || !self.src[self.pos..end].iter().copied().eq(lit.chars()) {
+            return false;
+        }
+        self.pos = end;
+        true
+    }
+
+    fn number(&mut self) -> Result<String, String> {
+        let start = self.pos;
+        while let Some('0'..='9' | '-' | '+' | '.' | 'e' | 'E') = self.peek() {
+            self.pos += 1;
+        }
+        if start == self.pos {
+            return Err(self.error("expected a number"));
+        }
+        Ok(self.src[start..self.pos].iter().collect())
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        if self.peek() != Some('"') {
+            return Err(self.error("expected a `str`"));
+        }
+        self.pos += 1;
+
+        let mut result = String::new();
+        loop {
+            let Some(current) = self.peek() else {
+                return Err(self.error("unterminated string"));
+            };
+            self.pos += 1;
+
+            match current {
+                '"' => return Ok(result),
+                '\\' => {
+                    let Some(escaped) = self.peek() else {
+                        return Err(self.error("unterminated string"));
+                    };
+                    self.pos += 1;
+                    match escaped {
+                        '"' => result.push('"'),
+                        '\\' => result.push('\\'),
+                        '/' => result.push('/'),
+                        'b' => result.push('\u{8}'),
+                        'f' => result.push('\u{c}'),
+                        'n' => result.push('\n'),
+                        'r' => result.push('\r'),
+                        't' => result.push('\t'),
+                        'u' => result.push(self.unicode_escape()?),
+                        _ => return Err(self.error("invalid escape sequence")),
+                    }
+                }
+                _ => result.push(current),
+            }
+        }
+    }
+
+    /* parses the 4 hex digits after `\u`, combining any surrogate pairs */
+    fn unicode_escape(&mut self) -> Result<char, String> {
+        let high = self.hex_code()?;
+        if !(0xd800..0xdc00).contains(&high) {
+            return char::from_u32(high).ok_or_else(|| self.error("invalid unicode escape"));
+        }
+
+        if !self.literal("\\u") {
+            return Err(self.error("unpaired surrogate in unicode escape"));
+        }
+        let low = self.hex_code()?;
+        if !(0xdc00..0xe000).contains(&low) {
+            return Err(self.error("unpaired surrogate in unicode escape"));
+        }
+        let code = 0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00);
+        char::from_u32(code).ok_or_else(|| self.error("invalid unicode escape"))
+    }
+
+    fn hex_code(&mut self) -> Result<u32, String> {
+        let end = self.pos + 4;
+        if end > self.src.len() {
+            return Err(self.error("invalid unicode escape"));
+        }
+        let digits: String = self.src[self.pos..end].iter().collect();
+        let Ok(code) = u32::from_str_radix(&digits, 16) else {
+            return Err(self.error("invalid unicode escape"));
+        };
+        self.pos = end;
+        Ok(code)
+    }
+}
+
+struct Stringifier<'a, 's> {
+    heap: &'a Heap,
+    enclosing: Vec<&'s JsonSchema>,
+    /* the objects, whose members are being written */
+    printing: Vec<GcRef>,
+    out: String,
+}
+
+impl<'s> Stringifier<'_, 's> {
+    /* JSON could represent neither cycles, nor the non-finite floats, so both
+     * are written as `null` and the conversion never fails */
+    fn stringify(&mut self, obj: &CvmObject, schema: &'s JsonSchema) {
+        let schema = resolve(schema, &self.enclosing);
+        match (obj, schema) {
+            (CvmObject::Int(val), _) => {
+                let _ = write!(self.out, "{}", val);
+            }
+            (CvmObject::Uint(val), _) => {
+                let _ = write!(self.out, "{}", val);
+            }
+            (CvmObject::Float(val), _) if !val.is_finite() => self.out.push_str("null"),
+            (CvmObject::Float(val), _) => {
+                /* the debug format always keeps the decimal point */
+                let _ = write!(self.out, "{:?}", val);
+            }
+            (CvmObject::Str(val), _) => escape_str(&val.to_string(), &mut self.out),
+            (CvmObject::Bool(val), _) => {
+                let _ = write!(self.out, "{}", val);
+            }
+
+            (CvmObject::List(list), JsonSchema::List(el_schema)) => {
+                self.out.push('[');
+                for (idx, el) in list.borrow().iter().enumerate() {
+                    if idx > 0 {
+                        self.out.push(',');
+                    }
+                    self.stringify(el, el_schema);
+                }
+                self.out.push(']');
+            }
+
+            (CvmObject::Object(obj), JsonSchema::Object(_)) if self.printing.contains(obj) => {
+                self.out.push_str("null");
+            }
+            (CvmObject::Object(obj), JsonSchema::Object(members)) => {
+                self.printing.push(*obj);
+                self.enclosing.push(schema);
+                self.out.push('{');
+                for (idx, ((name, member_schema), member)) in
+                    members.iter().zip(self.heap.get(*obj)).enumerate()
+                {
+                    if idx > 0 {
+                        self.out.push(',');
+                    }
+                    escape_str(name, &mut self.out);
+                    self.out.push(':');
+                    self.stringify(member, member_schema);
+                }
+                self.out.push('}');
+                self.enclosing.pop();
+                self.printing.pop();
+            }
+
+            _ => panic!("json_stringify typing failed"),
+        }
+    }
+}
+
+fn escape_str(val: &str, out: &mut String) {
+    out.push('"');
+    for current in val.chars() {
+        match current {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\u{8}' => out.push_str("\\b"),
+            '\u{c}' => out.push_str("\\f"),
+            current if (current as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", current as u32);
+            }
+            current => out.push(current),
+        }
+    }
+    out.push('"');
+}
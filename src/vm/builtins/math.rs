@@ -0,0 +1,135 @@
+use crate::common::MathFn;
+use crate::vm::{Cvm, CvmObject};
+
+fn pop_float(cvm: &mut Cvm) -> f64 {
+    let CvmObject::Float(val) = cvm.stack.pop().expect("expected an object on the stack") else {
+        panic!("math function typing failed");
+    };
+    val
+}
+
+/* an exponent, which does not fit into `u32`, overflows unless the base is
+ * -1, 0 or 1 */
+fn checked_pow_int(base: i64, exp: u64) -> Option<i64> {
+    match u32::try_from(exp) {
+        Ok(exp) => base.checked_pow(exp),
+        Err(_) if base == -1 => Some(if exp.is_multiple_of(2) { 1 } else { -1 }),
+        Err(_) if base == 0 || base == 1 => Some(base),
+        Err(_) => None,
+    }
+}
+
+fn checked_pow_uint(base: u64, exp: u64) -> Option<u64> {
+    match u32::try_from(exp) {
+        Ok(exp) => base.checked_pow(exp),
+        Err(_) if base <= 1 => Some(base),
+        Err(_) => None,
+    }
+}
+
+/* `None` if the integer result overflows */
+fn pow(base: CvmObject, exp: CvmObject, saturate: bool) -> Option<CvmObject> {
+    match (base, exp) {
+        (CvmObject::Float(base), CvmObject::Float(exp)) => Some(CvmObject::Float(base.powf(exp))),
+        (CvmObject::Int(base), CvmObject::Uint(exp)) => {
+            let result = checked_pow_int(base, exp);
+            /* only a negative base with an odd exponent overflows downwards */
+            let bound = match base < 0 && !exp.is_multiple_of(2) {
+                true => i64::MIN,
+                false => i64::MAX,
+            };
+            result.or(saturate.then_some(bound)).map(CvmObject::Int)
+        }
+        (CvmObject::Uint(base), CvmObject::Uint(exp)) => checked_pow_uint(base, exp)
+            .or(saturate.then_some(u64::MAX))
+            .map(CvmObject::Uint),
+        _ => panic!("math function typing failed"),
+    }
+}
+
+/* returns the index of the next instruction; the safe functions never raise
+ * exceptions, so their integer results saturate at the bounds of the type */
+pub fn math(cvm: &mut Cvm, func: MathFn, next_idx: usize) -> usize {
+    let result = match func {
+        MathFn::Sqrt => CvmObject::Float(pop_float(cvm).sqrt()),
+        MathFn::Exp => CvmObject::Float(pop_float(cvm).exp()),
+        MathFn::Ln => CvmObject::Float(pop_float(cvm).ln()),
+        MathFn::Log10 => CvmObject::Float(pop_float(cvm).log10()),
+        MathFn::Sin => CvmObject::Float(pop_float(cvm).sin()),
+        MathFn::Cos => CvmObject::Float(pop_float(cvm).cos()),
+        MathFn::Tan => CvmObject::Float(pop_float(cvm).tan()),
+        MathFn::Asin => CvmObject::Float(pop_float(cvm).asin()),
+        MathFn::Acos => CvmObject::Float(pop_float(cvm).acos()),
+        MathFn::Atan => CvmObject::Float(pop_float(cvm).atan()),
+
+        /* the float to integer casts saturate, with `NaN` resulting in 0 */
+        MathFn::Floor => CvmObject::Int(pop_float(cvm).floor() as i64),
+        MathFn::Ceil => CvmObject::Int(pop_float(cvm).ceil() as i64),
+        MathFn::Round => CvmObject::Int(pop_float(cvm).round() as i64),
+
+        MathFn::IsNan => CvmObject::Bool(pop_float(cvm).is_nan()),
+        MathFn::IsInf => CvmObject::Bool(pop_float(cvm).is_infinite()),
+
+        MathFn::Pow | MathFn::CheckedPow => {
+            let exp = cvm.stack.pop().expect("expected an object on the stack");
+            let base = cvm.stack.pop().expect("expected an object on the stack");
+            match pow(base, exp, func == MathFn::Pow) {
+                Some(result) => result,
+                None => {
+                    cvm.stack.push(CvmObject::Exception(
+                        "integer overflow in `pow!()`".to_string().into(),
+                    ));
+                    return cvm.handle_exception();
+                }
+            }
+        }
+
+        MathFn::Abs => match cvm.stack.pop().expect("expected an object on the stack") {
+            CvmObject::Int(val) => CvmObject::Int(val.saturating_abs()),
+            CvmObject::Float(val) => CvmObject::Float(val.abs()),
+            val @ CvmObject::Uint(_) => val,
+            _ => panic!("math function typing failed"),
+        },
+
+        MathFn::Min | MathFn::Max => {
+            let right = cvm.stack.pop().expect("expected an object on the stack");
+            let left = cvm.stack.pop().expect("expected an object on the stack");
+            let is_min = func == MathFn::Min;
+            match (left, right) {
+                (CvmObject::Int(l), CvmObject::Int(r)) => {
+                    CvmObject::Int(if is_min { l.min(r) } else { l.max(r) })
+                }
+                (CvmObject::Uint(l), CvmObject::Uint(r)) => {
+                    CvmObject::Uint(if is_min { l.min(r) } else { l.max(r) })
+                }
+                (CvmObject::Float(l), CvmObject::Float(r)) => {
+                    CvmObject::Float(if is_min { l.min(r) } else { l.max(r) })
+                }
+                _ => panic!("math function typing failed"),
+            }
+        }
+
+        // NOTE: unlike the standard library's `clamp()`, an invalid range does
+        // not panic - the upper bound takes precedence
+        MathFn::Clamp => {
+            let hi = cvm.stack.pop().expect("expected an object on the stack");
+            let lo = cvm.stack.pop().expect("expected an object on the stack");
+            let val = cvm.stack.pop().expect("expected an object on the stack");
+            match (val, lo, hi) {
+                (CvmObject::Int(val), CvmObject::Int(lo), CvmObject::Int(hi)) => {
+                    CvmObject::Int(val.max(lo).min(hi))
+                }
+                (CvmObject::Uint(val), CvmObject::Uint(lo), CvmObject::Uint(hi)) => {
+                    CvmObject::Uint(val.max(lo).min(hi))
+                }
+                (CvmObject::Float(val), CvmObject::Float(lo), CvmObject::Float(hi)) => {
+                    CvmObject::Float(val.max(lo).min(hi))
+                }
+                _ => panic!("math function typing failed"),
+            }
+        }
+    };
+
+    cvm.stack.push(result);
+    next_idx
+}
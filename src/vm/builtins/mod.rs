@@ -2,14 +2,60 @@
 //! binary and unary operations between [`CvmObjects`].
 
 pub mod bin_opr;
+pub mod json;
 pub mod list;
+pub mod math;
 pub mod un_opr;
 
 use super::{Cvm, CvmObject};
+use crate::common::format::FmtSegment;
+
+use std::io::Write;
 
 pub fn print(cvm: &mut Cvm, next_idx: usize) -> usize {
     let obj = cvm.stack.pop().unwrap();
-    println!("{}", obj);
+    writeln!(cvm.stdout, "{}", cvm.heap.display(&obj)).expect("could not write to stdout");
+    next_idx
+}
+
+/* stops the execution, returning an index past the end of any code, so the
+ * host decides what to do with the exit code */
+pub fn exit(cvm: &mut Cvm) -> usize {
+    let CvmObject::Int(code) = cvm.stack.pop().unwrap() else {
+        panic!("exit typing failed")
+    };
+    /* the execution is stopped regardless of whether flushing succeeds */
+    let _ = cvm.stdout.flush();
+    let _ = cvm.stderr.flush();
+
+    /* the exit codes of the processes are 32-bit */
+    cvm.exit_code = Some(code.clamp(i32::MIN as i64, i32::MAX as i64) as i32);
+    cvm.call_stack.truncate(0);
+    cvm.stack.truncate(0);
+    cvm.catch_idx = None;
+    usize::MAX
+}
+
+pub fn input(cvm: &mut Cvm, next_idx: usize) -> usize {
+    let CvmObject::Str(prompt) = cvm.stack.pop().unwrap() else {
+        panic!("input prompt typing failed")
+    };
+    write!(cvm.stdout, "{}", prompt).expect("could not write to stdout");
+    cvm.stdout.flush().expect("could not write to stdout");
+
+    /* reaching the end of the input results in an empty string */
+    let mut line = String::new();
+    if cvm.stdin.read_line(&mut line).is_err() {
+        line.clear();
+    }
+    if line.ends_with('\n') {
+        line.pop();
+        if line.ends_with('\r') {
+            line.pop();
+        }
+    }
+
+    cvm.stack.push(CvmObject::Str(line.into()));
     next_idx
 }
 
@@ -18,6 +64,11 @@ pub fn assert(cvm: &mut Cvm, next_idx: usize) -> usize {
         panic!("assert typing failed")
     };
 
+    // this is a workaround so the tests fail on a failed assertion, instead of
+    // only reporting the unhandled exception
+    #[cfg(feature = "panicking-asserts")]
+    assert!(successful, "assertion failed");
+
     if !successful {
         cvm.stack
             .push(CvmObject::Exception("assertion failed".to_string().into()));
@@ -27,6 +78,84 @@ pub fn assert(cvm: &mut Cvm, next_idx: usize) -> usize {
     next_idx
 }
 
+pub fn format(cvm: &mut Cvm, segments: &[FmtSegment], next_idx: usize) -> usize {
+    let arg_count = segments
+        .iter()
+        .filter(|segment| matches!(segment, FmtSegment::Arg(_)))
+        .count();
+    let args_start = cvm.stack.len() - arg_count;
+
+    let mut result = String::new();
+    let mut arg_idx = args_start;
+    for segment in segments {
+        let spec = match segment {
+            FmtSegment::Str(text) => {
+                result.push_str(text);
+                continue;
+            }
+            FmtSegment::Arg(spec) => spec,
+        };
+
+        let arg = cvm.stack.get(arg_idx).expect("expected a format argument");
+        arg_idx += 1;
+        let val = match (arg, spec.precision) {
+            (CvmObject::Float(val), Some(precision)) => format!("{:.*}", precision, val),
+            (CvmObject::Str(val), Some(precision)) => {
+                val.to_string().chars().take(precision).collect()
+            }
+            (arg, _) => cvm.heap.display(arg).to_string(),
+        };
+        let is_numeric = matches!(
+            arg,
+            CvmObject::Int(_) | CvmObject::Uint(_) | CvmObject::Float(_)
+        );
+        result.push_str(&spec.pad(val, is_numeric));
+    }
+
+    cvm.stack.truncate(args_start);
+    cvm.stack.push(CvmObject::Str(result.into()));
+    next_idx
+}
+
+pub fn gc_collect(cvm: &mut Cvm, next_idx: usize) -> usize {
+    let freed = cvm.collect_garbage();
+    cvm.stack.push(CvmObject::Uint(freed as u64));
+    next_idx
+}
+
+pub fn gc_stats(cvm: &mut Cvm, next_idx: usize) -> usize {
+    let stats = cvm.heap.stats().to_string();
+    cvm.stack.push(CvmObject::Str(stats.into()));
+    next_idx
+}
+
+pub fn call_native(cvm: &mut Cvm, native_id: usize, next_idx: usize) -> usize {
+    let native = cvm
+        .natives
+        .get(native_id)
+        .expect("expected a valid native function id")
+        .clone();
+
+    let args_start = cvm.stack.len() - native.arg_count;
+    let mut args = Vec::<CvmObject>::with_capacity(native.arg_count);
+    for idx in args_start..cvm.stack.len() {
+        args.push(cvm.stack.get(idx).unwrap().clone());
+    }
+    cvm.stack.truncate(args_start);
+
+    match (native.func)(&mut cvm.heap, &args) {
+        Ok(_) if native.is_void => next_idx,
+        Ok(val) => {
+            cvm.stack.push(val);
+            next_idx
+        }
+        Err(msg) => {
+            cvm.stack.push(CvmObject::Exception(msg.into()));
+            cvm.handle_exception()
+        }
+    }
+}
+
 fn get_operands(cvm: &mut Cvm) -> (CvmObject, CvmObject) {
     let right = cvm.stack.pop().expect("expected an object on the stack");
     let left = cvm.stack.pop().expect("expected an object on the stack");
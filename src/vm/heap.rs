@@ -0,0 +1,336 @@
+//! The heap of the `CVM`, holding the objects created by the scripts.
+//!
+//! The objects are stored inside an arena and are referenced through handles
+//! ([`GcRef`]), so they could reference each other freely, including in
+//! cycles. The memory is reclaimed by a mark-and-sweep garbage collector - any
+//! object, which is not reachable from the roots (the value stack, holding the
+//! local variables of every call frame, and the globals), is freed and it's
+//! slot is reused. Each reuse bumps the slot's generation, so a stale handle
+//! to the freed object is caught instead of aliasing the new one. Lists are
+//! reference counted and are only traced through, since they could hold
+//! objects.
+//!
+//! The collection is run only before the instructions, which allocate objects,
+//! and by the builtin `gc_collect()`. At these points every object in use is
+//! reachable from the roots, so the handles passed to the host functions stay
+//! valid during their call.
+
+use super::{CvmList, CvmObject};
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::iter::zip;
+use std::rc::Rc;
+
+use ahash::{AHashMap, AHashSet};
+
+/// The number of live objects, after which the first automatic collection is
+/// run. After each collection the threshold is set to twice the number of the
+/// remaining objects.
+const MIN_GC_THRESHOLD: usize = 10_000;
+
+/// The handle of an object on the [`Heap`]. The indices and generations are
+/// 32-bit, so the handle does not grow the size of every `CvmObject`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GcRef {
+    idx: u32,
+    generation: u32,
+}
+
+/// The state of the heap, reported by the builtin `gc_stats()`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GcStats {
+    /// The number of objects currently on the heap.
+    pub live: usize,
+    /// The number of finished collections.
+    pub collections: usize,
+    /// The total number of objects freed by the collections.
+    pub freed: usize,
+}
+
+impl std::fmt::Display for GcStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "live objects: {}, collections: {}, freed objects: {}",
+            self.live, self.collections, self.freed
+        )
+    }
+}
+
+#[derive(Debug)]
+struct Slot {
+    /* incremented each time the slot's object is freed */
+    generation: u32,
+    /* the members of the object, `None` for the freed slots */
+    members: Option<Vec<CvmObject>>,
+}
+
+#[derive(Debug)]
+pub struct Heap {
+    objects: Vec<Slot>,
+    free: Vec<u32>,
+    threshold: usize,
+    stats: GcStats,
+}
+
+impl Default for Heap {
+    fn default() -> Self {
+        Heap::new()
+    }
+}
+
+/* the copies of the objects and the shared lists, made during a single deep
+ * copy, so the copy keeps the same shape (including any cycles) */
+#[derive(Default)]
+struct Copies {
+    objects: AHashMap<GcRef, GcRef>,
+    lists: AHashMap<*const RefCell<VecDeque<CvmObject>>, CvmList>,
+}
+
+impl Heap {
+    pub fn new() -> Self {
+        Heap {
+            objects: Vec::new(),
+            free: Vec::new(),
+            threshold: MIN_GC_THRESHOLD,
+            stats: GcStats::default(),
+        }
+    }
+
+    /// Moves the object's members on the heap and returns it's handle.
+    pub fn alloc(&mut self, members: Vec<CvmObject>) -> GcRef {
+        self.stats.live += 1;
+        let idx = match self.free.pop() {
+            Some(idx) => idx,
+            None => {
+                let idx = u32::try_from(self.objects.len()).expect("the heap is full");
+                self.objects.push(Slot {
+                    generation: 0,
+                    members: None,
+                });
+                idx
+            }
+        };
+        let slot = &mut self.objects[idx as usize];
+        slot.members = Some(members);
+        GcRef {
+            idx,
+            generation: slot.generation,
+        }
+    }
+
+    pub fn get(&self, obj: GcRef) -> &[CvmObject] {
+        self.objects
+            .get(obj.idx as usize)
+            .filter(|slot| slot.generation == obj.generation)
+            .and_then(|slot| slot.members.as_deref())
+            .expect("use of a collected object")
+    }
+
+    pub fn get_mut(&mut self, obj: GcRef) -> &mut [CvmObject] {
+        self.objects
+            .get_mut(obj.idx as usize)
+            .filter(|slot| slot.generation == obj.generation)
+            .and_then(|slot| slot.members.as_deref_mut())
+            .expect("use of a collected object")
+    }
+
+    pub fn stats(&self) -> GcStats {
+        self.stats
+    }
+
+    /// Whether enough objects were allocated since the last collection.
+    pub fn should_collect(&self) -> bool {
+        self.stats.live >= self.threshold
+    }
+
+    /// Frees every object, which is not reachable from the roots, and returns
+    /// the number of freed objects.
+    pub fn collect<'a>(&mut self, roots: impl Iterator<Item = &'a CvmObject>) -> usize {
+        let mut marked = vec![false; self.objects.len()];
+        let mut traced_lists = AHashSet::<*const RefCell<VecDeque<CvmObject>>>::new();
+        let mut pending = Vec::<GcRef>::new();
+
+        for root in roots {
+            trace(root, &mut marked, &mut traced_lists, &mut pending);
+        }
+        while let Some(obj) = pending.pop() {
+            for member in self.get(obj) {
+                trace(member, &mut marked, &mut traced_lists, &mut pending);
+            }
+        }
+
+        let mut freed = 0;
+        for (idx, (slot, is_marked)) in zip(self.objects.iter_mut(), marked).enumerate() {
+            if !is_marked && slot.members.take().is_some() {
+                slot.generation = slot.generation.wrapping_add(1);
+                self.free.push(idx as u32);
+                freed += 1;
+            }
+        }
+
+        self.stats.live -= freed;
+        self.stats.freed += freed;
+        self.stats.collections += 1;
+        self.threshold = MIN_GC_THRESHOLD.max(self.stats.live * 2);
+        freed
+    }
+
+    /// Copies the value along with every object and shared list it references.
+    pub fn deep_copy(&mut self, value: CvmObject) -> CvmObject {
+        self.deep_copy_internal(value, &mut Copies::default())
+    }
+
+    fn deep_copy_internal(&mut self, value: CvmObject, copies: &mut Copies) -> CvmObject {
+        match value {
+            CvmObject::List(list) => {
+                if Rc::strong_count(&list) == 1 {
+                    return CvmObject::List(list);
+                }
+                if let Some(copy) = copies.lists.get(&Rc::as_ptr(&list)) {
+                    return CvmObject::List(copy.clone());
+                }
+
+                let copy: CvmList = Rc::new(RefCell::new(VecDeque::new()));
+                copies.lists.insert(Rc::as_ptr(&list), copy.clone());
+                let elements = list.borrow().clone();
+                for el in elements {
+                    let el = self.deep_copy_internal(el, copies);
+                    copy.borrow_mut().push_back(el);
+                }
+                CvmObject::List(copy)
+            }
+            CvmObject::Object(obj) => {
+                if let Some(copy) = copies.objects.get(&obj) {
+                    return CvmObject::Object(*copy);
+                }
+
+                /* the copy is allocated before it's members, so any cycles lead
+                 * back to it */
+                let copy = self.alloc(Vec::new());
+                copies.objects.insert(obj, copy);
+                let members: Vec<CvmObject> = self
+                    .get(obj)
+                    .to_vec()
+                    .into_iter()
+                    .map(|member| self.deep_copy_internal(member, copies))
+                    .collect();
+                self.objects[copy.idx as usize].members = Some(members);
+                CvmObject::Object(copy)
+            }
+            value => value,
+        }
+    }
+
+    /// Compares the values, comparing the objects member by member.
+    pub fn equals(&self, left: &CvmObject, right: &CvmObject) -> bool {
+        self.equals_internal(left, right, &mut AHashSet::new())
+    }
+
+    fn equals_internal(
+        &self,
+        left: &CvmObject,
+        right: &CvmObject,
+        compared: &mut AHashSet<(GcRef, GcRef)>,
+    ) -> bool {
+        match (left, right) {
+            (CvmObject::List(left), CvmObject::List(right)) => {
+                let left = left.borrow();
+                let right = right.borrow();
+                left.len() == right.len()
+                    && zip(left.iter(), right.iter())
+                        .all(|(l, r)| self.equals_internal(l, r, compared))
+            }
+            (CvmObject::Object(left), CvmObject::Object(right)) => {
+                /* the pairs, which are already being compared, are part of a
+                 * cycle and are equal unless some other member differs */
+                if left == right || !compared.insert((*left, *right)) {
+                    return true;
+                }
+                zip(self.get(*left), self.get(*right))
+                    .all(|(l, r)| self.equals_internal(l, r, compared))
+            }
+            (left, right) => left == right,
+        }
+    }
+
+    /// Returns a wrapper, which formats the value along with the members of
+    /// the objects it references.
+    pub fn display<'a>(&'a self, value: &'a CvmObject) -> HeapDisplay<'a> {
+        HeapDisplay { heap: self, value }
+    }
+}
+
+fn trace(
+    value: &CvmObject,
+    marked: &mut [bool],
+    traced_lists: &mut AHashSet<*const RefCell<VecDeque<CvmObject>>>,
+    pending: &mut Vec<GcRef>,
+) {
+    match value {
+        CvmObject::Object(obj) if !marked[obj.idx as usize] => {
+            marked[obj.idx as usize] = true;
+            pending.push(*obj);
+        }
+        CvmObject::List(list) if traced_lists.insert(Rc::as_ptr(list)) => {
+            for el in list.borrow().iter() {
+                trace(el, marked, traced_lists, pending);
+            }
+        }
+        _ => {}
+    }
+}
+
+pub struct HeapDisplay<'a> {
+    heap: &'a Heap,
+    value: &'a CvmObject,
+}
+
+impl HeapDisplay<'_> {
+    /* `printing` holds the objects, whose members are being printed, so a
+     * cycle is printed as `{...}` */
+    fn write(
+        &self,
+        f: &mut std::fmt::Formatter,
+        value: &CvmObject,
+        printing: &mut Vec<GcRef>,
+    ) -> std::fmt::Result {
+        match value {
+            CvmObject::List(list) => {
+                write!(f, "[")?;
+                let list = list.borrow();
+                for el in list.iter() {
+                    self.write(f, el, printing)?;
+                    write!(f, ", ")?;
+                }
+                /* `\x08` is the same as `\b` */
+                if !list.is_empty() {
+                    write!(f, "\x08\x08")?;
+                }
+                write!(f, "]")
+            }
+            CvmObject::Object(obj) => {
+                if printing.contains(obj) {
+                    return write!(f, "{{...}}");
+                }
+                printing.push(*obj);
+                write!(f, "{{")?;
+                for member in self.heap.get(*obj) {
+                    self.write(f, member, printing)?;
+                    write!(f, ", ")?;
+                }
+                printing.pop();
+                /* `\x08` is the same as `\b` */
+                write!(f, "\x08\x08}}")
+            }
+            value => write!(f, "{}", value),
+        }
+    }
+}
+
+impl std::fmt::Display for HeapDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.write(f, self.value, &mut Vec::new())
+    }
+}
@@ -9,27 +9,52 @@
 //! meaning all expressions are computed on the [`stack`].
 
 mod builtins;
+mod heap;
 mod object;
 
 use builtins::{
     assert,
-    bin_opr::{add, and, div, eq, gt, gt_eq, lt, lt_eq, modulo, mul, or, sub},
+    bin_opr::{self, add, and, div, eq, gt, gt_eq, lt, lt_eq, modulo, mul, or, sub},
+    call_native, exit, format, gc_collect, gc_stats, input,
+    json::{json_parse, json_stringify},
     list::{list_create, list_get, list_insert, list_remove, list_set},
+    math::math,
     print,
     un_opr::{neg, not},
 };
-use object::{CvmList, CvmObject, Gc};
+pub use heap::GcStats;
+pub(crate) use heap::{GcRef, Heap};
+pub(crate) use object::CvmList;
+pub use object::CvmObject;
 
 use crate::common::Bytecode;
 use crate::error::unhandled_exception;
 use crate::utils::{PtrString, Stack};
 
+use ahash::AHashMap;
+
+use std::io::{self, BufRead, BufReader, Write};
 use std::rc::Rc;
 
 #[derive(Debug)]
 struct CvmFunctionObject {
     arg_count: usize,
-    code: Rc<Vec<Bytecode>>,
+    code: Rc<[Bytecode]>,
+}
+
+/// The signature of the host functions, callable from the scripts. The
+/// arguments are passed in the order of their declaration. Returning `Err()`
+/// raises an exception inside the script with the given message.
+pub type NativeFn = dyn Fn(&[CvmObject]) -> Result<CvmObject, String>;
+
+/// The signature of the host functions, which access the objects on the
+/// `CVM`'s heap (e.g. the `Random` generators).
+pub(crate) type HeapNativeFn = dyn Fn(&mut Heap, &[CvmObject]) -> Result<CvmObject, String>;
+
+struct CvmNativeFunction {
+    arg_count: usize,
+    is_void: bool,
+    func: Box<HeapNativeFn>,
 }
 
 #[derive(Debug, Default)]
@@ -38,17 +63,59 @@ struct CvmCallFrame {
     stack_len: usize,
     catch_idx: Option<usize>,
 
-    code: Rc<Vec<Bytecode>>,
+    code: Rc<[Bytecode]>,
 }
 
-#[derive(Default)]
+/// The default maximum number of nested function calls.
+pub const DEFAULT_RECURSION_LIMIT: usize = 10_000;
+/// The default maximum number of values on the stack.
+pub const DEFAULT_STACK_LIMIT: usize = 1_000_000;
+
 pub struct Cvm {
     stack: Stack<CvmObject>,
     globals: Vec<CvmObject>,
     functions: Vec<Rc<CvmFunctionObject>>,
+    natives: Vec<Rc<CvmNativeFunction>>,
     call_stack: Stack<CvmCallFrame>,
+    // The string constants of the executed programs, pushed via
+    // `Bytecode::LoadStr()`.
+    strings: Vec<PtrString>,
+    // The identical constants share a single entry, so reloading the same
+    // programs (e.g. through `Chalcedony::call()`) does not grow the pool.
+    string_ids: AHashMap<String, usize>,
 
     catch_idx: Option<usize>,
+    // The position of the current call frame's first local variable on the
+    // stack.
+    frame_base: usize,
+
+    // Exceeding the limits raises an exception, instead of exhausting the
+    // host's memory.
+    recursion_limit: usize,
+    stack_limit: usize,
+
+    // The objects, referenced by the values on the stack and the globals.
+    pub(crate) heap: Heap,
+
+    // Set once a script calls `exit()`, after which nothing is executed until
+    // it is cleared via `Cvm::reset_exit()`.
+    exit_code: Option<i32>,
+
+    // The message of the exception, which was not caught by the script. Just
+    // like `exit()` it stops the execution until `Cvm::reset_exit()`.
+    unhandled_exception: Option<String>,
+
+    // The streams used by the builtins `print()` and `input()`, and for
+    // reporting errors.
+    pub(crate) stdout: Box<dyn Write>,
+    pub(crate) stderr: Box<dyn Write>,
+    stdin: Box<dyn BufRead>,
+}
+
+impl Default for Cvm {
+    fn default() -> Self {
+        Cvm::new()
+    }
 }
 
 macro_rules! push_constant {
@@ -65,57 +132,226 @@ fn push_string(cvm: &mut Cvm, val: PtrString, next_idx: usize) -> usize {
 
 impl Cvm {
     pub fn new() -> Self {
+        Cvm::with_io(
+            Box::new(io::stdout()),
+            Box::new(io::stderr()),
+            Box::new(BufReader::new(io::stdin())),
+        )
+    }
+
+    /// Creates a virtual machine, which writes it's output and errors to the
+    /// passed sinks and reads the input of the scripts from `stdin`.
+    pub fn with_io(
+        stdout: Box<dyn Write>,
+        stderr: Box<dyn Write>,
+        stdin: Box<dyn BufRead>,
+    ) -> Self {
         Cvm {
             stack: Stack::<CvmObject>::with_capacity(100_000),
             globals: Vec::<CvmObject>::new(),
             functions: Vec::<Rc<CvmFunctionObject>>::new(),
+            natives: Vec::<Rc<CvmNativeFunction>>::new(),
             call_stack: Stack::<CvmCallFrame>::with_capacity(10_000),
+            strings: Vec::<PtrString>::new(),
+            string_ids: AHashMap::<String, usize>::new(),
             catch_idx: None,
+            frame_base: 0,
+            recursion_limit: DEFAULT_RECURSION_LIMIT,
+            stack_limit: DEFAULT_STACK_LIMIT,
+            heap: Heap::new(),
+            exit_code: None,
+            unhandled_exception: None,
+            stdout,
+            stderr,
+            stdin,
         }
     }
 
+    /// Sets the maximum number of nested function calls. A call exceeding it
+    /// raises the exception `maximum recursion depth exceeded`.
+    pub fn set_recursion_limit(&mut self, limit: usize) {
+        self.recursion_limit = limit;
+    }
+
+    /// Sets the maximum number of values on the stack. A function call
+    /// exceeding it raises the exception `maximum stack size exceeded`.
+    pub fn set_stack_limit(&mut self, limit: usize) {
+        self.stack_limit = limit;
+    }
+
+    /// Registers the host function and returns the id, used to call it via
+    /// `Bytecode::CallNative()`. The return value of void functions is ignored.
+    pub fn register_native(
+        &mut self,
+        arg_count: usize,
+        is_void: bool,
+        func: Box<NativeFn>,
+    ) -> usize {
+        self.register_heap_native(arg_count, is_void, Box::new(move |_, args| func(args)))
+    }
+
+    /// Registers the host function, which receives access to the heap, and
+    /// returns the id, used to call it via `Bytecode::CallNative()`.
+    pub(crate) fn register_heap_native(
+        &mut self,
+        arg_count: usize,
+        is_void: bool,
+        func: Box<HeapNativeFn>,
+    ) -> usize {
+        self.natives.push(Rc::new(CvmNativeFunction {
+            arg_count,
+            is_void,
+            func,
+        }));
+        self.natives.len() - 1
+    }
+
+    /// The exit code passed to `exit()`, if a script has called it.
+    pub fn exit_code(&self) -> Option<i32> {
+        self.exit_code
+    }
+
+    /// The message of the exception, which stopped the script since it was not
+    /// caught.
+    pub fn unhandled_exception(&self) -> Option<&str> {
+        self.unhandled_exception.as_deref()
+    }
+
+    /// Whether the script has stopped, either by calling `exit()` or by an
+    /// unhandled exception.
+    pub fn is_stopped(&self) -> bool {
+        self.exit_code.is_some() || self.unhandled_exception.is_some()
+    }
+
+    /// Allows the execution of code after a script has called `exit()` or has
+    /// raised an unhandled exception.
+    pub fn reset_exit(&mut self) {
+        self.exit_code = None;
+        self.unhandled_exception = None;
+    }
+
     pub fn execute(&mut self, code: Vec<Bytecode>) {
-        let mut current_idx = 0;
-        while !self.call_stack.is_empty() || current_idx < code.len() {
-            current_idx = self.execute_next(current_idx, &code);
+        if self.is_stopped() {
+            return;
         }
+        let code = self.load(code);
+        self.execute_internal(code);
         /* remove any leftover local variables inside the global scope */
         self.stack.truncate(0);
     }
 
-    fn execute_next(&mut self, current_idx: usize, code: &[Bytecode]) -> usize {
-        let next_instr: Bytecode;
-        if let Some(frame) = self.call_stack.peek() {
-            next_instr = frame
-                .code
-                .get(current_idx)
-                .expect("invalid current idx")
-                .clone();
-        } else {
-            next_instr = code.get(current_idx).expect("invalid current idx").clone();
+    /// Calls the function with the given id, returning it's result or the
+    /// message of the unhandled exception it raised. Void functions, and the
+    /// functions which call `exit()`, return `Ok(None)`.
+    pub fn call(
+        &mut self,
+        func_id: usize,
+        args: Vec<CvmObject>,
+    ) -> Result<Option<CvmObject>, String> {
+        if self.is_stopped() {
+            return Ok(None);
+        }
+        for arg in args {
+            self.stack.push(arg);
+        }
+
+        /* the call is guarded, so the exceptions are returned to the caller */
+        let code = vec![
+            Bytecode::TryScope(2),
+            Bytecode::CallFunc(func_id),
+            Bytecode::CatchJmp(1),
+            Bytecode::Nop,
+        ];
+        let code = self.load(code);
+        self.execute_internal(code);
+
+        let result = self.stack.pop();
+        self.stack.truncate(0);
+        match result {
+            Some(CvmObject::Exception(exc)) => Err(exc.to_string()),
+            result => Ok(result),
+        }
+    }
+
+    /* moves the string constants into the constant pool, so they are not
+     * copied along with the instructions */
+    fn load(&mut self, mut code: Vec<Bytecode>) -> Rc<[Bytecode]> {
+        for instr in code.iter_mut() {
+            let Bytecode::ConstS(val) = instr else {
+                continue;
+            };
+            let key = val.to_string();
+            let id = match self.string_ids.get(&key) {
+                Some(id) => *id,
+                None => {
+                    let id = self.strings.len();
+                    self.strings.push(val.clone());
+                    self.string_ids.insert(key, id);
+                    id
+                }
+            };
+            *instr = Bytecode::LoadStr(id);
+        }
+        code.into()
+    }
+
+    fn execute_internal(&mut self, code: Rc<[Bytecode]>) {
+        /* the current frame's code is cached and reloaded only when a function
+         * is called or returns (including the unwinding of exceptions and tail
+         * calls) */
+        let mut frame_code = code.clone();
+        let mut depth = self.call_stack.len();
+        self.frame_base = self.call_stack.peek().map_or(0, |frame| frame.stack_len);
+        let mut current_idx = 0;
+
+        loop {
+            let Some(instr) = frame_code.get(current_idx) else {
+                if depth == 0 {
+                    break;
+                }
+                panic!("invalid current idx");
+            };
+            let is_tail_call = matches!(instr, Bytecode::TailCall(_));
+            current_idx = self.execute_next(current_idx, instr, &frame_code);
+
+            if self.call_stack.len() != depth || is_tail_call {
+                depth = self.call_stack.len();
+                match self.call_stack.peek() {
+                    Some(frame) => {
+                        frame_code = frame.code.clone();
+                        self.frame_base = frame.stack_len;
+                    }
+                    None => {
+                        frame_code = code.clone();
+                        self.frame_base = 0;
+                    }
+                }
+            }
         }
+    }
+
+    fn execute_next(&mut self, current_idx: usize, instr: &Bytecode, code: &[Bytecode]) -> usize {
         let next_idx = current_idx + 1;
-        match next_instr {
+        match *instr {
             Bytecode::ConstI(val) => push_constant!(self, Int, val, next_idx),
             Bytecode::ConstU(val) => push_constant!(self, Uint, val, next_idx),
             Bytecode::ConstF(val) => push_constant!(self, Float, val, next_idx),
-            Bytecode::ConstS(val) => push_string(self, val.clone(), next_idx),
+            Bytecode::ConstS(ref val) => push_string(self, val.clone(), next_idx),
+            Bytecode::LoadStr(id) => push_string(self, self.strings[id].clone(), next_idx),
             Bytecode::ConstB(val) => push_constant!(self, Bool, val, next_idx),
 
             Bytecode::ConstL(len) => list_create(self, len, next_idx),
 
             Bytecode::ConstObj(member_count) => {
-                let mut list = Vec::<CvmObject>::with_capacity(member_count);
+                self.collect_if_needed();
+                let mut members = Vec::<CvmObject>::with_capacity(member_count);
                 for _ in 0..member_count {
-                    match self.stack.pop().unwrap() {
-                        CvmObject::Object(obj) => {
-                            list.push(CvmObject::Object(Gc::Strong(obj.get_ref().clone())))
-                        }
-                        val => list.push(val),
-                    }
+                    members.push(self.stack.pop().unwrap());
                 }
-                list.reverse();
-                self.stack.push(CvmObject::Object(Gc::new(list)));
+                members.reverse();
+
+                let obj = self.heap.alloc(members);
+                self.stack.push(CvmObject::Object(obj));
                 next_idx
             }
 
@@ -135,8 +371,10 @@ impl Cvm {
             }
 
             Bytecode::Copy => {
+                self.collect_if_needed();
                 let val = self.stack.pop().unwrap();
-                self.stack.push(val.deep_copy());
+                let val = self.heap.deep_copy(val);
+                self.stack.push(val);
                 next_idx
             }
 
@@ -149,6 +387,7 @@ impl Cvm {
                 match self.stack.pop().unwrap() {
                     CvmObject::Uint(val) => self.stack.push(CvmObject::Int(val as i64)),
                     CvmObject::Float(val) => self.stack.push(CvmObject::Int(val as i64)),
+                    val @ CvmObject::Int(_) => self.stack.push(val),
                     _ => panic!("invalid cast to Int"),
                 };
                 next_idx
@@ -158,6 +397,7 @@ impl Cvm {
                 match self.stack.pop().unwrap() {
                     CvmObject::Int(val) => self.stack.push(CvmObject::Float(val as f64)),
                     CvmObject::Uint(val) => self.stack.push(CvmObject::Float(val as f64)),
+                    val @ CvmObject::Float(_) => self.stack.push(val),
                     _ => panic!("invalid cast to Float"),
                 }
                 next_idx
@@ -191,9 +431,7 @@ impl Cvm {
             }
 
             Bytecode::SetLocal(mut var_id) => {
-                if let Some(frame) = self.call_stack.peek() {
-                    var_id += frame.stack_len;
-                }
+                var_id += self.frame_base;
 
                 let value = self.stack.pop().unwrap();
                 if let Some(var) = self.stack.get_mut(var_id) {
@@ -207,9 +445,7 @@ impl Cvm {
                 next_idx
             }
             Bytecode::GetLocal(mut var_id) => {
-                if let Some(frame) = self.call_stack.peek() {
-                    var_id += frame.stack_len;
-                }
+                var_id += self.frame_base;
                 let value = self.stack.get(var_id).unwrap().clone();
                 self.stack.push(value);
                 next_idx
@@ -222,44 +458,20 @@ impl Cvm {
                     panic!("calling `GetAttr` on a non-object")
                 };
 
-                let obj = obj.get_ref();
-                self.stack
-                    .push(obj.borrow().data.get(attr_id).unwrap().clone());
-
+                let val = self.heap.get(obj).get(attr_id).unwrap().clone();
+                self.stack.push(val);
                 next_idx
             }
 
             Bytecode::SetAttr(attr_id) => {
                 let val = self.stack.pop().expect("expected a value on the stack");
                 let CvmObject::Object(dest_obj) =
-                    self.stack.top().expect("expected a value on the stack")
+                    self.stack.peek().expect("expected a value on the stack")
                 else {
                     panic!("calling `SetAttr` on a non-object");
                 };
 
-                let dest_obj = dest_obj.get_ref();
-                let mut dest_obj = dest_obj.borrow_mut();
-
-                /* dest_obj = val_obj */
-                if let CvmObject::Object(val_obj) = val {
-                    let val_obj = val_obj.get_ref();
-
-                    // the referenced object has the same or a greater lifetime
-                    if dest_obj.depth <= val_obj.borrow().depth {
-                        *dest_obj.data.get_mut(attr_id).unwrap() =
-                            CvmObject::Object(Gc::Weak(Rc::downgrade(&val_obj)));
-
-                    // the referenced object's lifetime must be extended via
-                    // strong link
-                    } else {
-                        *dest_obj.data.get_mut(attr_id).unwrap() =
-                            CvmObject::Object(Gc::Strong(val_obj.clone()));
-                        dest_obj.depth = val_obj.borrow().depth;
-                    }
-                } else {
-                    *dest_obj.data.get_mut(attr_id).unwrap() = val;
-                }
-
+                *self.heap.get_mut(*dest_obj).get_mut(attr_id).unwrap() = val;
                 next_idx
             }
 
@@ -278,13 +490,49 @@ impl Cvm {
             Bytecode::GtEq => gt_eq(self, next_idx),
 
             Bytecode::Eq => eq(self, next_idx),
+
+            Bytecode::AddI => bin_opr::add_i(self, next_idx),
+            Bytecode::SubI => bin_opr::sub_i(self, next_idx),
+            Bytecode::MulI => bin_opr::mul_i(self, next_idx),
+            Bytecode::DivI => bin_opr::div_i(self, next_idx),
+            Bytecode::ModI => bin_opr::mod_i(self, next_idx),
+            Bytecode::LtI => bin_opr::lt_i(self, next_idx),
+            Bytecode::GtI => bin_opr::gt_i(self, next_idx),
+            Bytecode::EqI => bin_opr::eq_i(self, next_idx),
+            Bytecode::LtEqI => bin_opr::lt_eq_i(self, next_idx),
+            Bytecode::GtEqI => bin_opr::gt_eq_i(self, next_idx),
+
+            Bytecode::AddU => bin_opr::add_u(self, next_idx),
+            Bytecode::SubU => bin_opr::sub_u(self, next_idx),
+            Bytecode::MulU => bin_opr::mul_u(self, next_idx),
+            Bytecode::DivU => bin_opr::div_u(self, next_idx),
+            Bytecode::ModU => bin_opr::mod_u(self, next_idx),
+            Bytecode::LtU => bin_opr::lt_u(self, next_idx),
+            Bytecode::GtU => bin_opr::gt_u(self, next_idx),
+            Bytecode::EqU => bin_opr::eq_u(self, next_idx),
+            Bytecode::LtEqU => bin_opr::lt_eq_u(self, next_idx),
+            Bytecode::GtEqU => bin_opr::gt_eq_u(self, next_idx),
+
+            Bytecode::AddF => bin_opr::add_f(self, next_idx),
+            Bytecode::SubF => bin_opr::sub_f(self, next_idx),
+            Bytecode::MulF => bin_opr::mul_f(self, next_idx),
+            Bytecode::DivF => bin_opr::div_f(self, next_idx),
+            Bytecode::ModF => bin_opr::mod_f(self, next_idx),
+            Bytecode::LtF => bin_opr::lt_f(self, next_idx),
+            Bytecode::GtF => bin_opr::gt_f(self, next_idx),
+            Bytecode::EqF => bin_opr::eq_f(self, next_idx),
+            Bytecode::LtEqF => bin_opr::lt_eq_f(self, next_idx),
+            Bytecode::GtEqF => bin_opr::gt_eq_f(self, next_idx),
+
+            Bytecode::ConcatS => bin_opr::concat_s(self, next_idx),
+
             Bytecode::Neg => neg(self, next_idx),
             Bytecode::Not => not(self, next_idx),
 
             Bytecode::CreateFunc(arg_count) => {
                 let func_obj = CvmFunctionObject {
                     arg_count,
-                    code: Rc::new(code[next_idx..].into()),
+                    code: code[next_idx..].into(),
                 };
                 self.functions.push(Rc::new(func_obj));
                 code.len()
@@ -294,8 +542,16 @@ impl Cvm {
                 let func_obj = self
                     .functions
                     .get(func_id)
-                    .expect("expected a valid function id")
-                    .clone();
+                    .expect("expected a valid function id");
+
+                if self.call_stack.len() >= self.recursion_limit {
+                    let arg_count = func_obj.arg_count;
+                    return self.throw_call_overflow(arg_count, "maximum recursion depth exceeded");
+                }
+                if self.stack.len() > self.stack_limit {
+                    let arg_count = func_obj.arg_count;
+                    return self.throw_call_overflow(arg_count, "maximum stack size exceeded");
+                }
 
                 // NOTE: the arguments to the function call are already in place
                 // and local variables are automatically handled
@@ -312,6 +568,29 @@ impl Cvm {
                 0
             }
 
+            Bytecode::TailCall(func_id) => {
+                let func_obj = self
+                    .functions
+                    .get(func_id)
+                    .expect("expected a valid function id");
+                let frame = self
+                    .call_stack
+                    .top()
+                    .expect("expected a call frame for the tail call");
+
+                /* the arguments replace the current function's variables */
+                let args_start = self.stack.len() - func_obj.arg_count;
+                self.stack.remove_range(frame.stack_len..args_start);
+                frame.code = func_obj.code.clone();
+
+                0
+            }
+
+            Bytecode::CallNative(native_id) => {
+                self.collect_if_needed();
+                call_native(self, native_id, next_idx)
+            }
+
             Bytecode::Return => {
                 let value = self.stack.pop().unwrap();
                 let frame = self.call_stack.pop().unwrap();
@@ -372,12 +651,54 @@ impl Cvm {
             }
 
             Bytecode::Print => print(self, next_idx),
+            Bytecode::Input => input(self, next_idx),
+            Bytecode::Math(func) => math(self, func, next_idx),
+            Bytecode::Format(ref segments) => format(self, segments, next_idx),
+            Bytecode::JsonParse(ref schema) => {
+                self.collect_if_needed();
+                json_parse(self, schema, next_idx)
+            }
+            Bytecode::JsonStringify(ref schema) => json_stringify(self, schema, next_idx),
             Bytecode::Assert => assert(self, next_idx),
+            Bytecode::Exit => exit(self),
+
+            Bytecode::GcCollect => gc_collect(self, next_idx),
+            Bytecode::GcStats => gc_stats(self, next_idx),
             Bytecode::Nop => next_idx,
         }
     }
 
-    /* returns the index of the next instruction */
+    /// Frees the objects, which are no longer reachable from the stack or the
+    /// globals, and returns their number.
+    pub fn collect_garbage(&mut self) -> usize {
+        /* the local variables of every call frame are on the stack */
+        let roots = self.stack.iter().chain(self.globals.iter());
+        self.heap.collect(roots)
+    }
+
+    pub fn gc_stats(&self) -> GcStats {
+        self.heap.stats()
+    }
+
+    /* runs the garbage collector before an instruction, which allocates
+     * objects, if enough objects were allocated since the last collection */
+    fn collect_if_needed(&mut self) {
+        if self.heap.should_collect() {
+            self.collect_garbage();
+        }
+    }
+
+    /* raises the exception in place of the function call, whose arguments
+     * are discarded */
+    fn throw_call_overflow(&mut self, arg_count: usize, msg: &str) -> usize {
+        self.stack.truncate(self.stack.len() - arg_count);
+        self.stack
+            .push(CvmObject::Exception(msg.to_string().into()));
+        self.handle_exception()
+    }
+
+    /* returns the index of the next instruction, or an index past the end of
+     * any code if the exception is not caught, which stops the execution */
     fn handle_exception(&mut self) -> usize {
         if let Some(catch_idx) = self.catch_idx {
             self.catch_idx = None;
@@ -398,7 +719,19 @@ impl Cvm {
             panic!("invalid exception");
         };
 
-        unhandled_exception(format!("{}", exc));
-        unreachable!();
+        let exc = exc.to_string();
+        unhandled_exception(&exc, &mut self.stderr);
+        self.unhandled_exception = Some(exc);
+        self.stack.truncate(0);
+        usize::MAX
+    }
+}
+
+/// Wrapper functions, used for tests.
+#[cfg(feature = "testing")]
+impl Cvm {
+    // Used for tests to check that the identical string constants are interned.
+    pub fn string_pool_len(&self) -> usize {
+        self.strings.len()
     }
 }
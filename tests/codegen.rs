@@ -1,16 +1,18 @@
+mod common;
+
 use chalcedony::common::operators::{AssignOprType, BinOprType};
 use chalcedony::common::{Bytecode, Type};
 
 use chalcedony::parser::ast::{
-    class::Member, func::Arg, NodeAssign, NodeAttrRes, NodeAttribute, NodeBreakStmnt, NodeClass,
-    NodeContStmnt, NodeElifStmnt, NodeElseStmnt, NodeExpr, NodeExprInner, NodeFuncCall,
-    NodeFuncCallStmnt, NodeFuncDef, NodeIfBranch, NodeIfStmnt, NodeInlineClass, NodeList,
-    NodeRetStmnt, NodeStmnt, NodeThrow, NodeTryCatch, NodeValue, NodeVarCall, NodeVarDef,
+    class::Member, func::Arg, ImportKind, NodeAssign, NodeAttrRes, NodeAttribute, NodeBreakStmnt,
+    NodeClass, NodeContStmnt, NodeElifStmnt, NodeElseStmnt, NodeExpr, NodeExprInner, NodeFuncCall,
+    NodeFuncCallStmnt, NodeFuncDef, NodeIfBranch, NodeIfStmnt, NodeImport, NodeInlineClass,
+    NodeList, NodeRetStmnt, NodeStmnt, NodeThrow, NodeTryCatch, NodeValue, NodeVarCall, NodeVarDef,
     NodeWhileLoop,
 };
 
 use chalcedony::interpreter::{
-    ArgAnnotation, Chalcedony, ClassNamespace, FuncAnnotation, MemberAnnotation, ToBytecode,
+    ArgAnnotation, Chalcedony, ClassNamespace, FuncAnnotation, MemberAnnotation, ToBytecode, Value,
 };
 
 use chalcedony::mocks::{hash_map, vecdeq, SpanMock};
@@ -54,6 +56,7 @@ fn compile_control_flow() {
                     span: SpanMock::new(),
                 },
                 is_const: false,
+                is_mut: false,
                 span: SpanMock::new(),
             }),
             // while i < 100:
@@ -141,6 +144,7 @@ fn compile_control_flow() {
                             span: SpanMock::new(),
                         },
                         is_const: false,
+                        is_mut: false,
                         span: SpanMock::new(),
                     }),
                     // while j < 10:
@@ -216,17 +220,17 @@ fn compile_control_flow() {
         // while i < 100:
         Bytecode::GetLocal(0),
         Bytecode::ConstU(100),
-        Bytecode::Lt,
+        Bytecode::LtU,
         Bytecode::If(38),
         // i += 1
         Bytecode::GetLocal(0),
         Bytecode::ConstU(1),
-        Bytecode::Add,
+        Bytecode::AddU,
         Bytecode::SetLocal(0),
         // if i == 42:
         Bytecode::GetLocal(0),
         Bytecode::ConstU(42),
-        Bytecode::Eq,
+        Bytecode::EqU,
         Bytecode::If(2),
         // break
         Bytecode::Jmp(29),
@@ -234,9 +238,9 @@ fn compile_control_flow() {
         // elif i % 2 == 0:
         Bytecode::GetLocal(0),
         Bytecode::ConstU(2),
-        Bytecode::Mod,
+        Bytecode::ModU,
         Bytecode::ConstU(0),
-        Bytecode::Eq,
+        Bytecode::EqU,
         Bytecode::If(2),
         // continue
         Bytecode::Jmp(-21),
@@ -247,19 +251,19 @@ fn compile_control_flow() {
         // while j < 10:
         Bytecode::GetLocal(1),
         Bytecode::ConstU(10),
-        Bytecode::Lt,
+        Bytecode::LtU,
         Bytecode::If(13),
         // j += 1
         Bytecode::GetLocal(1),
         Bytecode::ConstU(1),
-        Bytecode::Add,
+        Bytecode::AddU,
         Bytecode::SetLocal(1),
         // if j % 2 == 1
         Bytecode::GetLocal(1),
         Bytecode::ConstU(2),
-        Bytecode::Mod,
+        Bytecode::ModU,
         Bytecode::ConstU(1),
-        Bytecode::Eq,
+        Bytecode::EqU,
         Bytecode::If(2),
         // continue
         Bytecode::Jmp(-15),
@@ -360,7 +364,7 @@ fn compile_if_branching() {
         // if 2 > 3:
         Bytecode::ConstU(2),
         Bytecode::ConstU(3),
-        Bytecode::Gt,
+        Bytecode::GtU,
         Bytecode::If(3),
         // print("one")
         Bytecode::ConstS("one".to_string().into()),
@@ -369,7 +373,7 @@ fn compile_if_branching() {
         // elif 3 > 4:
         Bytecode::ConstU(3),
         Bytecode::ConstU(4),
-        Bytecode::Gt,
+        Bytecode::GtU,
         Bytecode::If(3),
         // print("two")
         Bytecode::ConstS("two".to_string().into()),
@@ -404,6 +408,7 @@ fn compile_function() {
         args: vecdeq![Arg {
             name: "n".to_string(),
             ty: Type::Int,
+            span: SpanMock::new(),
         }],
         ret_type: Type::Uint,
         namespace: None,
@@ -504,18 +509,21 @@ fn compile_function() {
         // if n > 2:
         Bytecode::GetLocal(0),
         Bytecode::ConstU(2),
-        Bytecode::Gt,
-        Bytecode::If(11),
+        Bytecode::CastI,
+        Bytecode::GtI,
+        Bytecode::If(13),
         // return fib(n-2) + fib(n-1)
         Bytecode::GetLocal(0),
         Bytecode::ConstU(2),
-        Bytecode::Sub,
+        Bytecode::CastI,
+        Bytecode::SubI,
         Bytecode::CallFunc(fib_id),
         Bytecode::GetLocal(0),
         Bytecode::ConstU(1),
-        Bytecode::Sub,
+        Bytecode::CastI,
+        Bytecode::SubI,
         Bytecode::CallFunc(fib_id),
-        Bytecode::Add,
+        Bytecode::AddU,
         Bytecode::Return,
         Bytecode::Nop,
         // return 1
@@ -599,11 +607,9 @@ fn compile_try_catch() {
 
     let exp = vec![
         // try:
-        Bytecode::TryScope(7),
-        // print(21 * 2)
-        Bytecode::ConstU(21),
-        Bytecode::ConstU(2),
-        Bytecode::Mul,
+        Bytecode::TryScope(5),
+        // print(21 * 2), folded at compile time
+        Bytecode::ConstU(42),
         Bytecode::Print,
         // throw "unexpected error"
         Bytecode::ConstS("unexpected error".to_string().into()),
@@ -614,7 +620,7 @@ fn compile_try_catch() {
         Bytecode::SetLocal(0),
         Bytecode::ConstS("Received the exception: ".to_string().into()),
         Bytecode::GetLocal(0),
-        Bytecode::Add,
+        Bytecode::ConcatS,
         Bytecode::Print,
     ];
 
@@ -671,16 +677,11 @@ fn compile_list() {
         .to_bytecode(&mut Chalcedony::new())
         .expect("could not compile list");
 
+    /* the constant elements are folded */
     let exp = vec![
         Bytecode::ConstU(1),
-        Bytecode::ConstU(2),
-        Bytecode::ConstU(3),
-        Bytecode::Mul,
-        Bytecode::ConstU(4),
-        Bytecode::ConstU(10),
-        Bytecode::Add,
-        Bytecode::ConstU(2),
-        Bytecode::Div,
+        Bytecode::ConstU(6),
+        Bytecode::ConstU(7),
         Bytecode::ConstL(3),
         Bytecode::ConstU(5),
         Bytecode::Mul,
@@ -715,7 +716,8 @@ fn compile_classes() {
             namespace: Some("Example".to_string()),
             args: vecdeq![Arg {
                 name: "value".to_string(),
-                ty: Type::Uint
+                ty: Type::Uint,
+                span: SpanMock::new(),
             }],
             body: vec![NodeStmnt::RetStmnt(NodeRetStmnt {
                 value: NodeExpr {
@@ -828,3 +830,168 @@ fn compile_classes() {
         .expect("could not compile NodeClass");
     assert_eq!(exp_print_bytecode, print_bytecode)
 }
+
+#[test]
+fn compile_imports() {
+    // equivalent to the scripts:
+    // ```
+    // # geometry.ch
+    // fn double(x: int) -> int:
+    //     return x * 2
+    //
+    // # main
+    // fn double(x: int) -> int:
+    //     return x + x
+    //
+    // import "geometry" as geo
+    // import "geometry.ch"
+    // ```
+    let script_dir = common::TempDir::new("imports");
+    let script_path = script_dir.write(
+        "geometry.ch",
+        "fn double(x: int) -> int:\n    return x * 2\n",
+    );
+
+    let mut interpreter = Chalcedony::new();
+    interpreter.interpret("fn double(x: int) -> int:\n    return x + x\n");
+
+    let namespaced_import = NodeImport {
+        path: script_dir
+            .path()
+            .join("geometry")
+            .to_str()
+            .unwrap()
+            .to_string(),
+        kind: ImportKind::Namespaced("geo".to_string()),
+        span: SpanMock::new(),
+    };
+    let global_import = NodeImport {
+        path: script_path.to_str().unwrap().to_string(),
+        kind: ImportKind::Global,
+        span: SpanMock::new(),
+    };
+
+    /* the id of `geo::double()` */
+    let geo_double_id = interpreter.get_next_func_id();
+
+    let exp_geo_namespace = ClassNamespace {
+        members: vec![],
+        methods: hash_map!(
+            "double".to_string() => vec![Rc::new(FuncAnnotation::new(
+                    geo_double_id,
+                    vec![ArgAnnotation::new(0, "x".to_string(), Type::Int)],
+                    Type::Int,
+                    false
+                    ))],
+        ),
+    };
+
+    let namespaced_bytecode = namespaced_import
+        .to_bytecode(&mut interpreter)
+        .expect("could not compile NodeImport");
+    assert_eq!(Vec::<Bytecode>::new(), namespaced_bytecode);
+    assert_eq!(Some(&exp_geo_namespace), interpreter.get_namespace("geo"));
+
+    /* the imported `double()` collides with the one in the main script */
+    assert!(global_import.to_bytecode(&mut interpreter).is_err());
+}
+
+#[test]
+fn compile_selective_imports() {
+    // equivalent to the scripts:
+    // ```
+    // # utils.ch
+    // const SCALE = 3
+    //
+    // class Point:
+    //     x: int
+    //
+    // fn scale(x: int) -> int:
+    //     return x * SCALE
+    //
+    // fn hidden() -> int:
+    //     return 0
+    //
+    // # main
+    // from "utils.ch" import scale, SCALE, Point
+    // from "utils.ch" import missing
+    // ```
+    let script_dir = common::TempDir::new("selective");
+    let script_path = script_dir.write(
+        "utils.ch",
+        "const SCALE = 3\n\nclass Point:\n    x: int\n\n\
+         fn scale(x: int) -> int:\n    return x * SCALE\n\n\
+         fn hidden() -> int:\n    return 0\n",
+    );
+    let path = script_path.to_str().unwrap().to_string();
+
+    let items = |names: &[&str]| {
+        names
+            .iter()
+            .map(|name| NodeVarCall {
+                name: name.to_string(),
+                span: SpanMock::new(),
+            })
+            .collect()
+    };
+    let selective_import = NodeImport {
+        path: path.clone(),
+        kind: ImportKind::Selective(items(&["scale", "SCALE", "Point"])),
+        span: SpanMock::new(),
+    };
+    let missing_import = NodeImport {
+        path,
+        kind: ImportKind::Selective(items(&["missing"])),
+        span: SpanMock::new(),
+    };
+
+    let mut interpreter = Chalcedony::new();
+    let bytecode = selective_import
+        .to_bytecode(&mut interpreter)
+        .expect("could not compile NodeImport");
+    assert_eq!(Vec::<Bytecode>::new(), bytecode);
+
+    /* only the listed definitions are in scope */
+    assert_eq!(
+        Ok(Value::Int(6)),
+        interpreter.call("scale", &[Value::Int(2)])
+    );
+    assert!(interpreter.call("hidden", &[]).is_err());
+    assert!(interpreter.get_namespace("Point").is_some());
+
+    assert!(missing_import.to_bytecode(&mut interpreter).is_err());
+}
+
+#[test]
+fn compile_std_imports() {
+    // equivalent to the code:
+    // ```
+    // import std::math as math
+    // import std::unknown
+    // ```
+    let mut interpreter = Chalcedony::new();
+
+    let math_import = NodeImport {
+        path: "std::math".to_string(),
+        kind: ImportKind::Namespaced("math".to_string()),
+        span: SpanMock::new(),
+    };
+    let unknown_import = NodeImport {
+        path: "std::unknown".to_string(),
+        kind: ImportKind::Global,
+        span: SpanMock::new(),
+    };
+
+    let math_bytecode = math_import
+        .to_bytecode(&mut interpreter)
+        .expect("could not compile NodeImport");
+    assert_eq!(Vec::<Bytecode>::new(), math_bytecode);
+
+    let math_namespace = interpreter
+        .get_namespace("math")
+        .expect("did not create the `math` namespace");
+    assert!(math_namespace.methods.contains_key("gcd"));
+    assert!(math_namespace.methods.contains_key("is_prime"));
+
+    assert!(unknown_import.to_bytecode(&mut interpreter).is_err());
+}
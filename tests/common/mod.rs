@@ -0,0 +1,59 @@
+//! The fixtures, shared between the integration tests. Each test crate uses
+//! only some of them.
+#![allow(dead_code)]
+
+use chalcedony::interpreter::Chalcedony;
+use chalcedony::mocks::SharedBuffer;
+
+use std::fs;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Creates an interpreter with an empty input, whose output and errors are
+/// captured in the returned buffers.
+pub fn interpreter() -> (Chalcedony, SharedBuffer, SharedBuffer) {
+    interpreter_with_input("")
+}
+
+pub fn interpreter_with_input(stdin: &'static str) -> (Chalcedony, SharedBuffer, SharedBuffer) {
+    let stdout = SharedBuffer::new();
+    let stderr = SharedBuffer::new();
+    let interpreter = Chalcedony::with_io(stdout.clone(), stderr.clone(), Cursor::new(stdin));
+    (interpreter, stdout, stderr)
+}
+
+/// A temporary directory, unique to a single test, which is removed with all
+/// of it's contents once it is dropped.
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    pub fn new(name: &str) -> Self {
+        /* the tests inside a crate run in parallel in the same process */
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let id = COUNTER.fetch_add(1, Ordering::Relaxed);
+
+        let path = std::env::temp_dir().join(format!("chal-{}-{}-{}", name, process::id(), id));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).expect("could not create the test directory");
+        TempDir(path)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+
+    /// Writes the file inside the directory and returns it's path.
+    pub fn write(&self, name: &str, contents: &str) -> PathBuf {
+        let path = self.0.join(name);
+        fs::write(&path, contents).expect("could not write the test file");
+        path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
@@ -1,6 +1,12 @@
-use chalcedony::common::Bytecode;
-use chalcedony::interpreter::Chalcedony;
-use chalcedony::vm::Cvm;
+mod common;
+
+use chalcedony::common::{Bytecode, Type};
+use chalcedony::error::ScriptError;
+use chalcedony::interpreter::{Chalcedony, Value};
+use chalcedony::vm::{Cvm, CvmObject};
+
+use std::cell::Cell;
+use std::rc::Rc;
 
 #[test]
 fn valid_features_set() {
@@ -117,7 +123,29 @@ fn interpret_guarded_exception() {
 }
 
 #[test]
-#[should_panic]
+fn interpret_interned_strings() {
+    let mut vm = Cvm::new();
+    let code = || {
+        vec![
+            Bytecode::ConstS("pooled".to_string().into()),
+            Bytecode::ConstS("pooled".to_string().into()),
+            Bytecode::Eq,
+            Bytecode::Assert,
+            Bytecode::ConstS("other".to_string().into()),
+            Bytecode::ConstS("other".to_string().into()),
+            Bytecode::Eq,
+            Bytecode::Assert,
+        ]
+    };
+
+    /* loading the same program again reuses the pooled constants */
+    for _ in 0..3 {
+        vm.execute(code());
+    }
+    assert_eq!(2, vm.string_pool_len());
+}
+
+#[test]
 fn interpret_unhandled_exception() {
     let mut vm = Cvm::new();
 
@@ -135,6 +163,12 @@ fn interpret_unhandled_exception() {
         Bytecode::Print,
     ];
     vm.execute(code);
+    assert_eq!(Some("unexpected error"), vm.unhandled_exception());
+    assert!(vm.is_stopped());
+
+    vm.reset_exit();
+    assert!(!vm.is_stopped());
+    vm.execute(vec![Bytecode::ConstB(true), Bytecode::Assert]);
 }
 
 #[test]
@@ -219,3 +253,1073 @@ fn interpret_instance_creation_and_access() {
     ];
     vm.execute(code);
 }
+
+#[test]
+fn interpret_native_functions() {
+    let mut interpreter = Chalcedony::new();
+    let calls = Rc::new(Cell::new(0));
+
+    let double_calls = calls.clone();
+    interpreter
+        .register_fn(None, "double", vec![Type::Int], Type::Int, move |args| {
+            double_calls.set(double_calls.get() + 1);
+            let CvmObject::Int(val) = args[0] else {
+                return Err("expected an int".to_string());
+            };
+            Ok(CvmObject::Int(val * 2))
+        })
+        .expect("could not register `double()`");
+
+    /* the same overload could not be registered twice */
+    assert_eq!(
+        Err(ScriptError::FunctionAlreadyExists("double".to_string())),
+        interpreter.register_fn(None, "double", vec![Type::Int], Type::Int, |args| {
+            Ok(args[0].clone())
+        })
+    );
+
+    let fail_calls = calls.clone();
+    interpreter
+        .register_fn(
+            Some("host"),
+            "fail!",
+            vec![Type::Str],
+            Type::Void,
+            move |args| {
+                fail_calls.set(fail_calls.get() + 1);
+                Err(format!("host failure: {}", args[0]))
+            },
+        )
+        .expect("could not register `host::fail!()`");
+
+    let code = r#"
+assert(double(21) == 42)
+let caught = false
+try:
+    host::fail!("oops")
+catch (exc: exception):
+    assert("" + exc == "host failure: oops")
+    caught = true
+assert(caught)
+"#;
+    interpreter.interpret(code);
+    assert_eq!(2, calls.get());
+}
+
+#[test]
+fn interpret_host_calls() {
+    let mut interpreter = Chalcedony::new();
+    let code = r#"
+class Point:
+    x: int
+    y: int
+
+fn scale(p: Point, factor: int) -> Point:
+    return Point { x: p.x * factor, y: p.y * factor }
+
+fn total(list: [float]) -> float:
+    let result = 0.0
+    for el in list:
+        result += el
+    return result
+
+fn total(a: int, b: int) -> int:
+    return a + b
+
+fn fail!(msg: str) -> int:
+    throw msg
+    return 0
+"#;
+    interpreter.interpret(code);
+
+    let list = Value::from(vec![1.5, 2.5]);
+    let res = interpreter
+        .call("total", &[list])
+        .expect("could not call `total`");
+    assert_eq!(Ok(4.0), f64::try_from(res));
+
+    /* the implicit cast from `uint` to `int` */
+    let res = interpreter
+        .call("total", &[Value::Uint(40), Value::Int(2)])
+        .expect("could not call `total`");
+    assert_eq!(Value::Int(42), res);
+    assert_eq!(
+        Err(ScriptError::InvalidValue(Type::Int, Type::Uint)),
+        interpreter.call("total", &[Value::Uint(u64::MAX), Value::Int(2)])
+    );
+
+    let point = Value::Object {
+        class: "Point".to_string(),
+        fields: [
+            ("x".to_string(), Value::Int(1)),
+            ("y".to_string(), Value::Int(-2)),
+        ]
+        .into_iter()
+        .collect(),
+    };
+    let res = interpreter
+        .call("scale", &[point, Value::Int(3)])
+        .expect("could not call `scale`");
+    let Value::Object { fields, .. } = res else {
+        panic!("expected an object");
+    };
+    assert_eq!(Some(&Value::Int(3)), fields.get("x"));
+    assert_eq!(Some(&Value::Int(-6)), fields.get("y"));
+
+    assert_eq!(
+        Err(ScriptError::Exception("oops".to_string())),
+        interpreter.call("fail!", &["oops".into()])
+    );
+    assert_eq!(
+        Err(ScriptError::UnknownFunction("total".to_string())),
+        interpreter.call("total", &[Value::Bool(true)])
+    );
+    assert_eq!(
+        Err(ScriptError::InvalidValue(Type::Str, Type::Int)),
+        String::try_from(Value::Int(3))
+    );
+
+    /* the definitions after an error are never executed, so they are unknown */
+    let (mut interpreter, _, stderr) = common::interpreter();
+    interpreter.interpret("let bad: int = \"str\"\nfn double(x: int) -> int:\n    return x * 2\n");
+    assert!(interpreter.has_failed());
+    assert_eq!(
+        Err(ScriptError::UnknownFunction("double".to_string())),
+        interpreter.call("double", &[Value::Int(2)])
+    );
+    interpreter.interpret("print(double(2))\n");
+    assert!(stderr.contents().contains("unknown function"));
+}
+
+#[test]
+fn interpret_custom_io() {
+    let (mut interpreter, stdout, stderr) = common::interpreter_with_input("Alice\nBob\r\n");
+
+    let code = r#"
+let name = input("name: ")
+print("hello " + name)
+print(input())
+print(len(input()))
+"#;
+    interpreter.interpret(code);
+    assert_eq!("name: hello Alice\nBob\n0\n", stdout.contents());
+    assert_eq!("", stderr.contents());
+
+    interpreter.interpret("print(unknown_var)\n");
+    assert!(stderr.contents().contains("unknown variable"));
+}
+
+#[test]
+fn interpret_imports() {
+    let dir = common::TempDir::new("imports");
+    std::fs::create_dir(dir.path().join("lib")).unwrap();
+    dir.write(
+        "lib/geo.ch",
+        "const SIDES = 4\n\nclass Point:\n    x: int\n\n\
+         fn area(w: int) -> int:\n    return w * SIDES\n",
+    );
+    let main = dir.write(
+        "main.ch",
+        r#"
+import "geo" as geo
+import std::math as m
+print(geo::area(3) + geo::SIDES)
+print(m::pi == PI)
+let p = Point{x: 2}
+print(p.x)
+"#,
+    );
+
+    /* `geo` is found only through the search path */
+    std::env::set_var("CHAL_PATH", dir.path().join("lib"));
+    let (mut interpreter, stdout, stderr) = common::interpreter();
+    interpreter.interpret_script(main.to_str().unwrap().to_string());
+    std::env::remove_var("CHAL_PATH");
+    assert_eq!("", stderr.contents());
+    assert_eq!("16\ntrue\n2\n", stdout.contents());
+
+    /* the imported classes are checked for collisions at the import */
+    let (mut interpreter, _, stderr) = common::interpreter();
+    interpreter.interpret("class Range:\n    lo: int\n\nimport std::collections\n");
+    assert!(interpreter.has_failed());
+    assert!(stderr
+        .contents()
+        .contains("the imported `Range` collides with an existing definition"));
+
+    /* the error lists every script in the cycle */
+    let first = dir.write("first.ch", "import \"second.ch\"\n");
+    dir.write("second.ch", "import \"first.ch\"\n");
+    let (mut interpreter, _, stderr) = common::interpreter();
+    interpreter.interpret_script(first.to_str().unwrap().to_string());
+    let errors = stderr.contents();
+    let chain = errors
+        .lines()
+        .find_map(|line| line.split_once("import cycle detected: "))
+        .map(|(_, chain)| chain.to_string())
+        .unwrap_or_else(|| panic!("missing the import cycle in:\n{}", errors));
+    let scripts: Vec<&str> = chain
+        .split(" -> ")
+        .map(|path| path.rsplit('/').next().unwrap())
+        .collect();
+    assert_eq!(vec!["first.ch", "second.ch", "first.ch"], scripts);
+}
+
+#[test]
+fn interpret_std_strings() {
+    let (mut interpreter, stdout, stderr) = common::interpreter();
+
+    let code = r#"
+import std::strings
+print(pad_left("7", 3, "0") + "|" + pad_right("ab", 4, ".") + "|")
+print(pad_left("x", 5, "") + pad_right("y", 5, ""))
+"#;
+    interpreter.interpret(code);
+    assert_eq!("", stderr.contents());
+    assert_eq!("007|ab..|\nxy\n", stdout.contents());
+}
+
+#[test]
+fn interpret_file_system() {
+    let temp_dir = common::TempDir::new("fs");
+    let dir = temp_dir.path().to_str().unwrap().to_string();
+
+    let (mut interpreter, stdout, _) = common::interpreter();
+
+    let code = format!(
+        r#"
+let path = "{dir}/data.txt"
+write_file!(path, "first")
+append_file!(path, "-second")
+assert(read_file!(path) == "first-second")
+assert(exists(path))
+assert(!exists("{dir}/missing.txt"))
+
+let file = File::open!(path, "a")
+file.write!("-third")
+file.close()
+
+file = File::open!(path, "r")
+print(file.read_line!())
+file.close()
+try:
+    print(file.read_line!())
+catch (exc: exception):
+    print(exc)
+
+# the reopened file takes the closed one's slot, but not it's handle
+let other = File::open!(path, "r")
+try:
+    print(file.read_line!())
+catch (exc: exception):
+    print(exc)
+print(other.read_line!())
+other.close()
+
+for entry in list_dir!("{dir}"):
+    print(entry)
+
+import std::fs as fs
+import std::sys
+from std::fs import exists
+print(exists(path) && fs::read!(path) == read_file!(path))
+"#
+    );
+    interpreter.interpret(&code);
+    assert_eq!(
+        "first-second-third\nthe file is closed\nthe file is closed\nfirst-second-third\n\
+         data.txt\ntrue\n",
+        stdout.contents()
+    );
+
+    /* the failed writes are not deferred until the file is closed */
+    if cfg!(target_os = "linux") {
+        let (mut interpreter, stdout, _) = common::interpreter();
+        let code = r#"
+let file = File::open!("/dev/full", "w")
+try:
+    file.write!("data")
+catch (exc: exception):
+    print(exc)
+file.close()
+"#;
+        interpreter.interpret(code);
+        assert!(stdout.contents().starts_with("could not write to the file"));
+    }
+}
+
+#[test]
+fn interpret_math_builtins() {
+    let (mut interpreter, stdout, _) = common::interpreter();
+
+    let code = r#"
+print(sqrt(16.0))
+print(pow(2, 10))
+print(pow(-3, 3))
+print(pow(2.0, 3))
+print(floor(-2.5))
+print(ceil(2.1))
+print(round(2.5))
+print(abs(-4))
+print(abs(-1.5))
+print(min(3, 7))
+print(max(-3, 7))
+print(clamp(12, 0, 10))
+print(clamp(-0.5, 0.0, 1.0))
+print(round(PI * 100.0))
+print(ln(E))
+print(is_nan(sqrt(-1.0)))
+print(is_inf(exp(1000.0)))
+print(pow(-1, 5_000_000_001))
+try:
+    print(pow!(10, 20))
+catch (exc: exception):
+    print(exc)
+print(pow!(-2, 63) == pow(-2, 63))
+
+# the safe functions saturate instead of raising
+fn bounds() -> str:
+    let ints = f"{pow(10, 20)} {pow(-10, 19)} {pow(-10, 20)} {abs(pow(-2, 63))}"
+    return ints + f" {floor(exp(1000.0))} {round(sqrt(-1.0))}"
+print(bounds())
+"#;
+    interpreter.interpret(code);
+    assert_eq!(
+        "4\n1024\n-27\n8\n-3\n3\n3\n4\n1.5\n3\n7\n10\n0\n314\n1\ntrue\ntrue\n-1\n\
+         integer overflow in `pow!()`\ntrue\n\
+         18446744073709551615 -9223372036854775808 9223372036854775807 9223372036854775807 \
+         9223372036854775807 0\n",
+        stdout.contents()
+    );
+}
+
+#[test]
+fn interpret_random_generator() {
+    let (mut interpreter, stdout, _) = common::interpreter();
+
+    let code = r#"
+let first = Random::new(42)
+let second = Random::new(42)
+let i = 0
+while i < 100:
+    let val = first.next_int(-3, 3)
+    assert(val == second.next_int(3, -3))
+    assert(val >= -3 && val <= 3)
+    let float_val = first.next_float()
+    assert(float_val == second.next_float())
+    assert(float_val >= 0.0 && float_val < 1.0)
+    i += 1
+
+let list = [1, 2, 3, 4, 5]
+first.shuffle(list)
+let total = 0
+for el in list:
+    total += el
+assert(total == 15)
+
+let choice: uint = first.choice!(list)
+assert(choice >= 1 && choice <= 5)
+
+let empty: [int] = List::new()
+try:
+    print(first.choice!(empty))
+catch (exc: exception):
+    print(exc)
+
+print(Random::new(7).next_int(0, 1000))
+"#;
+    interpreter.interpret(code);
+    assert_eq!("choosing from an empty list\n310\n", stdout.contents());
+}
+
+#[test]
+fn interpret_process_builtins() {
+    std::env::set_var("CHAL_TEST_VAR", "configured");
+
+    let (mut interpreter, stdout, _) = common::interpreter();
+    interpreter.set_argv(vec!["script.ch".to_string(), "--verbose".to_string()]);
+
+    let code = r#"
+for arg in argv():
+    print(arg)
+
+print(env_get("CHAL_TEST_VAR"))
+print(len(env_get("CHAL_UNSET_VAR")))
+try:
+    print(env_get!("CHAL_UNSET_VAR"))
+catch (exc: exception):
+    print(exc)
+
+let start = time_ms()
+sleep(5)
+assert(time_ms() >= start + 5)
+"#;
+    interpreter.interpret(code);
+    assert_eq!(
+        "script.ch\n--verbose\nconfigured\n0\nthe environment variable `CHAL_UNSET_VAR` is not set\n",
+        stdout.contents()
+    );
+    assert_eq!(None, interpreter.exit_code());
+
+    /* `exit()` stops the script, but not the host */
+    let code = r#"
+fn bye(code: int):
+    print("bye")
+    exit(code)
+    print("unreachable")
+
+bye(2)
+print("unreachable")
+"#;
+    interpreter.interpret(code);
+    assert_eq!(Some(2), interpreter.exit_code());
+    assert_eq!(
+        Err(ScriptError::Exit(3)),
+        interpreter.call("bye", &[Value::Int(3)])
+    );
+    interpreter.interpret("print(\"next\")\n");
+    assert_eq!(None, interpreter.exit_code());
+    assert!(stdout.contents().ends_with("bye\nbye\nnext\n"));
+
+    /* the codes, which do not fit into `i32`, are clamped */
+    interpreter.interpret("exit(-5_000_000_000)\n");
+    assert_eq!(Some(i32::MIN), interpreter.exit_code());
+}
+
+#[test]
+fn interpret_json() {
+    let (mut interpreter, stdout, _) = common::interpreter();
+
+    let code = r#"
+class Point:
+    x: int
+    y: float
+
+class Shape:
+    name: str
+    points: [Point]
+    closed: bool
+
+let src = '{"name": "tri", "id": [1, null], "closed": true, "points": [{"x": -1, "y": 2.5}, {"y": 1, "x": 3}]}'
+let shape: Shape = json_parse!(src)
+print(shape.name)
+print(shape.points.len())
+print(json_stringify(shape))
+
+let nums: [uint] = json_parse!(" [1, 2, 3] ")
+print(json_stringify(nums))
+nums = json_parse!("[4]")
+shape.points = json_parse!('[{"x": 0, "y": 0}]')
+print(json_stringify(nums) + " " + json_stringify(shape.points))
+print(json_stringify('say "hi"'))
+
+fn load!(src: str) -> Point:
+    return json_parse!(src)
+
+try:
+    let point = load!('{"x": 1.5, "y": 0}')
+catch (exc: exception):
+    print(exc)
+
+try:
+    let other = load!('{"x": 1}')
+catch (exc: exception):
+    print(exc)
+
+# the safe conversion writes the values JSON could not represent as `null`
+fn dump(x: float) -> str:
+    return json_stringify([x, 1.0 / 0.0])
+print(dump(sqrt(-1.0)))
+
+class Tree:
+    val: int
+    children: [Tree]
+
+let tree: Tree = json_parse!('{"val": 1, "children": [{"val": 2, "children": []}]}')
+print(tree.children.len())
+tree.children.push_back(tree)
+print(json_stringify(tree))
+"#;
+    interpreter.interpret(code);
+    assert_eq!(
+        concat!(
+            "tri\n",
+            "2\n",
+            r#"{"name":"tri","points":[{"x":-1,"y":2.5},{"x":3,"y":1.0}],"closed":true}"#,
+            "\n[1,2,3]\n",
+            r#"[4] [{"x":0,"y":0.0}]"#,
+            "\n",
+            r#""say \"hi\"""#,
+            "\ninvalid JSON at position 6: expected an `int`\n",
+            "invalid JSON at position 8: missing member `y`\n",
+            "[null,null]\n",
+            "1\n",
+            r#"{"val":1,"children":[{"val":2,"children":[]},null]}"#,
+            "\n",
+        ),
+        stdout.contents()
+    );
+}
+
+#[test]
+fn interpret_formatted_strings() {
+    let (mut interpreter, stdout, _) = common::interpreter();
+
+    let code = r#"
+let name = "Ana"
+let age = 31
+let ratio = 2.0 / 3.0
+
+print(f"{name} is {age} years")
+print(f'[{name:>6}|{age:<4}|{ratio:.3}|{ratio:08.2}|{-age:05}|{name:*^9}] {{}}')
+print(format("{} + {} = {}", 1, 2, 1 + 2))
+print(format("{:.2}", "abcdef"))
+print(f"{len(name) * 2} chars, {name == 'Ana'}")
+"#;
+    interpreter.interpret(code);
+    assert_eq!(
+        concat!(
+            "Ana is 31 years\n",
+            "[   Ana|31  |0.667|00000.67|-0031|***Ana***] {}\n",
+            "1 + 2 = 3\n",
+            "ab\n",
+            "6 chars, true\n",
+        ),
+        stdout.contents()
+    );
+
+    /* user-defined overloads of `format()` are called as usual */
+    let dir = common::TempDir::new("format");
+    let script = dir.write(
+        "main.ch",
+        "fn format(x: int) -> str:\n    return f\"<{x}>\"\n\nprint(format(3))\n",
+    );
+    let (mut interpreter, stdout, stderr) = common::interpreter();
+    interpreter.interpret_script(script.to_str().unwrap().to_string());
+    assert_eq!("<3>\n", stdout.contents());
+    assert_eq!("", stderr.contents());
+}
+
+#[test]
+fn interpret_return_paths() {
+    let (mut interpreter, stdout, stderr) = common::interpreter();
+
+    let code = r#"
+fn sign(x: int) -> int:
+    if x > 0:
+        return 1
+    elif x < 0:
+        return -1
+    else:
+        return 0
+
+fn check!(x: int) -> str:
+    if x > 0:
+        return "positive"
+    throw "not positive"
+
+fn describe(x: int) -> str:
+    try:
+        return check!(x)
+    catch (_exc: exception):
+        return "failed"
+
+fn root(x: int) -> int:
+    let i = 0
+    while true:
+        if i * i >= x:
+            return i
+        i += 1
+
+print(sign(-5))
+print(describe(3))
+print(describe(0))
+print(root(17))
+"#;
+    interpreter.interpret(code);
+    assert_eq!("-1\npositive\nfailed\n5\n", stdout.contents());
+    assert_eq!("", stderr.contents());
+
+    let code = r#"
+fn partial(x: int) -> int:
+    if x > 0:
+        return 1
+    elif x < 0:
+        print("negative")
+    else:
+        return 0
+
+fn breaking(x: int) -> int:
+    while true:
+        if x > 0:
+            break
+        return 1
+"#;
+    interpreter.interpret(code);
+    let errors = stderr.contents();
+    assert_eq!(
+        2,
+        errors
+            .matches("could reach its end after this statement")
+            .count()
+    );
+    assert!(errors.contains("print(\"negative\")"));
+    assert!(errors.contains("while true:"));
+}
+
+#[test]
+fn interpret_block_scopes() {
+    let (mut interpreter, stdout, stderr) = common::interpreter();
+
+    let code = r#"
+fn classify(n: int) -> str:
+    let label: str
+    if n < 0:
+        label = "negative"
+    elif n == 0:
+        label = "zero"
+    else:
+        label = "positive"
+    return label
+
+fn shadow(n: int) -> int:
+    let x = n
+    if x > 0:
+        let x = x * 10
+        print(x)
+    if x > 1:
+        let first = 5
+        print(first)
+    else:
+        let second = "reused slot"
+        print(second)
+    for x in [7, 8]:
+        print(x)
+    return x
+
+fn parse!(n: int) -> int:
+    let res: int
+    try:
+        if n < 0:
+            throw "negative"
+        res = n
+    catch (_exc: exception):
+        return -1
+    return res
+
+print(classify(-3))
+print(classify(4))
+print(shadow(2))
+print(shadow(0))
+print(parse!(-2) + parse!(5))
+"#;
+    interpreter.interpret(code);
+    assert_eq!("", stderr.contents());
+    assert_eq!(
+        "negative\npositive\n20\n5\n7\n8\n2\nreused slot\n7\n8\n0\n4\n",
+        stdout.contents()
+    );
+
+    let code = r#"
+fn missing_else(n: int) -> int:
+    let x: int
+    if n > 0:
+        x = 1
+    return x
+
+fn loop_only(n: int) -> int:
+    let x: int
+    while n > 0:
+        x = n
+        n -= 1
+    return x
+
+fn leaked(n: int) -> int:
+    if n > 0:
+        let y = n
+        print(y)
+    return y
+
+let global_value: int
+"#;
+    interpreter.interpret(code);
+    let errors = stderr.contents();
+    assert_eq!(
+        2,
+        errors
+            .matches("the variable `x` is possibly unassigned")
+            .count()
+    );
+    assert!(errors.contains("unknown variable 'y'"));
+    assert!(errors.contains("only typed local variables could be declared without a value"));
+}
+
+#[test]
+fn interpret_mutable_globals() {
+    let (mut interpreter, stdout, stderr) = common::interpreter();
+
+    let code = r#"
+let mut calls = 0
+let mut log = "ids:"
+
+fn next_id() -> int:
+    calls += 1
+    return calls
+
+fn remember(val: str):
+    log += " " + val
+    log += f"={next_id()}"
+
+fn label(val: str) -> str:
+    return val + "=" + calls
+
+fn report():
+    remember(label("c"))
+
+remember("a")
+remember("b")
+print(calls)
+print(log)
+"#;
+    interpreter.interpret(code);
+    assert_eq!("", stderr.contents());
+    assert_eq!("2\nids: a=1 b=2\n", stdout.contents());
+
+    /* the impurity propagates to the callers, reading a global is pure */
+    assert_eq!(Ok(false), interpreter.is_pure("next_id", &[]));
+    assert_eq!(Ok(false), interpreter.is_pure("remember", &[Type::Str]));
+    assert_eq!(Ok(false), interpreter.is_pure("report", &[]));
+    assert_eq!(Ok(true), interpreter.is_pure("label", &[Type::Str]));
+
+    /* a global, which is not `let mut`, could still be modified in place */
+    let code = r#"
+let items: [int] = [-1]
+
+fn append():
+    items.push_back(-7)
+
+fn alias():
+    let other = items
+    other.push_back(-8)
+
+fn count() -> uint:
+    return len(items)
+
+fn own_list() -> uint:
+    let own = [1, 2]
+    own.push_back(3)
+    return len(own)
+
+append()
+alias()
+print(count())
+"#;
+    interpreter.interpret(code);
+    assert_eq!("", stderr.contents());
+    assert!(stdout.contents().ends_with("\n3\n"));
+    assert_eq!(Ok(false), interpreter.is_pure("append", &[]));
+    assert_eq!(Ok(false), interpreter.is_pure("alias", &[]));
+    assert_eq!(Ok(false), interpreter.is_pure("count", &[]));
+    assert_eq!(Ok(true), interpreter.is_pure("own_list", &[]));
+    assert_eq!(
+        Err(ScriptError::UnknownFunction("missing".to_string())),
+        interpreter.is_pure("missing", &[])
+    );
+
+    let code = r#"
+let frozen = 0
+
+fn mutate():
+    frozen = 1
+
+fn local():
+    let mut val = 3
+"#;
+    interpreter.interpret(code);
+    let errors = stderr.contents();
+    assert!(errors.contains("functions could mutate only the global variables"));
+    assert!(errors.contains("only global variables could be declared with `let mut`"));
+}
+
+#[test]
+fn interpret_constant_folding() {
+    let (mut interpreter, stdout, stderr) = common::interpreter();
+
+    let code = r#"
+const WIDTH = 4 * 10 + 2
+const HALF: float = WIDTH / 4
+const TAU = 2.0 * PI
+const GREETING = "width=" + WIDTH
+const ROW = "{:>" + "6}|"
+
+fn area(height: uint) -> uint:
+    return WIDTH * height * (WIDTH - 40)
+
+print(WIDTH)
+print(HALF)
+print(TAU == 2.0 * PI)
+print(GREETING)
+print(format(ROW, WIDTH))
+print(area(3))
+print(-(7 % 4) + 0.5)
+"#;
+    interpreter.interpret(code);
+    assert_eq!("", stderr.contents());
+    assert_eq!(
+        "42\n10\ntrue\nwidth=42\n    42|\n252\n-3\n",
+        stdout.contents()
+    );
+
+    let code = r#"
+const ZERO = 10 - 10
+const BROKEN = 5 / ZERO
+const UNDERFLOW = ZERO - 1
+let huge = 18446744073709551615 + 1
+"#;
+    interpreter.interpret(code);
+    let errors = stderr.contents();
+    assert!(errors.contains("division by zero in a constant expression"));
+    assert_eq!(
+        2,
+        errors
+            .matches("the constant expression overflows its type")
+            .count()
+    );
+}
+
+#[test]
+fn interpret_typed_operators() {
+    let (mut interpreter, stdout, stderr) = common::interpreter();
+
+    let code = r#"
+fn collatz(n: uint) -> uint:
+    let steps = 0
+    while n != 1:
+        if n % 2 == 0:
+            n = n / 2
+        else:
+            n = 3 * n + 1
+        steps += 1
+    return steps
+
+let x: int = -7
+let y: float = 2.5
+print(collatz(27))
+print(x / 2 + x % 3)
+print(x < 3 && x >= -7)
+print(y * 2 - x)
+print(y > 2 && y <= 2.5)
+print("steps: " + collatz(6) + ", " + y)
+"#;
+    interpreter.interpret(code);
+    assert_eq!("", stderr.contents());
+    assert_eq!(
+        "111\n-4\ntrue\n12\ntrue\nsteps: 8, 2.5\n",
+        stdout.contents()
+    );
+
+    /* the specialized operators fall back to the generic ones */
+    let mut vm = Cvm::new();
+    vm.execute(vec![
+        Bytecode::ConstU(40),
+        Bytecode::ConstI(2),
+        Bytecode::AddI,
+        Bytecode::ConstI(42),
+        Bytecode::EqI,
+        Bytecode::Assert,
+        Bytecode::ConstF(1.5),
+        Bytecode::ConstU(3),
+        Bytecode::LtF,
+        Bytecode::Assert,
+    ]);
+}
+
+#[test]
+fn interpret_string_constants() {
+    let (mut interpreter, stdout, stderr) = common::interpreter();
+
+    /* the pooled constants are copied, so modifying them has no effect on
+     * the next iterations */
+    let code = r#"
+fn greet(name: str) -> str:
+    let res = "hi "
+    res += name
+    return res
+
+let i = 0
+while i < 3:
+    print(greet("x" * (i + 1)))
+    i += 1
+"#;
+    interpreter.interpret(code);
+    assert_eq!("", stderr.contents());
+    assert_eq!("hi x\nhi xx\nhi xxx\n", stdout.contents());
+}
+
+#[test]
+fn interpret_tail_calls() {
+    let (mut interpreter, stdout, stderr) = common::interpreter();
+
+    let code = r#"
+fn finish(acc: uint) -> uint:
+    return acc * 2
+
+fn sum(n: uint, acc: uint) -> uint:
+    if n == 0:
+        return finish(acc)
+    let next = n - 1
+    return sum(next, acc + n)
+
+fn describe(prefix: str, n: uint) -> str:
+    return prefix + n
+
+fn describe(n: uint) -> str:
+    return describe("n = ", n)
+
+fn fail!(n: uint) -> uint:
+    if n == 0:
+        throw "reached the bottom"
+    return fail!(n - 1)
+
+fn guarded() -> uint:
+    try:
+        return fail!(3)
+    catch (_exc: exception):
+        return 42
+
+print(sum(100000, 0))
+print(describe(7))
+print(guarded())
+try:
+    print(fail!(5))
+catch (exc: exception):
+    print(exc)
+"#;
+    interpreter.interpret(code);
+    assert_eq!("", stderr.contents());
+    assert_eq!(
+        "10000100000\nn = 7\n42\nreached the bottom\n",
+        stdout.contents()
+    );
+}
+
+#[test]
+fn interpret_recursion_limits() {
+    let (mut interpreter, stdout, stderr) = common::interpreter();
+    interpreter.set_recursion_limit(100);
+    interpreter.set_stack_limit(200);
+
+    let code = r#"
+fn depth(n: uint) -> uint:
+    if n == 0:
+        return 0
+    return depth(n - 1) + 1
+
+fn wide(n: uint, a: uint, b: uint, c: uint) -> uint:
+    if n == 0:
+        return a + b + c
+    return wide(n - 1, a, b, c) + 1
+
+fn countdown(n: uint) -> uint:
+    if n == 0:
+        return 0
+    return countdown(n - 1)
+
+# chal: allow(redundant_try)
+try:
+    print(depth(50))
+    print(depth(1000))
+catch (exc: exception):
+    print(exc)
+
+# chal: allow(redundant_try)
+try:
+    print(wide(80, 1, 2, 3))
+catch (exc: exception):
+    print(exc)
+
+print(depth(99))
+print(countdown(100000))
+"#;
+    interpreter.interpret(code);
+    assert_eq!("", stderr.contents());
+    assert_eq!(
+        "50\nmaximum recursion depth exceeded\nmaximum stack size exceeded\n99\n0\n",
+        stdout.contents()
+    );
+}
+
+#[test]
+fn interpret_uncaught_recursion() {
+    let (mut interpreter, stdout, stderr) = common::interpreter();
+    interpreter.set_recursion_limit(100);
+
+    let code = r#"
+fn boom(n: int) -> int:
+    return 1 + boom(n + 1)
+
+print("before")
+print(boom(0))
+print("after")
+"#;
+    /* the unhandled exception stops the script, not the host */
+    interpreter.interpret(code);
+    assert!(interpreter.has_failed());
+    assert!(stderr
+        .contents()
+        .contains("maximum recursion depth exceeded"));
+    assert_eq!("before\n", stdout.contents());
+
+    assert_eq!(
+        Err(ScriptError::Exception(
+            "maximum recursion depth exceeded".to_string()
+        )),
+        interpreter.call("boom", &[Value::Int(0)])
+    );
+
+    interpreter.interpret("print(\"still running\")");
+    assert!(!interpreter.has_failed());
+    assert_eq!("before\nstill running\n", stdout.contents());
+}
+
+#[test]
+fn interpret_garbage_collection() {
+    let (mut interpreter, stdout, stderr) = common::interpreter();
+
+    let code = r#"
+class Node:
+    val: int
+    links: [Node]
+
+fn cycle(val: int) -> Node:
+    let first = Node{val: val, links: List::new()}
+    let second = Node{val: val + 1, links: [first]}
+    first.links.push_back(second)
+    return first
+
+let kept = cycle(10)
+let dropped = cycle(20).val
+print(gc_collect())
+print(gc_collect())
+print(kept)
+
+let mut sum: int = 0
+let mut i = 0
+while i < 20000:
+    sum += cycle(i).val
+    i += 1
+print(sum)
+print(gc_collect() > 0)
+print(gc_stats())
+"#;
+    interpreter.interpret(code);
+    assert_eq!("", stderr.contents());
+    let output = stdout.contents();
+    let (output, stats) = output.trim_end().rsplit_once('\n').unwrap();
+    assert_eq!(
+        "2\n0\n{10, [{11, [{...}, \x08\x08], \x08\x08}, \x08\x08], \x08\x08}\n199990000\ntrue",
+        output
+    );
+    /* the number of automatic collections depends on the heap's threshold */
+    assert!(stats.starts_with("live objects: 2, "), "{}", stats);
+    assert!(stats.ends_with(", freed objects: 40002"), "{}", stats);
+}
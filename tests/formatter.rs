@@ -0,0 +1,106 @@
+use chalcedony::formatter::Formatter;
+
+use std::fs;
+
+#[test]
+fn format_script() {
+    let code = r#"#!/usr/bin/chal
+import "other.ch"   # trailing comment
+from std::math import sqrt,   pow
+const   LIMIT:int=0xFF
+
+
+class Point:
+    x: int
+    y: int
+
+    fn new(x: int, y: int) -> Point:
+        return Point{x:x,y}
+    fn dist(self: Point, other: Point) -> float:
+        return sqrt(itof((self.x-other.x)*(self.x-other.x) + (self.y - other.y) * (self.y-other.y)))
+
+fn main():
+	let a = -(-5)
+	let b = ((1+2))*3 - (4-(5-6))
+	let c = !(a == 5) && (b>2 || false)
+	let s = f"{a} and {b:>5}"
+	let l = [ (-a), 2,3 ]
+	if a>0:
+	    print(r'C:\new')
+	    # end of the if body
+	else:   # else comment
+	    print('\t')
+
+	try:
+	    throw "oops"
+	catch(e:exception):
+	    print(e)
+	let very_long_variable_name = some_function_name(first_argument_value, second_argument_value, third)
+	Builder::new() \
+	    .first() \
+	    .second()
+# end
+"#;
+
+    let expected = r#"#!/usr/bin/chal
+import "other.ch"  # trailing comment
+from std::math import sqrt, pow
+const LIMIT: int = 0xFF
+
+class Point:
+    x: int
+    y: int
+
+    fn new(x: int, y: int) -> Point:
+        return Point {x, y}
+    fn dist(self, other: Point) -> float:
+        return sqrt(
+            itof((self.x - other.x) * (self.x - other.x) + (self.y - other.y) * (self.y - other.y))
+        )
+
+fn main():
+    let a = -(-5)
+    let b = (1 + 2) * 3 - (4 - (5 - 6))
+    let c = !(a == 5) && (b > 2 || false)
+    let s = f"{a} and {b:>5}"
+    let l = [(-a), 2, 3]
+    if a > 0:
+        print(r'C:\new')
+        # end of the if body
+    else:  # else comment
+        print('\t')
+
+    try:
+        throw "oops"
+    catch (e: exception):
+        print(e)
+    let very_long_variable_name = some_function_name(
+        first_argument_value,
+        second_argument_value,
+        third
+    )
+    Builder::new().first().second()
+# end
+"#;
+
+    let formatted = Formatter::format(code, None).expect("expected a valid script");
+    assert_eq!(formatted, expected);
+    assert_eq!(Formatter::format(&formatted, None).unwrap(), formatted);
+
+    assert!(Formatter::format("let a = (1 + 2\n", None).is_err());
+}
+
+#[test]
+fn format_examples() {
+    for entry in fs::read_dir("examples").expect("expected the examples directory") {
+        let path = entry.unwrap().path();
+        let code = fs::read_to_string(&path).unwrap();
+        let formatted = Formatter::format(&code, None).expect("expected a valid script");
+        assert_eq!(
+            Formatter::format(&formatted, None).unwrap(),
+            formatted,
+            "formatting `{}` is not idempotent",
+            path.display()
+        );
+    }
+}
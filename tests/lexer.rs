@@ -1,5 +1,8 @@
 use chalcedony::common::Type;
-use chalcedony::lexer::{Delimiter, Keyword, Lexer, Line, Operator, Special, TokenKind};
+use chalcedony::lexer::{
+    Delimiter, FmtStrPart, Keyword, Lexer, Line, LosslessToken, Operator, Special, TokenKind,
+    TriviaKind,
+};
 
 use std::collections::VecDeque;
 use std::iter::zip;
@@ -344,3 +347,146 @@ catch (exc: exception):
     assert_chunks(expected, &mut lexer);
     assert!(lexer.is_empty());
 }
+
+#[test]
+fn lex_fmt_string() {
+    let code = "f'{name:>6} has {{}} {-n + 1}'";
+    let mut lexer = Lexer::new(code, None);
+
+    let tok = lexer.advance().expect("expected an ok token");
+    let TokenKind::FmtStr(parts) = tok.kind else {
+        panic!("expected a formatted string")
+    };
+
+    let expected: Vec<(Option<Vec<TokenKind>>, &str)> = vec![
+        (Some(vec![TokenKind::Identifier("name".to_string())]), ">6"),
+        (None, " has {} "),
+        (
+            Some(vec![
+                TokenKind::Operator(Operator::Neg),
+                TokenKind::Identifier("n".to_string()),
+                TokenKind::Operator(Operator::Add),
+                TokenKind::Uint(1),
+            ]),
+            "",
+        ),
+    ];
+    assert_eq!(parts.len(), expected.len());
+
+    for (part, (exp_tokens, exp_src)) in zip(parts, expected) {
+        match (part, exp_tokens) {
+            (FmtStrPart::Str(text), None) => assert_eq!(text, exp_src),
+            (FmtStrPart::Expr { tokens, spec, .. }, Some(exp_tokens)) => {
+                let kinds: Vec<TokenKind> = tokens.into_iter().map(|tok| tok.kind).collect();
+                assert_eq!(kinds, exp_tokens);
+                assert_eq!(spec, exp_src);
+            }
+            (part, _) => panic!("unexpected formatted string part {:?}", part),
+        }
+    }
+
+    assert_eq!(
+        lexer.advance().expect("expected an ok token").kind,
+        TokenKind::Newline
+    );
+    assert!(lexer.is_empty())
+}
+
+#[test]
+fn lex_literals() {
+    let code = r#"-0o17 0xFf 0b1010_0101 1_000 2.5e3 1E-2 "a\tb\n\"c\"\\\u{e9}" r'C:\new' """multi
+line""""#;
+    let expected = vec![
+        TokenKind::Int(-15),
+        TokenKind::Uint(255),
+        TokenKind::Uint(165),
+        TokenKind::Uint(1000),
+        TokenKind::Float(2500.0),
+        TokenKind::Float(0.01),
+        TokenKind::Str("a\tb\n\"c\"\\é".to_string()),
+        TokenKind::Str("C:\\new".to_string()),
+        TokenKind::Str("multi\nline".to_string()),
+        TokenKind::Newline,
+    ];
+
+    let mut lexer = Lexer::new(code, None);
+    for exp in expected {
+        let tok = lexer.advance().expect("expected an ok token");
+        assert_eq!(tok.kind, exp);
+    }
+    assert!(lexer.is_empty());
+
+    let invalid = [
+        "'\\q'",
+        "'\\u{110000}'",
+        "'unclosed",
+        "0x",
+        "0b102",
+        "0o8",
+        "18446744073709551616",
+        "1.2.3",
+    ];
+    for code in invalid {
+        let mut lexer = Lexer::new(code, None);
+        assert!(lexer.advance().is_err(), "`{}` should not be lexed", code);
+    }
+}
+
+#[test]
+fn lex_lossless() {
+    let code = "# the docs of main\nfn main(): \\\n\t# inline\n  let a = [1,  # first\n    2] # trailing\n\n\tprint(f'{a} é')  \n# end";
+
+    let mut result = String::new();
+    let mut tokens = Vec::<LosslessToken>::new();
+    for tok in Lexer::lossless(code, None) {
+        let tok = tok.expect("expected an ok token");
+        for trivia in &tok.leading {
+            assert_eq!(&code[trivia.range.clone()], trivia.text);
+            result += &trivia.text;
+        }
+        assert_eq!(&code[tok.range.clone()], tok.text);
+        result += &tok.text;
+        tokens.push(tok);
+    }
+    assert_eq!(result, code);
+
+    /* the leading comment and its newline are trivia of the first token */
+    let front = tokens.first().unwrap();
+    assert_eq!(
+        front.token.as_ref().unwrap().kind,
+        TokenKind::Keyword(Keyword::Fn)
+    );
+    let kinds: Vec<TriviaKind> = front.leading.iter().map(|trivia| trivia.kind).collect();
+    assert_eq!(kinds, vec![TriviaKind::Comment, TriviaKind::Newline]);
+    assert_eq!(front.leading[0].text, "# the docs of main");
+
+    /* the line continuation, the comments and the newlines inside the brackets */
+    let kinds: Vec<TriviaKind> = tokens
+        .iter()
+        .flat_map(|tok| tok.leading.iter().map(|trivia| trivia.kind))
+        .collect();
+    assert!(kinds.contains(&TriviaKind::LineContinuation));
+    let newlines = tokens
+        .iter()
+        .filter(|tok| {
+            tok.token
+                .as_ref()
+                .is_some_and(|tok| tok.kind == TokenKind::Newline)
+        })
+        .count();
+    assert_eq!(newlines, 3);
+
+    /* the comment at the end is kept without a token */
+    let back = tokens.last().unwrap();
+    assert!(back.token.is_none());
+    assert_eq!(back.leading.last().unwrap().text, "# end");
+
+    let invalid = ["a \\ b", "let a = (1,\n2"];
+    for code in invalid {
+        assert!(
+            Lexer::lossless(code, None).any(|tok| tok.is_err()),
+            "`{}` should not be lexed",
+            code
+        );
+    }
+}
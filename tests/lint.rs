@@ -0,0 +1,149 @@
+mod common;
+
+fn interpret_with_warnings(code: &str, deny: bool) -> (String, String) {
+    let (mut interpreter, stdout, stderr) = common::interpreter();
+    interpreter.set_deny_warnings(deny);
+    interpreter.interpret(code);
+    (stdout.contents(), stderr.contents())
+}
+
+#[test]
+fn lint_warnings() {
+    let code = r#"
+let total = 0
+
+fn compute(a: int, b: int) -> int:
+    let unused = 3
+    let total = a
+    return total
+    print("after return")
+
+fn guard!() -> int:
+    try:
+        print("nothing unsafe")
+    catch (exc: exception):
+        print(exc)
+    if 1 < 2:
+        print("constant")
+    while true:
+        break
+    return 1
+
+print(compute(1, 2))
+"#;
+    let (stdout, stderr) = interpret_with_warnings(code, false);
+    assert_eq!("1\n", stdout);
+
+    for lint in [
+        "unused variable `unused` [unused_variable]",
+        "unused function argument `b` [unused_argument]",
+        "the local variable `total` shadows a global variable [shadowed_variable]",
+        "unreachable code [unreachable_code]",
+        "the try block contains no unsafe operations [redundant_try]",
+        "the condition is always the same [constant_condition]",
+    ] {
+        assert!(stderr.contains(lint), "missing `{}` in:\n{}", lint, stderr);
+    }
+    assert_eq!(6, stderr.matches("warning").count());
+}
+
+#[test]
+fn lint_unused_argument_span() {
+    let code = "fn compute(a: int, b: int) -> int:\n    return a\nprint(compute(1, 2))\n";
+    let (stdout, stderr) = interpret_with_warnings(code, false);
+    assert_eq!("1\n", stdout);
+    assert!(stderr.contains("unused function argument `b`"));
+    /* only `b: int` is underlined, not the whole signature */
+    assert_eq!(6, stderr.matches('^').count(), "{}", stderr);
+}
+
+#[test]
+fn lint_suppression() {
+    let code = r##"
+fn compute(a: int, b: int) -> int: # chal: allow(unused_argument)
+    # chal: allow(unused_variable, constant_condition)
+    let unused = "# chal: allow(unreachable_code)"
+    return a
+    print('#') # chal: allow(unreachable_code)
+
+print(compute(1, 2))
+"##;
+    let (stdout, stderr) = interpret_with_warnings(code, false);
+    assert_eq!("1\n", stdout);
+    assert_eq!("", stderr);
+}
+
+#[test]
+fn lint_deny_warnings() {
+    let code = r#"
+fn compute(a: int) -> int:
+    let unused = 3
+    return a
+
+print(compute(1))
+"#;
+    let (stdout, stderr) = interpret_with_warnings(code, true);
+    assert_eq!("", stdout);
+    assert!(stderr.contains("error"));
+    assert!(stderr.contains("unused variable `unused`"));
+}
+
+#[test]
+fn lint_unused_definitions() {
+    let dir = common::TempDir::new("lint");
+    let utils = "fn double(x: int) -> int:\n    return x * 2\n\nlet FACTOR = 3\n";
+    dir.write("utils.ch", utils);
+    dir.write(
+        "helpers.ch",
+        "fn triple(x: int) -> int:\n    return x * 3\n",
+    );
+
+    let main = r#"
+import "helpers.ch" as helpers
+from "utils.ch" import FACTOR
+import std::math
+
+fn unused(n: int) -> int:
+    return unused(n)
+
+fn _helper() -> int:
+    return 1
+
+fn used() -> int:
+    return FACTOR
+
+print(used())
+"#;
+    let main_path = dir.write("main.ch", main);
+
+    let (mut interpreter, stdout, stderr) = common::interpreter();
+    interpreter.interpret_script(main_path.to_str().unwrap().to_string());
+
+    let stderr = stderr.contents();
+    assert_eq!("3\n", stdout.contents());
+    assert!(stderr.contains("unused function `unused`"));
+    assert!(stderr.contains("unused import `helpers.ch`"));
+    assert!(stderr.contains("unused import `std::math`"));
+    assert_eq!(3, stderr.matches("warning").count(), "{}", stderr);
+    assert!(!interpreter.has_failed());
+}
+
+#[test]
+fn lint_examples() {
+    let examples = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("examples");
+    let mut scripts: Vec<_> = std::fs::read_dir(examples)
+        .expect("could not read the examples")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "ch"))
+        .collect();
+    scripts.sort();
+    assert!(!scripts.is_empty());
+
+    for script in scripts {
+        let (mut interpreter, _, stderr) = common::interpreter();
+        interpreter.set_deny_warnings(true);
+        interpreter.interpret_script(script.to_str().unwrap().to_string());
+        assert_eq!("", stderr.contents(), "in {}", script.display());
+        assert!(!interpreter.has_failed(), "in {}", script.display());
+    }
+}
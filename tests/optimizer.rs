@@ -0,0 +1,107 @@
+mod common;
+
+use chalcedony::common::Bytecode;
+use chalcedony::interpreter::{optimize, OptLevel};
+
+use std::fs;
+
+#[test]
+fn optimize_constants_and_dead_code() {
+    let code = vec![
+        Bytecode::ConstU(2),
+        Bytecode::ConstU(3),
+        Bytecode::Add,
+        Bytecode::CastF,
+        Bytecode::SetGlobal(0),
+        Bytecode::Jmp(2),
+        Bytecode::ConstS("unreachable".to_string().into()),
+        Bytecode::Print,
+        Bytecode::Nop,
+        Bytecode::GetGlobal(1),
+        Bytecode::Pop,
+        Bytecode::GetGlobal(0),
+        Bytecode::Print,
+    ];
+    let exp = vec![
+        Bytecode::ConstF(5.0),
+        Bytecode::SetGlobal(0),
+        Bytecode::GetGlobal(0),
+        Bytecode::Print,
+    ];
+    assert_eq!(exp, optimize(code));
+}
+
+#[test]
+fn optimize_jump_offsets() {
+    let func = vec![
+        Bytecode::CreateFunc(1),
+        Bytecode::GetLocal(0),
+        Bytecode::If(3),
+        Bytecode::ConstI(1),
+        Bytecode::Return,
+        Bytecode::ReturnVoid,
+        Bytecode::Nop,
+        Bytecode::ConstI(2),
+        Bytecode::Neg,
+        Bytecode::Return,
+    ];
+    let exp = vec![
+        Bytecode::CreateFunc(1),
+        Bytecode::GetLocal(0),
+        Bytecode::If(2),
+        Bytecode::ConstI(1),
+        Bytecode::Return,
+        Bytecode::ConstI(-2),
+        Bytecode::Return,
+    ];
+    assert_eq!(exp, optimize(func));
+
+    let try_catch = vec![
+        Bytecode::TryScope(3),
+        Bytecode::Nop,
+        Bytecode::CallFunc(0),
+        Bytecode::CatchJmp(2),
+        Bytecode::Pop,
+        Bytecode::Nop,
+        Bytecode::GetGlobal(0),
+        Bytecode::Print,
+    ];
+    let exp = vec![
+        Bytecode::TryScope(2),
+        Bytecode::CallFunc(0),
+        Bytecode::CatchJmp(1),
+        Bytecode::Pop,
+        Bytecode::GetGlobal(0),
+        Bytecode::Print,
+    ];
+    assert_eq!(exp, optimize(try_catch));
+}
+
+fn run_script(path: &str, level: OptLevel) -> String {
+    let (mut interpreter, stdout, stderr) = common::interpreter();
+    interpreter.set_opt_level(level);
+    interpreter.interpret_script(path.to_string());
+    stdout.contents() + &stderr.contents()
+}
+
+#[test]
+fn optimize_examples() {
+    let mut examples: Vec<String> = fs::read_dir("examples")
+        .expect("expected the examples directory")
+        .map(|entry| entry.unwrap().path().to_string_lossy().to_string())
+        .filter(|path| path.ends_with(".ch"))
+        .collect();
+    examples.sort();
+    assert!(!examples.is_empty());
+
+    for example in examples {
+        let unoptimized = run_script(&example, OptLevel::O0);
+        assert!(!unoptimized.is_empty(), "{} has no output", example);
+        assert_eq!(
+            unoptimized,
+            run_script(&example, OptLevel::O1),
+            "{} differs when optimized",
+            example
+        );
+    }
+}
@@ -3,10 +3,11 @@ use chalcedony::common::Type;
 
 use chalcedony::lexer::{Delimiter, Keyword, Operator, Special, TokenKind};
 use chalcedony::parser::ast::{
-    class::Member, func::Arg, NodeAttrRes, NodeAttribute, NodeBreakStmnt, NodeClass, NodeContStmnt,
-    NodeElifStmnt, NodeElseStmnt, NodeExpr, NodeExprInner, NodeFuncCall, NodeFuncCallStmnt,
-    NodeFuncDef, NodeIfBranch, NodeIfStmnt, NodeInlineClass, NodeList, NodeRetStmnt, NodeStmnt,
-    NodeThrow, NodeTryCatch, NodeValue, NodeVarCall, NodeVarDef, NodeWhileLoop,
+    class::Member, func::Arg, ImportKind, NodeAttrRes, NodeAttribute, NodeBreakStmnt, NodeClass,
+    NodeContStmnt, NodeElifStmnt, NodeElseStmnt, NodeExpr, NodeExprInner, NodeFuncCall,
+    NodeFuncCallStmnt, NodeFuncDef, NodeIfBranch, NodeIfStmnt, NodeImport, NodeInlineClass,
+    NodeList, NodeRetStmnt, NodeStmnt, NodeThrow, NodeTryCatch, NodeValue, NodeVarCall, NodeVarDef,
+    NodeWhileLoop,
 };
 
 use chalcedony::mocks::{hash_map, line, line_reader, token_reader, vecdeq, SpanMock};
@@ -60,6 +61,7 @@ fn parse_var_def() {
             span: SpanMock::new(),
         },
         is_const: false,
+        is_mut: false,
         span: SpanMock::new(),
     };
 
@@ -124,6 +126,7 @@ fn parse_func_def() {
         args: vecdeq![Arg {
             name: "n".to_string(),
             ty: Type::Int,
+            span: SpanMock::new(),
         }],
         namespace: None,
         ret_type: Type::Uint,
@@ -562,6 +565,7 @@ fn parse_list() {
         name: "a".to_string(),
         ty: Type::Any,
         is_const: false,
+        is_mut: false,
         value: NodeExpr {
             expr: vecdeq![
                 NodeExprInner::List(NodeList {
@@ -691,7 +695,8 @@ fn parse_class_def() {
                 namespace: Some("Example".to_string()),
                 args: vecdeq![Arg {
                     name: "val".to_string(),
-                    ty: Type::Uint
+                    ty: Type::Uint,
+                    span: SpanMock::new(),
                 }],
                 body: vec![NodeStmnt::RetStmnt(NodeRetStmnt {
                     value: NodeExpr {
@@ -725,7 +730,8 @@ fn parse_class_def() {
                 namespace: Some("Example".to_string()),
                 args: vecdeq![Arg {
                     name: "self".to_string(),
-                    ty: Type::Custom(Box::new("Example".to_string()))
+                    ty: Type::Custom(Box::new("Example".to_string())),
+                    span: SpanMock::new(),
                 }],
                 body: vec![NodeStmnt::RetStmnt(NodeRetStmnt {
                     value: NodeExpr {
@@ -765,3 +771,68 @@ fn parse_class_def() {
 
     assert_eq!(exp, recv);
 }
+
+#[test]
+fn parse_import() {
+    // equivalent to the code:
+    // ```
+    // import "geometry.ch" as geo
+    // from "utils" import fib, MAX_DEPTH
+    // import std::math
+    // ```
+    let namespaced = token_reader!(
+        TokenKind::Keyword(Keyword::Import),
+        TokenKind::Str("geometry.ch".to_string()),
+        TokenKind::Identifier("as".to_string()),
+        TokenKind::Identifier("geo".to_string())
+    );
+    let selective = token_reader!(
+        TokenKind::Identifier("from".to_string()),
+        TokenKind::Str("utils".to_string()),
+        TokenKind::Keyword(Keyword::Import),
+        TokenKind::Identifier("fib".to_string()),
+        TokenKind::Special(Special::Comma),
+        TokenKind::Identifier("MAX_DEPTH".to_string())
+    );
+
+    let std_module = token_reader!(
+        TokenKind::Keyword(Keyword::Import),
+        TokenKind::Identifier("std".to_string()),
+        TokenKind::Special(Special::Resolution),
+        TokenKind::Identifier("math".to_string())
+    );
+
+    let recv_namespaced = NodeImport::new(namespaced).expect("did not parse NodeImport");
+    let recv_selective = NodeImport::new(selective).expect("did not parse NodeImport");
+    let recv_std_module = NodeImport::new(std_module).expect("did not parse NodeImport");
+
+    let exp_namespaced = NodeImport {
+        path: "geometry.ch".to_string(),
+        kind: ImportKind::Namespaced("geo".to_string()),
+        span: SpanMock::new(),
+    };
+    let exp_selective = NodeImport {
+        path: "utils".to_string(),
+        kind: ImportKind::Selective(vec![
+            NodeVarCall {
+                name: "fib".to_string(),
+                span: SpanMock::new(),
+            },
+            NodeVarCall {
+                name: "MAX_DEPTH".to_string(),
+                span: SpanMock::new(),
+            },
+        ]),
+        span: SpanMock::new(),
+    };
+
+    assert_eq!(exp_namespaced, recv_namespaced);
+    let exp_std_module = NodeImport {
+        path: "std::math".to_string(),
+        kind: ImportKind::Global,
+        span: SpanMock::new(),
+    };
+
+    assert_eq!(exp_selective, recv_selective);
+    assert_eq!(exp_std_module, recv_std_module);
+}
@@ -6,7 +6,8 @@ syntax keyword keywords fn return let if elif else while continue break try catc
 syntax keyword type uint int float str bool void exception
 syntax keyword boolean true false
 
-syntax keyword builtin print assert utoi ftoi itou ftou itof utof self
+syntax keyword builtin print input exists exit argv env_get time_ms sleep json_stringify format assert utoi ftoi itou ftou itof utof self
+syntax keyword builtin sqrt pow exp ln log10 sin cos tan asin acos atan floor ceil round abs min max clamp is_nan is_inf PI E
 
 syntax match comment '#.*$'
 syntax region string start='"' end='"'